@@ -1 +1,662 @@
+//! Crate-level error type. Every module defines its own error type, all of them can be converted
+//! into [Error] so that data can be parsed through several layers with `?`. The conversion
+//! classifies the module error by [ErrorKind] and records the protocol [Layer] and the path of
+//! the failing field. The original error is kept as [Source].
+//!
+//! The byte offset is filled in where the module error knows it: the end of the data for
+//! [ErrorKind::NotEnoughData], the position of fixed header fields such as version or length
+//! fields and the offset of the failing packet for [rtcp::validate_compound]. It is relative to
+//! the data passed to the failing parser, the caller adds the start of that data within the
+//! enclosing data with [Error::at_offset].
 
+use core::fmt::Display;
+
+use crate::{gre, ip, rist, rtcp, rtp, ts, udp};
+
+/// Protocol layer an error originated from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    Ip,
+    Ipv4,
+    Ipv6,
+    Udp,
+    Gre,
+    Rtp,
+    Rtcp,
+    Ts,
+    Rist,
+    /// A header generated with [rist_rs_macros::PacketView], the layer is not known
+    Header,
+}
+
+/// Classification of an error independent of the protocol layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Not enough data was supplied to read a field
+    NotEnoughData { need: usize, got: usize },
+
+    /// The buffer supplied for writing is too small
+    NotEnoughSpace { need: usize, got: usize },
+
+    /// A length field or the length of the data is inconsistent with the packet
+    InvalidLength,
+
+    /// A field holds a value that is invalid or does not fit into the field
+    InvalidValue,
+
+    /// The padding of a packet is invalid
+    InvalidPadding,
+
+    /// A text field is not valid utf8
+    InvalidUtf8,
+
+    /// The packet uses a type, format or version that is not supported
+    Unsupported,
+
+    /// The packet is fragmented, can not be fragmented or a fragment is inconsistent
+    Fragmentation,
+
+    /// A limit on the size or number of elements was exceeded
+    LimitExceeded,
+
+    /// Any other error, holds a description
+    Other(&'static str),
+}
+
+/// The module error an [Error] was converted from
+#[derive(Debug, Clone, Copy)]
+pub enum Source {
+    Ip(ip::error::Error),
+    Udp(udp::datagram::error::Error),
+    UdpReduced(udp::reduced::error::Error),
+    Gre(gre::error::Error),
+    Rtp(rtp::error::Error),
+    RistExtension(rtp::ext::rist::Error),
+    Rtcp(rtcp::error::Error),
+    Ts(ts::error::Error),
+    KeepAlive(rist::keep_alive::error::Error),
+    Npd(rist::npd::error::Error),
+    Header(rist_rs_macros::packet::error::Error),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Error {
+    kind: ErrorKind,
+    layer: Layer,
+    field: Option<&'static str>,
+    offset: Option<usize>,
+    module_error: Source,
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ErrorKind::NotEnoughData { need, got } => {
+                write!(f, "not enough data, need {need} bytes, got {got} bytes")
+            }
+            ErrorKind::NotEnoughSpace { need, got } => {
+                write!(f, "not enough space, need {need} bytes, got {got} bytes")
+            }
+            ErrorKind::InvalidLength => write!(f, "invalid length"),
+            ErrorKind::InvalidValue => write!(f, "invalid value"),
+            ErrorKind::InvalidPadding => write!(f, "invalid padding"),
+            ErrorKind::InvalidUtf8 => write!(f, "invalid utf8"),
+            ErrorKind::Unsupported => write!(f, "unsupported"),
+            ErrorKind::Fragmentation => write!(f, "fragmentation error"),
+            ErrorKind::LimitExceeded => write!(f, "limit exceeded"),
+            ErrorKind::Other(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}: {}", self.layer, self.kind)?;
+        if let Some(field) = self.field {
+            write!(f, " in field [{field}]")?;
+        }
+        if let Some(offset) = self.offset {
+            write!(f, " at offset {offset}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl Error {
+    /// Make a new error. The offset of [ErrorKind::NotEnoughData] is the end of the data, where
+    /// the missing bytes would have started
+    fn new(kind: ErrorKind, layer: Layer, field: &'static str, module_error: Source) -> Self {
+        let offset = match kind {
+            ErrorKind::NotEnoughData { got, .. } => Some(got),
+            _ => None,
+        };
+        Self {
+            kind,
+            layer,
+            field: (!field.is_empty()).then_some(field),
+            offset,
+            module_error,
+        }
+    }
+
+    /// Set the offset, if known
+    fn with_offset(mut self, offset: Option<usize>) -> Self {
+        if offset.is_some() {
+            self.offset = offset;
+        }
+        self
+    }
+
+    /// Get the kind of error
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Get the protocol layer the error originated from
+    pub fn layer(&self) -> Layer {
+        self.layer
+    }
+
+    /// Path of the failing field, e.g. `GRE::Key`, if known
+    pub fn field(&self) -> Option<&'static str> {
+        self.field
+    }
+
+    /// Byte offset of the failing field or packet, relative to the data passed to the failing
+    /// parser unless shifted with [Error::at_offset]. `None` if the module error does not know
+    /// the position, e.g. for errors while writing
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// Get the module error this error was converted from
+    pub fn module_error(&self) -> Source {
+        self.module_error
+    }
+
+    /// Shift the offset by `base` bytes when the data passed to the failing parser starts
+    /// `base` bytes into the enclosing data. If the offset is unknown it is set to `base`,
+    /// the start of the failing layer
+    pub fn at_offset(mut self, base: usize) -> Self {
+        self.offset = Some(self.offset.unwrap_or(0) + base);
+        self
+    }
+}
+
+impl From<ip::error::Error> for Error {
+    fn from(e: ip::error::Error) -> Self {
+        use ip::error::general::ErrorKind as General;
+        use ip::v4::error::ErrorKind as V4;
+        use ip::v6::error::ErrorKind as V6;
+        let mut offset = None;
+        let (kind, layer, field) = match e {
+            ip::error::Error::General(g) => match g.kind() {
+                General::Empty => (
+                    ErrorKind::NotEnoughData { need: 1, got: 0 },
+                    Layer::Ip,
+                    "IpPacket",
+                ),
+                General::IpVersionNotImplemented(_) => {
+                    offset = Some(0);
+                    (ErrorKind::Unsupported, Layer::Ip, "IpPacket::Version")
+                }
+            },
+            ip::error::Error::V4(v4) => {
+                let (kind, field) = match v4.kind() {
+                    V4::InvalidVersion(_) | V4::WrongVersion(..) => {
+                        offset = Some(0);
+                        (ErrorKind::InvalidValue, "Ipv4Packet::Version")
+                    }
+                    V4::NotEnoughData { need, got, field } => {
+                        (ErrorKind::NotEnoughData { need, got }, *field)
+                    }
+                    V4::HeaderTooLong => {
+                        offset = Some(0);
+                        (ErrorKind::InvalidLength, "Ipv4Packet::IHL")
+                    }
+                    V4::NotEnoughSpace { need, got } => {
+                        (ErrorKind::NotEnoughSpace { need, got }, "")
+                    }
+                    V4::InvalidValue { field, .. } => (ErrorKind::InvalidValue, *field),
+                    V4::CannotFragment { .. } => (ErrorKind::Fragmentation, "Ipv4Packet::DF"),
+                    V4::InvalidFragment => (ErrorKind::Fragmentation, "Ipv4Packet::Offset"),
+                    V4::ReassemblyLimitExceeded { .. } => (ErrorKind::LimitExceeded, ""),
+                };
+                (kind, Layer::Ipv4, field)
+            }
+            ip::error::Error::V6(v6) => {
+                let (kind, field) = match v6.kind() {
+                    V6::WrongVersion(..) => {
+                        offset = Some(0);
+                        (ErrorKind::InvalidValue, "Ipv6Packet::Version")
+                    }
+                    V6::NotEnoughData { need, got, field } => {
+                        (ErrorKind::NotEnoughData { need, got }, *field)
+                    }
+                    V6::ExtensionHeaderTooLong => {
+                        (ErrorKind::InvalidLength, "Ipv6Packet::ExtensionHeader")
+                    }
+                };
+                (kind, Layer::Ipv6, field)
+            }
+        };
+        Error::new(kind, layer, field, Source::Ip(e)).with_offset(offset)
+    }
+}
+
+impl From<ip::v4::error::Error> for Error {
+    fn from(e: ip::v4::error::Error) -> Self {
+        ip::error::Error::from(e).into()
+    }
+}
+
+impl From<ip::v6::error::Error> for Error {
+    fn from(e: ip::v6::error::Error) -> Self {
+        ip::error::Error::from(e).into()
+    }
+}
+
+impl From<udp::datagram::error::Error> for Error {
+    fn from(e: udp::datagram::error::Error) -> Self {
+        use udp::datagram::error::ErrorKind as Udp;
+        let mut offset = None;
+        let (kind, field) = match e.kind() {
+            Udp::NotEnoughData { need, got } => (ErrorKind::NotEnoughData { need, got }, ""),
+            Udp::InvalidLength(_) => {
+                offset = Some(4);
+                (ErrorKind::InvalidLength, "UDP::Length")
+            }
+            Udp::NotUdp(_) => (ErrorKind::Unsupported, "IpPacket::Protocol"),
+            Udp::Fragmented => (ErrorKind::Fragmentation, ""),
+            Udp::Ip(ip) => {
+                return Error {
+                    module_error: Source::Udp(e),
+                    ..ip.into()
+                }
+            }
+        };
+        Error::new(kind, Layer::Udp, field, Source::Udp(e)).with_offset(offset)
+    }
+}
+
+impl From<udp::reduced::error::Error> for Error {
+    fn from(e: udp::reduced::error::Error) -> Self {
+        use udp::reduced::error::ErrorKind as Reduced;
+        let mut offset = None;
+        let (kind, field) = match e.kind() {
+            Reduced::NotEnoughData { need, got } => (ErrorKind::NotEnoughData { need, got }, ""),
+            Reduced::NotEnoughSpace { need, got } => (ErrorKind::NotEnoughSpace { need, got }, ""),
+            Reduced::PayloadTooLong(_) => (ErrorKind::LimitExceeded, "UDP::Length"),
+            Reduced::NotUdp(_) => {
+                offset = Some(9);
+                (ErrorKind::Unsupported, "Ipv4Packet::Protocol")
+            }
+            Reduced::Fragmented => (ErrorKind::Fragmentation, ""),
+            Reduced::Ipv4(ip) => {
+                return Error {
+                    module_error: Source::UdpReduced(e),
+                    ..ip.into()
+                }
+            }
+        };
+        Error::new(kind, Layer::Udp, field, Source::UdpReduced(e)).with_offset(offset)
+    }
+}
+
+impl From<gre::error::Error> for Error {
+    fn from(e: gre::error::Error) -> Self {
+        use gre::error::ErrorKind as Gre;
+        let (kind, field) = match e.kind() {
+            Gre::NotEnoughData { need, got, field } => {
+                (ErrorKind::NotEnoughData { need, got }, *field)
+            }
+            Gre::NotEnoughSpace { need, got } => (ErrorKind::NotEnoughSpace { need, got }, ""),
+        };
+        Error::new(kind, Layer::Gre, field, Source::Gre(e))
+    }
+}
+
+impl From<rtp::error::Error> for Error {
+    fn from(e: rtp::error::Error) -> Self {
+        let kind = match e.kind() {
+            rtp::error::Kind::Other(s) => ErrorKind::Other(s),
+            rtp::error::Kind::NotEnoughSpace { need, got } => {
+                ErrorKind::NotEnoughSpace { need, got }
+            }
+        };
+        Error::new(kind, Layer::Rtp, "", Source::Rtp(e))
+    }
+}
+
+impl From<rtp::ext::rist::Error> for Error {
+    fn from(e: rtp::ext::rist::Error) -> Self {
+        let mut offset = None;
+        let (kind, field) = match e.kind() {
+            rtp::ext::rist::ErrorKind::InvalidLength => {
+                (ErrorKind::InvalidLength, "RTP::Extension::Length")
+            }
+            rtp::ext::rist::ErrorKind::InvalidProfile(_) => {
+                offset = Some(0);
+                (ErrorKind::InvalidValue, "RTP::Extension::Profile")
+            }
+        };
+        Error::new(kind, Layer::Rist, field, Source::RistExtension(e)).with_offset(offset)
+    }
+}
+
+impl From<rtcp::error::Error> for Error {
+    fn from(e: rtcp::error::Error) -> Self {
+        use rtcp::app::error::Error as App;
+        use rtcp::app::rist::error::Error as AppRist;
+        use rtcp::error::{CompoundRule, ErrorKind as Rtcp};
+        let mut offset = None;
+        let (kind, field) = match *e.kind() {
+            Rtcp::NotEnoughData { need, got, field } => {
+                (ErrorKind::NotEnoughData { need, got }, *field)
+            }
+            Rtcp::InvalidPadding => (ErrorKind::InvalidPadding, "RTCP::Padding"),
+            Rtcp::UnknownReportType(_) => {
+                offset = Some(1);
+                (ErrorKind::Unsupported, "RTCP::PacketType")
+            }
+            Rtcp::NotEnoughSpace { need, got } => (ErrorKind::NotEnoughSpace { need, got }, ""),
+            Rtcp::TooManyItems(_) => (ErrorKind::LimitExceeded, "RTCP::Count"),
+            Rtcp::UnalignedLength(_) => (ErrorKind::InvalidLength, "RTCP::Length"),
+            Rtcp::PacketTooLong(_) => (ErrorKind::LimitExceeded, "RTCP::Length"),
+            Rtcp::SDES(e) => match e {
+                rtcp::sdes::error::Error::Utf8(_) => (ErrorKind::InvalidUtf8, "RTCP::SDES::Item"),
+                rtcp::sdes::error::Error::EndOfPacketReached => {
+                    (ErrorKind::InvalidLength, "RTCP::SDES::Item")
+                }
+                rtcp::sdes::error::Error::InvalidPadding => {
+                    (ErrorKind::InvalidPadding, "RTCP::SDES::Padding")
+                }
+                rtcp::sdes::error::Error::UnknownType => {
+                    (ErrorKind::Unsupported, "RTCP::SDES::ItemType")
+                }
+                rtcp::sdes::error::Error::ItemTooLong(_) => {
+                    (ErrorKind::LimitExceeded, "RTCP::SDES::Item")
+                }
+            },
+            Rtcp::RR(e) => match e {
+                rtcp::rr::error::Error::EndOfPacketReached => {
+                    (ErrorKind::InvalidLength, "RTCP::RR::ReportBlock")
+                }
+                rtcp::rr::error::Error::InvalidPacketLen(_) => {
+                    (ErrorKind::InvalidLength, "RTCP::RR")
+                }
+            },
+            Rtcp::SR(rtcp::sr::error::Error::InvalidPacketLen(_)) => {
+                (ErrorKind::InvalidLength, "RTCP::SR")
+            }
+            Rtcp::APP(e) => match e {
+                App::EndOfPacketReached => (ErrorKind::InvalidLength, "RTCP::APP"),
+                App::UnknownApplication(_) => (ErrorKind::Unsupported, "RTCP::APP::Name"),
+                App::Utf8Error(_) => (ErrorKind::InvalidUtf8, "RTCP::APP::Name"),
+                App::Rist(AppRist::UnknownSubtype(_)) => {
+                    (ErrorKind::Unsupported, "RTCP::APP::RIST::Subtype")
+                }
+                App::Rist(AppRist::EndOfPacketReached) => {
+                    (ErrorKind::InvalidLength, "RTCP::APP::RIST")
+                }
+                App::Rist(AppRist::RTT(_)) => (ErrorKind::InvalidLength, "RTCP::APP::RIST::RTT"),
+                App::Rist(AppRist::RangeNack(_)) => {
+                    (ErrorKind::InvalidLength, "RTCP::APP::RIST::RangeNack")
+                }
+            },
+            Rtcp::NACK(e) => match e {
+                rtcp::nack::error::Error::InvalidPacketLen(_) => {
+                    (ErrorKind::InvalidLength, "RTCP::NACK")
+                }
+                rtcp::nack::error::Error::UnknownFormat(_) => {
+                    (ErrorKind::Unsupported, "RTCP::NACK::Format")
+                }
+                rtcp::nack::error::Error::NotAscending(_) => {
+                    (ErrorKind::InvalidValue, "RTCP::NACK::PID")
+                }
+            },
+            Rtcp::BYE(e) => match e {
+                rtcp::bye::error::Error::InvalidPacketLen(_) => {
+                    (ErrorKind::InvalidLength, "RTCP::BYE")
+                }
+                rtcp::bye::error::Error::EndOfPacketReached => {
+                    (ErrorKind::InvalidLength, "RTCP::BYE::Reason")
+                }
+                rtcp::bye::error::Error::Utf8(_) => (ErrorKind::InvalidUtf8, "RTCP::BYE::Reason"),
+                rtcp::bye::error::Error::ReasonTooLong(_) => {
+                    (ErrorKind::LimitExceeded, "RTCP::BYE::Reason")
+                }
+            },
+            Rtcp::XR(e) => match e {
+                rtcp::xr::error::Error::InvalidPacketLen(_) => {
+                    (ErrorKind::InvalidLength, "RTCP::XR")
+                }
+                rtcp::xr::error::Error::EndOfPacketReached
+                | rtcp::xr::error::Error::InvalidBlockLen { .. } => {
+                    (ErrorKind::InvalidLength, "RTCP::XR::ReportBlock")
+                }
+            },
+            Rtcp::InvalidCompound { rule, offset: o } => {
+                offset = Some(o);
+                match rule {
+                    CompoundRule::Version => (ErrorKind::InvalidValue, "RTCP::Version"),
+                    CompoundRule::FirstPacketType => (ErrorKind::InvalidValue, "RTCP::PacketType"),
+                    CompoundRule::Padding => (ErrorKind::InvalidPadding, "RTCP::Padding"),
+                    CompoundRule::Length => (ErrorKind::InvalidLength, "RTCP::Length"),
+                }
+            }
+        };
+        Error::new(kind, Layer::Rtcp, field, Source::Rtcp(e)).with_offset(offset)
+    }
+}
+
+/// Conversions for the errors of the RTCP packet types go through [rtcp::error::Error]
+macro_rules! from_rtcp {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Error {
+                fn from(e: $t) -> Self {
+                    rtcp::error::Error::from(e).into()
+                }
+            }
+        )*
+    };
+}
+
+from_rtcp!(
+    rtcp::sdes::error::Error,
+    rtcp::rr::error::Error,
+    rtcp::sr::error::Error,
+    rtcp::app::error::Error,
+    rtcp::nack::error::Error,
+    rtcp::bye::error::Error,
+    rtcp::xr::error::Error
+);
+
+impl From<ts::error::Error> for Error {
+    fn from(e: ts::error::Error) -> Self {
+        use ts::error::ErrorKind as Ts;
+        let mut offset = None;
+        let (kind, field) = match e.kind() {
+            Ts::NotEnoughData { need, got } => (ErrorKind::NotEnoughData { need, got }, ""),
+            Ts::InvalidSyncByte(_) => {
+                offset = Some(0);
+                (ErrorKind::InvalidValue, "TS::SyncByte")
+            }
+            Ts::InvalidAdaptationFieldLength(_) => {
+                offset = Some(4);
+                (ErrorKind::InvalidLength, "TS::AdaptationField")
+            }
+            Ts::TrailingData(_) => (ErrorKind::InvalidLength, ""),
+        };
+        Error::new(kind, Layer::Ts, field, Source::Ts(e)).with_offset(offset)
+    }
+}
+
+impl From<rist::keep_alive::error::Error> for Error {
+    fn from(e: rist::keep_alive::error::Error) -> Self {
+        use rist::keep_alive::error::ErrorKind as KeepAlive;
+        let mut offset = None;
+        let (kind, field) = match e.kind() {
+            KeepAlive::NotEnoughData { need, got } => (ErrorKind::NotEnoughData { need, got }, ""),
+            KeepAlive::NotEnoughSpace { need, got } => {
+                (ErrorKind::NotEnoughSpace { need, got }, "")
+            }
+            KeepAlive::InvalidState => {
+                offset = Some(rist::keep_alive::HEADER_SIZE - 1);
+                (ErrorKind::InvalidValue, "KeepAlive::Flags")
+            }
+            KeepAlive::InvalidJson(_) => {
+                offset = Some(rist::keep_alive::HEADER_SIZE);
+                (ErrorKind::InvalidUtf8, "KeepAlive::JSON")
+            }
+        };
+        Error::new(kind, Layer::Rist, field, Source::KeepAlive(e)).with_offset(offset)
+    }
+}
+
+impl From<rist::npd::error::Error> for Error {
+    fn from(e: rist::npd::error::Error) -> Self {
+        use rist::npd::error::ErrorKind as Npd;
+        let (kind, field) = match e.kind() {
+            Npd::NotEnoughSpace { need, got } => (ErrorKind::NotEnoughSpace { need, got }, ""),
+            Npd::InvalidPacketSize(_) => (ErrorKind::InvalidValue, "NPD::PacketSize"),
+            Npd::TrailingData(_) => (ErrorKind::InvalidLength, "NPD::Payload"),
+            Npd::TooManyPackets(_) => (ErrorKind::LimitExceeded, "NPD::PacketCount"),
+            Npd::PacketCountMismatch { .. } => (ErrorKind::InvalidValue, "NPD::PacketCount"),
+        };
+        Error::new(kind, Layer::Rist, field, Source::Npd(e))
+    }
+}
+
+impl From<rist_rs_macros::packet::error::Error> for Error {
+    fn from(e: rist_rs_macros::packet::error::Error) -> Self {
+        use rist_rs_macros::packet::error::ErrorKind as Header;
+        let (kind, field) = match e.kind() {
+            Header::NotEnoughData { need, got } => (ErrorKind::NotEnoughData { need, got }, ""),
+            Header::NotEnoughSpace { need, got } => (ErrorKind::NotEnoughSpace { need, got }, ""),
+            Header::InvalidValue { field, .. } => (ErrorKind::InvalidValue, field),
+        };
+        Error::new(kind, Layer::Header, field, Source::Header(e))
+    }
+}
+
+#[allow(unused)]
+mod test {
+    use super::*;
+    use crate::ip::v4::Ipv4PacketBuilder;
+    use crate::ip::IpPacketView;
+    use crate::rtp::RTPView;
+    use crate::udp::UdpDatagramView;
+
+    /// Get the SSRC of a RTP packet carried in an Ipv4 datagram, crossing three error types
+    fn ssrc(data: &[u8]) -> Result<u32, Error> {
+        let ip = IpPacketView::try_from(data)?;
+        let udp = UdpDatagramView::try_from_ip(&ip)?;
+        let rtp = RTPView::try_new(udp.payload()?).map_err(|e| Error::from(e).at_offset(28))?;
+        Ok(rtp.ssrc())
+    }
+
+    #[test]
+    fn layers() {
+        let mut datagram = [0u8; 8 + 12];
+        datagram[5] = 20;
+        datagram[16..20].copy_from_slice(&0x01020304u32.to_be_bytes());
+        let mut buf = [0u8; 64];
+        let len = Ipv4PacketBuilder::new()
+            .with_protocol(udp::datagram::IP_PROTOCOL_UDP)
+            .write(&datagram, &mut buf)
+            .unwrap();
+        assert_eq!(ssrc(&buf[..len]).unwrap(), 0x01020304);
+
+        let err = ssrc(&buf[..10]).unwrap_err();
+        assert_eq!(err.layer(), Layer::Ipv4);
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::NotEnoughData { got: 10, .. }
+        ));
+        assert!(matches!(err.module_error(), Source::Ip(_)));
+
+        // RTP packet too short, the offset points at the start of the RTP packet
+        datagram[5] = 19;
+        let len = Ipv4PacketBuilder::new()
+            .with_protocol(udp::datagram::IP_PROTOCOL_UDP)
+            .write(&datagram[..19], &mut buf)
+            .unwrap();
+        let err = ssrc(&buf[..len]).unwrap_err();
+        assert_eq!(err.layer(), Layer::Rtp);
+        assert_eq!(err.offset(), Some(28));
+        assert!(matches!(err.kind(), ErrorKind::Other(_)));
+    }
+
+    #[test]
+    fn field_and_offset() {
+        // an RR packet followed by a packet with version 0
+        let data = [0x80, 201, 0, 1, 0, 0, 0, 1, 0, 201, 0, 0];
+        let err = Error::from(rtcp::validate_compound(&data).unwrap_err());
+        assert_eq!(err.layer(), Layer::Rtcp);
+        assert_eq!(err.kind(), ErrorKind::InvalidValue);
+        assert_eq!(err.field(), Some("RTCP::Version"));
+        assert_eq!(err.offset(), Some(8));
+        assert_eq!(err.at_offset(42).offset(), Some(50));
+
+        let err = Error::from(ts::TsPacketView::try_new(&[0x48; ts::PACKET_LEN]).unwrap_err());
+        assert_eq!(err.field(), Some("TS::SyncByte"));
+        assert_eq!(err.offset(), Some(0));
+        assert_eq!(err.at_offset(42).offset(), Some(42));
+
+        // the key is missing from a GRE header announcing checksum and key
+        let data = [0xa0, 0, 0x88, 0xb6, 0, 0, 0, 0, 0, 0];
+        let err = Error::from(
+            gre::GREPacket::try_new(&data)
+                .unwrap()
+                .key()
+                .unwrap()
+                .unwrap_err(),
+        );
+        assert_eq!(err.field(), Some("GRE::Key"));
+        assert_eq!(err.offset(), Some(10));
+
+        // the VSF protocol header is missing after the GRE header
+        let data = [0, 0, 0xcc, 0xe0, 0, 0];
+        let err = Error::from(
+            gre::GREPacket::try_new(&data)
+                .unwrap()
+                .rist_payload()
+                .unwrap_err(),
+        );
+        assert_eq!(err.field(), Some("GRE::VsfProtocolHeader"));
+        assert_eq!(err.offset(), Some(6));
+    }
+
+    #[test]
+    fn generated_header() {
+        fn mac(data: &[u8]) -> Result<[u8; 6], Error> {
+            Ok(rist::keep_alive::KeepAliveHeaderView::try_new(data)?.mac_address())
+        }
+        assert_eq!(mac(&[1, 2, 3, 4, 5, 6, 0, 0]).unwrap(), [1, 2, 3, 4, 5, 6]);
+        let err = mac(&[0; 7]).unwrap_err();
+        assert_eq!(err.layer(), Layer::Header);
+        assert_eq!(err.kind(), ErrorKind::NotEnoughData { need: 8, got: 7 });
+        assert_eq!(err.offset(), Some(7));
+        assert!(matches!(err.module_error(), Source::Header(_)));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn display() {
+        use std::string::ToString;
+        let err = Error::from(rtcp::error::invalid_compound(
+            rtcp::error::CompoundRule::Length,
+            4,
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Rtcp: invalid length in field [RTCP::Length] at offset 4"
+        );
+        let _: &dyn std::error::Error = &err;
+    }
+}
@@ -0,0 +1,120 @@
+use core::mem::size_of;
+
+use super::{error, ext, GREPacket};
+use crate::util;
+
+/// Builder for GRE packets as carried over UDP by the RIST Main Profile (VSF TR-06-2).
+/// Collects the header fields and writes them together with a payload into a
+/// caller-supplied buffer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GREBuilder {
+    protocol: u16,
+    checksum: bool,
+    key: Option<u32>,
+    sequence_number: Option<u32>,
+    ext0: [u8; 2],
+}
+
+impl GREBuilder {
+    /// Bits of the first header word that are used by the C, K and S flags and the version
+    const FLAGS_MASK: [u8; 2] = [0xb0, 0x07];
+
+    /// Create a new builder without optional fields and a protocol type of 0
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the protocol type of the encapsulated payload
+    pub fn with_protocol(mut self, protocol: u16) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Calculate and add a checksum over the header and the payload
+    pub fn with_checksum(mut self, checksum: bool) -> Self {
+        self.checksum = checksum;
+        self
+    }
+
+    /// Add the key field
+    pub fn with_key(mut self, key: u32) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Add the sequence number field
+    pub fn with_sequence_number(mut self, sequence_number: u32) -> Self {
+        self.sequence_number = Some(sequence_number);
+        self
+    }
+
+    /// Set the RIST specific bits of the first header word. Bits that overlap with the
+    /// GRE flags or the GRE version are ignored
+    pub fn with_rist_extension(mut self, ext: ext::vsf_tr06_2::Extension) -> Self {
+        let [b0, b1]: [u8; 2] = ext.into();
+        self.ext0 = [b0 & !Self::FLAGS_MASK[0], b1 & !Self::FLAGS_MASK[1]];
+        self
+    }
+
+    /// Length of the header including all optional fields
+    pub fn header_len(&self) -> usize {
+        GREPacket::OPT_FIELDS_OFFSET
+            + [
+                self.checksum,
+                self.key.is_some(),
+                self.sequence_number.is_some(),
+            ]
+            .into_iter()
+            .filter(|b| *b)
+            .count()
+                * size_of::<u32>()
+    }
+
+    /// Total length of a packet written by this builder with a payload of `payload_len` bytes
+    pub fn packet_len(&self, payload_len: usize) -> usize {
+        self.header_len() + payload_len
+    }
+
+    /// Write the packet with the given payload into `buf`. Returns the number of bytes
+    /// written or an error if the buffer is too small.
+    pub fn write(&self, payload: &[u8], buf: &mut [u8]) -> Result<usize, error::Error> {
+        let len = self.packet_len(payload.len());
+        if buf.len() < len {
+            return Err(error::not_enough_space(len, buf.len()));
+        }
+        let buf = &mut buf[..len];
+        buf[0] = self.ext0[0]
+            | if self.checksum { 0x80 } else { 0 }
+            | if self.key.is_some() { 0x20 } else { 0 }
+            | if self.sequence_number.is_some() {
+                0x10
+            } else {
+                0
+            };
+        buf[1] = self.ext0[1];
+        buf[2..4].copy_from_slice(&self.protocol.to_be_bytes());
+        let mut offset = GREPacket::OPT_FIELDS_OFFSET;
+        if self.checksum {
+            buf[offset..offset + 4].fill(0);
+            offset += 4;
+        }
+        for field in [self.key, self.sequence_number].into_iter().flatten() {
+            buf[offset..offset + 4].copy_from_slice(&field.to_be_bytes());
+            offset += 4;
+        }
+        buf[offset..].copy_from_slice(payload);
+        if self.checksum {
+            let sum = util::checksum::u16(buf, &[GREPacket::CHECKSUM_FIELD]);
+            buf[GREPacket::CHECKSUM_FIELD].copy_from_slice(&sum.to_be_bytes());
+        }
+        Ok(len)
+    }
+
+    /// Write the packet with the given payload into a newly allocated vector
+    #[cfg(feature = "alloc")]
+    pub fn to_vec(&self, payload: &[u8]) -> Result<alloc::vec::Vec<u8>, error::Error> {
+        let mut buf = alloc::vec![0u8; self.packet_len(payload.len())];
+        self.write(payload, &mut buf)?;
+        Ok(buf)
+    }
+}
@@ -0,0 +1,68 @@
+use core::fmt::Display;
+
+#[derive(Debug, Clone, Copy)]
+pub enum ErrorKind {
+    /// Not enough data was supplied to read the value of a specific field
+    NotEnoughData {
+        need: usize,
+        got: usize,
+        field: &'static &'static str,
+    },
+
+    /// The buffer supplied for writing a packet is too small
+    NotEnoughSpace { need: usize, got: usize },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Error {
+    kind: ErrorKind,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.kind {
+            ErrorKind::NotEnoughData { need, got, field } => {
+                write!(f, "Not enough data to read value(s) from field [{}], need at least {} bytes, got {} bytes", **field, need, got)
+            }
+            ErrorKind::NotEnoughSpace { need, got } => {
+                write!(
+                    f,
+                    "Not enough space to write GRE packet, need {need} bytes, got {got} bytes"
+                )
+            }
+        }
+    }
+}
+
+impl Error {
+    /// Create a new error from a given ErrorKind
+    fn new(kind: ErrorKind) -> Self {
+        Self { kind }
+    }
+
+    /// Extract the error kind
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Make the lengths relative to data starting `base` bytes before the data the error was
+    /// raised for
+    pub(super) fn shifted(self, base: usize) -> Self {
+        match self.kind {
+            ErrorKind::NotEnoughData { need, got, field } => {
+                not_enough_data(need + base, got + base, field)
+            }
+            _ => self,
+        }
+    }
+}
+
+/// Make an error that indicates that not enough data was supplied to read the value of a part of the GRE packet
+pub(super) fn not_enough_data(need: usize, got: usize, field: &'static &'static str) -> Error {
+    Error::new(ErrorKind::NotEnoughData { need, got, field })
+}
+
+/// Make an error that indicates that the buffer is too small to write a GRE packet
+pub(super) fn not_enough_space(need: usize, got: usize) -> Error {
+    Error::new(ErrorKind::NotEnoughSpace { need, got })
+}
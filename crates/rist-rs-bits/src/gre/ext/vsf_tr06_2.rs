@@ -1,4 +1,102 @@
-#[derive(Debug)]
+/// GRE protocol type of a full IPv4 datagram
+pub const PROTOCOL_IPV4: u16 = 0x0800;
+
+/// GRE protocol type of a full IPv6 datagram
+pub const PROTOCOL_IPV6: u16 = 0x86dd;
+
+/// GRE protocol type of EAPoL (IEEE 802.1X) authentication messages
+pub const PROTOCOL_EAPOL: u16 = 0x888e;
+
+/// GRE protocol type of keep-alive messages as used by the first revision of VSF TR-06-2
+pub const PROTOCOL_KEEP_ALIVE: u16 = 0x88b5;
+
+/// GRE protocol type of reduced overhead packets. The payload starts directly with the reduced
+/// UDP header, there is no VSF protocol header
+pub const PROTOCOL_REDUCED_OVERHEAD: u16 = 0x88b6;
+
+/// GRE protocol type of the VSF EtherType. The payload starts with a VSF protocol type
+/// and subtype that select the kind of message
+pub const PROTOCOL_VSF: u16 = 0xcce0;
+
+/// VSF protocol type of RIST messages
+pub const VSF_TYPE_RIST: u16 = 0x0000;
+
+/// VSF protocol subtype of reduced overhead RTP/RTCP packets
+pub const VSF_SUBTYPE_REDUCED_OVERHEAD: u16 = 0x0000;
+
+/// VSF protocol subtype of keep-alive messages
+pub const VSF_SUBTYPE_KEEP_ALIVE: u16 = 0x8000;
+
+/// VSF protocol subtype of future nonce announcements for PSK operation
+pub const VSF_SUBTYPE_FUTURE_NONCE: u16 = 0x8001;
+
+/// Length of the VSF protocol type and subtype in front of VSF EtherType payloads
+pub const VSF_HEADER_LEN: usize = 4;
+
+/// Get the VSF protocol header that precedes a RIST message of the given subtype
+pub fn vsf_header(subtype: u16) -> [u8; VSF_HEADER_LEN] {
+    let [t0, t1] = VSF_TYPE_RIST.to_be_bytes();
+    let [s0, s1] = subtype.to_be_bytes();
+    [t0, t1, s0, s1]
+}
+
+/// Classification of the payload of a RIST Main Profile GRE packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RistGrePayload<'a> {
+    /// A full IPv4 or IPv6 datagram, protocol type 0x0800 or 0x86dd
+    FullDatagram(&'a [u8]),
+
+    /// A reduced overhead packet (reduced UDP header followed by RTP/RTCP), either with protocol
+    /// type 0x88b6 or as VSF subtype without the VSF header
+    ReducedOverhead(&'a [u8]),
+
+    /// A keep-alive message, either with the legacy protocol type or as VSF subtype
+    KeepAlive(&'a [u8]),
+
+    /// A future nonce announcement, without the VSF header
+    FutureNonce(&'a [u8]),
+
+    /// An EAPoL authentication message
+    Eapol(&'a [u8]),
+
+    /// Payload of a protocol type or VSF subtype that is not known. Holds the full GRE payload
+    Unknown { protocol: u16, payload: &'a [u8] },
+}
+
+impl<'a> RistGrePayload<'a> {
+    /// Classify a GRE payload by its protocol type
+    pub fn classify(protocol: u16, payload: &'a [u8]) -> Result<Self, super::super::error::Error> {
+        Ok(match protocol {
+            PROTOCOL_IPV4 | PROTOCOL_IPV6 => Self::FullDatagram(payload),
+            PROTOCOL_EAPOL => Self::Eapol(payload),
+            PROTOCOL_KEEP_ALIVE => Self::KeepAlive(payload),
+            PROTOCOL_REDUCED_OVERHEAD => Self::ReducedOverhead(payload),
+            PROTOCOL_VSF => {
+                if payload.len() < VSF_HEADER_LEN {
+                    return Err(super::super::error::not_enough_data(
+                        VSF_HEADER_LEN,
+                        payload.len(),
+                        &"GRE::VsfProtocolHeader",
+                    ));
+                }
+                let data = &payload[VSF_HEADER_LEN..];
+                match (
+                    u16::from_be_bytes([payload[0], payload[1]]),
+                    u16::from_be_bytes([payload[2], payload[3]]),
+                ) {
+                    (VSF_TYPE_RIST, VSF_SUBTYPE_REDUCED_OVERHEAD) => Self::ReducedOverhead(data),
+                    (VSF_TYPE_RIST, VSF_SUBTYPE_KEEP_ALIVE) => Self::KeepAlive(data),
+                    (VSF_TYPE_RIST, VSF_SUBTYPE_FUTURE_NONCE) => Self::FutureNonce(data),
+                    _ => Self::Unknown { protocol, payload },
+                }
+            }
+            _ => Self::Unknown { protocol, payload },
+        })
+    }
+}
+
+/// Bits of the first GRE header word used by the RIST Main Profile (VSF TR-06-2)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct Extension {
     bytes: [u8; 2],
 }
@@ -9,9 +107,29 @@ impl From<[u8; 2]> for Extension {
     }
 }
 
+impl From<Extension> for [u8; 2] {
+    fn from(ext: Extension) -> Self {
+        ext.bytes
+    }
+}
+
 impl super::Extension0 for Extension {}
 
 impl Extension {
+    /// Mask of the RIST GRE version bits in the second byte
+    const VERSION_MASK: u8 = 0x38;
+
+    /// Bit in the second byte that selects 256 bit keys
+    const KEY_LENGTH_256: u8 = 0x40;
+
+    /// Bit in the first byte that marks reduced overhead operation (H bit)
+    const REDUCED_OVERHEAD: u8 = 0x08;
+
+    /// Create an extension with all bits cleared (RIST GRE version 0)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
     /// RIST GRE version used. If the version is 1, the key length can be obtained from this extension
     pub fn rist_gre_version(&self) -> u8 {
         (self.bytes[1] >> 3) & 0x7
@@ -20,12 +138,43 @@ impl Extension {
     /// Key length for PSK operation. Returns `None` if the RIST GRE version is 0, returns either 128 or 256
     /// if the RIST GRE version is 1
     pub fn key_length(&self) -> Option<u16> {
-        (self.rist_gre_version() == 1).then(|| {
-            if (self.bytes[1] & 0x40) != 0 {
-                256
-            } else {
-                128
-            }
-        })
+        (self.rist_gre_version() == 1).then(|| if self.is_key_length_256() { 256 } else { 128 })
+    }
+
+    /// Check if the H bit is set, indicating reduced overhead operation
+    pub fn is_reduced_overhead(&self) -> bool {
+        (self.bytes[0] & Self::REDUCED_OVERHEAD) != 0
+    }
+
+    /// Check if 256 bit keys are selected, independent of the RIST GRE version
+    pub fn is_key_length_256(&self) -> bool {
+        (self.bytes[1] & Self::KEY_LENGTH_256) != 0
+    }
+
+    /// Set or clear the H bit
+    pub fn with_reduced_overhead(mut self, reduced_overhead: bool) -> Self {
+        if reduced_overhead {
+            self.bytes[0] |= Self::REDUCED_OVERHEAD;
+        } else {
+            self.bytes[0] &= !Self::REDUCED_OVERHEAD;
+        }
+        self
+    }
+
+    /// Set the RIST GRE version. Only the lower 3 bits are used
+    pub fn with_rist_gre_version(mut self, version: u8) -> Self {
+        self.bytes[1] = (self.bytes[1] & !Self::VERSION_MASK) | ((version & 0x7) << 3);
+        self
+    }
+
+    /// Set the key length for PSK operation. A length of 256 selects 256 bit keys, any other
+    /// value selects 128 bit keys. Only evaluated by receivers if the RIST GRE version is 1
+    pub fn with_key_length(mut self, key_length: u16) -> Self {
+        if key_length == 256 {
+            self.bytes[1] |= Self::KEY_LENGTH_256;
+        } else {
+            self.bytes[1] &= !Self::KEY_LENGTH_256;
+        }
+        self
     }
 }
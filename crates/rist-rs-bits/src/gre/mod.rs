@@ -1,4 +1,6 @@
-#![allow(unused)]
+mod builder;
+/// Error types for GRE packets
+pub mod error;
 pub mod ext;
 
 use core::{
@@ -8,15 +10,16 @@ use core::{
 
 use super::util;
 
-#[derive(Debug)]
-struct Error {}
+pub use builder::GREBuilder;
 
-struct GREPacket<'a> {
+/// View over an immutable slice of data that can be interpreted as a GRE packet (RFC2784/RFC2890)
+#[derive(Debug, Clone, Copy)]
+pub struct GREPacket<'a> {
     data: &'a [u8],
 }
 
 impl<'a> TryFrom<&'a [u8]> for GREPacket<'a> {
-    type Error = Error;
+    type Error = error::Error;
 
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
         Self::try_new(data)
@@ -27,124 +30,137 @@ impl<'a> GREPacket<'a> {
     /// Offset of the optional fields after the fixed header
     const OPT_FIELDS_OFFSET: usize = 4;
 
-    pub fn try_new<T, U>(bytes: &'a T) -> Result<Self, Error>
+    /// Position of the checksum field
+    const CHECKSUM_FIELD: core::ops::Range<usize> =
+        Self::OPT_FIELDS_OFFSET..Self::OPT_FIELDS_OFFSET + 2;
+
+    pub fn try_new<T, U>(bytes: &'a T) -> Result<Self, error::Error>
     where
         T: AsRef<U> + ?Sized,
         U: ?Sized + 'a,
         &'a U: Into<&'a [u8]>,
     {
         let data: &'a [u8] = bytes.as_ref().into();
-        if data.len() >= 4 {
+        if data.len() >= Self::OPT_FIELDS_OFFSET {
             Ok(Self { data })
         } else {
-            Err(Error {})
+            Err(error::not_enough_data(
+                Self::OPT_FIELDS_OFFSET,
+                data.len(),
+                &"GRE::Header",
+            ))
         }
     }
 
     /// Check if the checksum bit is set
-    fn has_checksum(&self) -> bool {
+    pub fn has_checksum(&self) -> bool {
         util::check_bit!(self.data[0], 0)
     }
 
     /// Check if the key field is set
-    fn has_key(&self) -> bool {
+    pub fn has_key(&self) -> bool {
         util::check_bit!(self.data[0], 2)
     }
 
     /// Check if the sequence number bit is set
-    fn has_sequence(&self) -> bool {
+    pub fn has_sequence(&self) -> bool {
         util::check_bit!(self.data[0], 3)
     }
 
     /// Get the GRE protocol version
-    fn version(&self) -> u8 {
+    pub fn version(&self) -> u8 {
         self.data[1] & 0x7
     }
 
     /// Get the encapsulated protocol type
-    fn protocol(&self) -> u16 {
+    pub fn protocol(&self) -> u16 {
         util::read_int!(self.data, u16, 2)
     }
 
+    /// Length of the header including all optional fields indicated by the flags
+    pub fn header_len(&self) -> usize {
+        Self::OPT_FIELDS_OFFSET
+            + [self.has_checksum(), self.has_key(), self.has_sequence()]
+                .into_iter()
+                .filter(|b| *b)
+                .count()
+                * size_of::<u32>()
+    }
+
+    /// Read the optional 32 bit field at `offset`
+    fn opt_field(&self, offset: usize, field: &'static &'static str) -> Result<u32, error::Error> {
+        if self.data.len() < offset + size_of::<u32>() {
+            Err(error::not_enough_data(
+                offset + size_of::<u32>(),
+                self.data.len(),
+                field,
+            ))
+        } else {
+            Ok(util::read_int!(self.data, u32, offset))
+        }
+    }
+
     /// Get the checksum. Returns `None` if the checksum bit is not set, an `Error` if the
     /// slice is too short to contain a checksum at the right position, or the checksum
-    fn checksum(&self) -> Option<Result<u16, Error>> {
+    pub fn checksum(&self) -> Option<Result<u16, error::Error>> {
         self.has_checksum().then(|| {
-            if self.data.len() < Self::OPT_FIELDS_OFFSET + size_of::<u32>() {
-                Err(Error {})
-            } else {
-                Ok(util::read_int!(self.data, u16, Self::OPT_FIELDS_OFFSET))
-            }
+            self.opt_field(Self::OPT_FIELDS_OFFSET, &"GRE::Checksum")
+                .map(|field| (field >> 16) as u16)
         })
     }
 
     /// Get the key. Returns `None` if the key bit is not set, an `Error` if the
     /// slice is too short to contain a key at the right position, or the key
-    fn key(&self) -> Option<Result<u32, Error>> {
+    pub fn key(&self) -> Option<Result<u32, error::Error>> {
         self.has_key().then(|| {
             let offset = if self.has_checksum() {
                 Self::OPT_FIELDS_OFFSET + size_of::<u32>()
             } else {
                 Self::OPT_FIELDS_OFFSET
             };
-            if self.data.len() < offset + size_of::<u32>() {
-                Err(Error {})
-            } else {
-                Ok(util::read_int!(self.data, u32, offset))
-            }
+            self.opt_field(offset, &"GRE::Key")
         })
     }
 
     /// Get the sequence number. Returns `None` if the sequence number bit is not set,
     /// an `Error` if the slice is too short to contain a sequence number at the right position,
     /// or the sequence number
-    fn sequence_number(&self) -> Option<Result<u32, Error>> {
-        self.has_sequence().then(|| {
-            let mut offset = Self::OPT_FIELDS_OFFSET;
-            if self.has_checksum() {
-                offset += size_of::<u32>();
-            }
-            if self.has_key() {
-                offset += size_of::<u32>();
-            }
-            if self.data.len() < offset + size_of::<u32>() {
-                Err(Error {})
-            } else {
-                Ok(util::read_int!(self.data, u32, offset))
-            }
-        })
+    pub fn sequence_number(&self) -> Option<Result<u32, error::Error>> {
+        self.has_sequence()
+            .then(|| self.opt_field(self.header_len() - size_of::<u32>(), &"GRE::SequenceNumber"))
     }
 
     /// Get the encapsulated payload. Returns an error if the slice is shorter than the expected header length.
     /// Otherwise returns the (possibly zero-sized) payload
-    fn payload(&self) -> Result<&'a [u8], Error> {
-        let mut offset = Self::OPT_FIELDS_OFFSET;
-        if self.has_checksum() {
-            offset += size_of::<u32>();
-        }
-        if self.has_key() {
-            offset += size_of::<u32>();
-        }
-        if self.has_sequence() {
-            offset += size_of::<u32>();
-        }
+    pub fn payload(&self) -> Result<&'a [u8], error::Error> {
+        let offset = self.header_len();
         if self.data.len() < offset {
-            Err(Error {})
+            Err(error::not_enough_data(
+                offset,
+                self.data.len(),
+                &"GRE::Header",
+            ))
         } else {
             Ok(&self.data[offset..])
         }
     }
 
-    /// Verify the checksum. The checksum implementation behind this function is very naive and slow
-    /// and should not be used in production scenarios
-    fn verify_checksum(&self) -> Option<Result<bool, Error>> {
+    /// Classify the payload as defined by the RIST Main Profile (VSF TR-06-2). Returns an error
+    /// if the slice is too short to contain the header or the VSF protocol header
+    pub fn rist_payload(&self) -> Result<ext::vsf_tr06_2::RistGrePayload<'a>, error::Error> {
+        ext::vsf_tr06_2::RistGrePayload::classify(self.protocol(), self.payload()?)
+            .map_err(|e| e.shifted(self.header_len()))
+    }
+
+    /// Verify the checksum over the header and the payload
+    pub fn verify_checksum(&self) -> Option<Result<bool, error::Error>> {
         self.checksum().map(|sum| {
-            sum.map(|sum| util::checksum::u16(self.data, Some(Self::OPT_FIELDS_OFFSET)) == sum)
+            sum.map(|sum| util::checksum::u16(self.data, &[Self::CHECKSUM_FIELD]) == sum)
         })
     }
 
     /// Get the first extension field
-    fn ext0<Ext: ext::Extension0>(&self) -> Ext {
+    pub fn ext0<Ext: ext::Extension0>(&self) -> Ext {
         Ext::from(
             self.data[0..2]
                 .try_into()
@@ -153,22 +169,15 @@ impl<'a> GREPacket<'a> {
     }
 
     /// Get the second extension field
-    fn ext1<Ext: ext::Extension1>(&self) -> Option<Result<Ext, Error>> {
+    pub fn ext1<Ext: ext::Extension1>(&self) -> Option<Result<Ext, error::Error>> {
         self.has_checksum().then(|| {
-            if self.data.len() < Self::OPT_FIELDS_OFFSET + size_of::<u32>() {
-                Err(Error {})
-            } else {
-                Ok(Ext::from(
-                    self.data[Self::OPT_FIELDS_OFFSET + size_of::<u16>()
-                        ..Self::OPT_FIELDS_OFFSET + size_of::<u32>()]
-                        .try_into()
-                        .expect("invalid range returned from slice"),
-                ))
-            }
+            self.opt_field(Self::OPT_FIELDS_OFFSET, &"GRE::Reserved1")
+                .map(|field| Ext::from([(field >> 8) as u8, field as u8]))
         })
     }
 }
 
+#[allow(unused)]
 mod test {
 
     use super::*;
@@ -182,7 +191,7 @@ mod test {
 
     const GRE_WITH_KEY: [u8; 8] = [0x20, 0x00, 0x01, 0x01, 0x11, 0x11, 0x11, 0x0a];
 
-    fn packet(data: &[u8]) -> GREPacket {
+    fn packet(data: &[u8]) -> GREPacket<'_> {
         GREPacket::try_from(data).unwrap()
     }
 
@@ -221,9 +230,9 @@ mod test {
         assert!(!gre.has_key());
         assert!(!gre.has_sequence());
         assert_eq!(gre.version(), 0);
-        assert!(matches!(gre.checksum(), None));
-        assert!(matches!(gre.sequence_number(), None));
-        assert!(matches!(gre.key(), None));
+        assert!(gre.checksum().is_none());
+        assert!(gre.sequence_number().is_none());
+        assert!(gre.key().is_none());
     }
 
     #[test]
@@ -232,4 +241,173 @@ mod test {
         assert!(gre.has_key());
         assert_eq!(gre.key().unwrap().unwrap(), 0x1111110a);
     }
+
+    #[test]
+    fn gre_too_short() {
+        assert!(GREPacket::try_from([0x20, 0x00, 0x01].as_slice()).is_err());
+        let gre = packet(&GRE_WITH_KEY[..6]);
+        assert!(gre.key().unwrap().is_err());
+        assert!(gre.payload().is_err());
+    }
+
+    #[test]
+    fn build_key_and_sequence() {
+        let data = GREBuilder::new()
+            .with_protocol(0x0101)
+            .with_key(0x1111110a)
+            .with_sequence_number(0xdeadbeef)
+            .to_vec(&[1, 2, 3])
+            .unwrap();
+        assert_eq!(data[0], GRE_WITH_KEY[0] | 0x10);
+        assert_eq!(data[1..8], GRE_WITH_KEY[1..]);
+        let gre = packet(&data);
+        assert!(gre.has_sequence());
+        assert!(!gre.has_checksum());
+        assert_eq!(gre.header_len(), 12);
+        assert_eq!(gre.key().unwrap().unwrap(), 0x1111110a);
+        assert_eq!(gre.sequence_number().unwrap().unwrap(), 0xdeadbeef);
+        assert_eq!(gre.payload().unwrap(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn build_reproduces_example() {
+        let data = GREBuilder::new()
+            .with_protocol(0x0800)
+            .to_vec(&EXAMPLE_GRE[4..])
+            .unwrap();
+        assert_eq!(data, EXAMPLE_GRE);
+    }
+
+    #[test]
+    fn build_checksum() {
+        let data = GREBuilder::new()
+            .with_protocol(ext::vsf_tr06_2::PROTOCOL_REDUCED_OVERHEAD)
+            .with_checksum(true)
+            .with_sequence_number(7)
+            .to_vec(&[0xab, 0xcd, 0xef])
+            .unwrap();
+        let gre = packet(&data);
+        assert!(gre.verify_checksum().unwrap().unwrap());
+        assert_eq!(gre.sequence_number().unwrap().unwrap(), 7);
+        let mut corrupted = data.clone();
+        corrupted[12] ^= 0xff;
+        assert!(!packet(&corrupted).verify_checksum().unwrap().unwrap());
+    }
+
+    #[test]
+    fn build_rist_extension() {
+        let data = GREBuilder::new()
+            .with_protocol(ext::vsf_tr06_2::PROTOCOL_REDUCED_OVERHEAD)
+            .with_key(1)
+            .with_rist_extension(
+                ext::vsf_tr06_2::Extension::new()
+                    .with_rist_gre_version(1)
+                    .with_key_length(256),
+            )
+            .to_vec(&[])
+            .unwrap();
+        let gre = packet(&data);
+        assert!(gre.has_key());
+        assert_eq!(gre.version(), 0);
+        let ext = gre.ext0::<ext::vsf_tr06_2::Extension>();
+        assert_eq!(ext.rist_gre_version(), 1);
+        assert_eq!(ext.key_length(), Some(256));
+    }
+
+    #[test]
+    fn build_not_enough_space() {
+        let mut buf = [0u8; 7];
+        assert!(GREBuilder::new().with_key(1).write(&[], &mut buf).is_err());
+    }
+
+    #[test]
+    fn rist_payload() {
+        use ext::vsf_tr06_2::*;
+
+        let gre = packet(&EXAMPLE_GRE);
+        assert_eq!(
+            gre.rist_payload().unwrap(),
+            RistGrePayload::FullDatagram(&EXAMPLE_GRE[4..])
+        );
+
+        let mut payload = vsf_header(VSF_SUBTYPE_KEEP_ALIVE).to_vec();
+        payload.extend_from_slice(&[1, 2, 3, 4]);
+        let data = GREBuilder::new()
+            .with_protocol(PROTOCOL_VSF)
+            .to_vec(&payload)
+            .unwrap();
+        assert_eq!(
+            packet(&data).rist_payload().unwrap(),
+            RistGrePayload::KeepAlive(&[1, 2, 3, 4])
+        );
+
+        // reduced overhead packets start with the reduced UDP header, not with a VSF header
+        let data = GREBuilder::new()
+            .with_protocol(PROTOCOL_REDUCED_OVERHEAD)
+            .with_rist_extension(Extension::new().with_reduced_overhead(true))
+            .to_vec(&payload)
+            .unwrap();
+        let gre = packet(&data);
+        assert!(gre.ext0::<Extension>().is_reduced_overhead());
+        assert_eq!(
+            gre.rist_payload().unwrap(),
+            RistGrePayload::ReducedOverhead(&payload)
+        );
+        let data = GREBuilder::new()
+            .with_protocol(PROTOCOL_REDUCED_OVERHEAD)
+            .to_vec(&[0, 0])
+            .unwrap();
+        assert_eq!(
+            packet(&data).rist_payload().unwrap(),
+            RistGrePayload::ReducedOverhead(&[0, 0])
+        );
+
+        payload[2..4].copy_from_slice(&VSF_SUBTYPE_REDUCED_OVERHEAD.to_be_bytes());
+        let data = GREBuilder::new()
+            .with_protocol(PROTOCOL_VSF)
+            .to_vec(&payload)
+            .unwrap();
+        assert_eq!(
+            packet(&data).rist_payload().unwrap(),
+            RistGrePayload::ReducedOverhead(&[1, 2, 3, 4])
+        );
+
+        payload[2..4].copy_from_slice(&[0x12, 0x34]);
+        let data = GREBuilder::new()
+            .with_protocol(PROTOCOL_VSF)
+            .to_vec(&payload)
+            .unwrap();
+        assert!(matches!(
+            packet(&data).rist_payload().unwrap(),
+            RistGrePayload::Unknown {
+                protocol: PROTOCOL_VSF,
+                ..
+            }
+        ));
+
+        let data = GREBuilder::new()
+            .with_protocol(PROTOCOL_VSF)
+            .to_vec(&[0, 0])
+            .unwrap();
+        assert!(packet(&data).rist_payload().is_err());
+    }
+
+    #[test]
+    fn rist_extension_flags() {
+        use ext::vsf_tr06_2::Extension;
+
+        let ext = Extension::new()
+            .with_reduced_overhead(true)
+            .with_rist_gre_version(1)
+            .with_key_length(256);
+        assert!(ext.is_reduced_overhead());
+        assert!(ext.is_key_length_256());
+        assert_eq!(<[u8; 2]>::from(ext), [0x08, 0x48]);
+        let ext = ext
+            .with_reduced_overhead(false)
+            .with_key_length(128)
+            .with_rist_gre_version(0);
+        assert_eq!(ext, Extension::new());
+        assert_eq!(ext.key_length(), None);
+    }
 }
@@ -37,7 +37,7 @@ pub mod general {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum Error {
     /// General error
     General(general::Error),
@@ -46,7 +46,7 @@ pub enum Error {
     V4(super::v4::error::Error),
 
     /// Ipv6 related error
-    V6(),
+    V6(super::v6::error::Error),
 }
 
 impl From<super::v4::error::Error> for Error {
@@ -55,6 +55,12 @@ impl From<super::v4::error::Error> for Error {
     }
 }
 
+impl From<super::v6::error::Error> for Error {
+    fn from(err: super::v6::error::Error) -> Self {
+        Error::V6(err)
+    }
+}
+
 impl Error {
     /// True if the error is a Ipv4-related error
     fn is_v4(&self) -> bool {
@@ -63,7 +69,7 @@ impl Error {
 
     /// True if the error is Ipv6-related error
     fn is_v6(&self) -> bool {
-        matches!(self, Error::V6())
+        matches!(self, Error::V6(_))
     }
 
     /// True if the error is a general error
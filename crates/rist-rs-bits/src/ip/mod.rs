@@ -2,7 +2,7 @@
 use core::{convert::TryFrom, fmt::Display};
 
 use super::util;
-mod error;
+pub mod error;
 
 /// Read the version number of an ip packet.
 /// Returns the Ip version fields value or an error if the slice is empty
@@ -14,8 +14,11 @@ pub fn read_ip_version(data: &[u8]) -> Result<u8, error::Error> {
     }
 }
 
-enum IpPacketView<'a> {
+/// View over an Ipv4 or Ipv6 packet
+#[derive(Debug, Clone, Copy)]
+pub enum IpPacketView<'a> {
     V4(v4::Ipv4PacketView<'a>),
+    V6(v6::Ipv6PacketView<'a>),
 }
 
 impl<'a> TryFrom<&'a [u8]> for IpPacketView<'a> {
@@ -24,6 +27,7 @@ impl<'a> TryFrom<&'a [u8]> for IpPacketView<'a> {
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
         match read_ip_version(data)? {
             4 => Ok(IpPacketView::V4(v4::Ipv4PacketView::try_from(data)?)),
+            6 => Ok(IpPacketView::V6(v6::Ipv6PacketView::try_from(data)?)),
             v => Err(error::Error::General(
                 error::general::ip_version_not_implemented(v),
             )),
@@ -32,32 +36,47 @@ impl<'a> TryFrom<&'a [u8]> for IpPacketView<'a> {
 }
 
 impl<'a> IpPacketView<'a> {
-    fn source_addr(&self) -> rist_rs_types::net::IpAddr {
+    /// Get the source address
+    pub fn source_addr(&self) -> rist_rs_types::net::IpAddr {
         match self {
             Self::V4(a) => a.source_addr().into(),
+            Self::V6(a) => a.source_addr().into(),
         }
     }
 
-    fn dest_addr(&self) -> rist_rs_types::net::IpAddr {
+    /// Get the destination address
+    pub fn dest_addr(&self) -> rist_rs_types::net::IpAddr {
         match self {
             Self::V4(a) => a.dest_addr().into(),
+            Self::V6(a) => a.dest_addr().into(),
         }
     }
 
-    fn v4(&self) -> Option<v4::Ipv4PacketView<'a>> {
+    /// Get the Ipv4 packet, `None` if this is an Ipv6 packet
+    pub fn v4(&self) -> Option<v4::Ipv4PacketView<'a>> {
         match self {
             Self::V4(v) => Some(*v),
             _ => None,
         }
     }
+
+    /// Get the Ipv6 packet, `None` if this is an Ipv4 packet
+    pub fn v6(&self) -> Option<v6::Ipv6PacketView<'a>> {
+        match self {
+            Self::V6(v) => Some(*v),
+            _ => None,
+        }
+    }
 }
 
 impl<'a> Display for IpPacketView<'a> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             IpPacketView::V4(v4) => v4.fmt(f),
+            IpPacketView::V6(v6) => v6.fmt(f),
         }
     }
 }
 
 pub mod v4;
+pub mod v6;
@@ -0,0 +1,255 @@
+use rist_rs_types::net::Ipv4Addr;
+
+use super::{error, IPV4_BASE_HEADER_LEN, IPV4_CHECKSUM_FIELD};
+use crate::util;
+
+/// Builder for Ipv4 packets without options. Collects the header fields and writes them
+/// together with a payload into a caller-supplied buffer. The total length and the header
+/// checksum are computed when writing.
+#[derive(Debug, Clone, Copy)]
+pub struct Ipv4PacketBuilder {
+    dscp: u8,
+    ecn: u8,
+    identification: u16,
+    df: bool,
+    mf: bool,
+    offset: usize,
+    ttl: u8,
+    protocol: u8,
+    source: Ipv4Addr,
+    destination: Ipv4Addr,
+}
+
+impl Default for Ipv4PacketBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ipv4PacketBuilder {
+    /// TTL used if none is set
+    pub const DEFAULT_TTL: u8 = 64;
+
+    /// Largest fragment offset in bytes that can be represented
+    const OFFSET_MAX: usize = 0x1fff * 8;
+
+    /// Create a new builder with a TTL of 64 and all other fields set to zero
+    pub fn new() -> Self {
+        Self {
+            dscp: 0,
+            ecn: 0,
+            identification: 0,
+            df: false,
+            mf: false,
+            offset: 0,
+            ttl: Self::DEFAULT_TTL,
+            protocol: 0,
+            source: Ipv4Addr::from(0),
+            destination: Ipv4Addr::from(0),
+        }
+    }
+
+    /// Set the DSCP value. Must fit into 6 bits
+    pub fn with_dscp(mut self, dscp: u8) -> Self {
+        self.dscp = dscp;
+        self
+    }
+
+    /// Set the ECN value. Must fit into 2 bits
+    pub fn with_ecn(mut self, ecn: u8) -> Self {
+        self.ecn = ecn;
+        self
+    }
+
+    /// Set the fragmentation identification value
+    pub fn with_identification(mut self, identification: u16) -> Self {
+        self.identification = identification;
+        self
+    }
+
+    /// Set the DF (Don't Fragment) flag
+    pub fn with_df(mut self, df: bool) -> Self {
+        self.df = df;
+        self
+    }
+
+    /// Set the MF (More Fragments) flag
+    pub fn with_mf(mut self, mf: bool) -> Self {
+        self.mf = mf;
+        self
+    }
+
+    /// Set the fragment offset in bytes. Must be a multiple of 8
+    pub fn with_offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Set the TTL (Time To Live)
+    pub fn with_ttl(mut self, ttl: u8) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the protocol of the payload
+    pub fn with_protocol(mut self, protocol: u8) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Set the source address
+    pub fn with_source_addr(mut self, source: Ipv4Addr) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Set the destination address
+    pub fn with_dest_addr(mut self, destination: Ipv4Addr) -> Self {
+        self.destination = destination;
+        self
+    }
+
+    /// Length of the header
+    pub fn header_len(&self) -> usize {
+        IPV4_BASE_HEADER_LEN
+    }
+
+    /// Total length of a packet written by this builder with a payload of `payload_len` bytes
+    pub fn packet_len(&self, payload_len: usize) -> usize {
+        self.header_len() + payload_len
+    }
+
+    /// Check that all configured values can be represented in the header
+    fn validate(&self, payload_len: usize) -> Result<(), error::Error> {
+        if self.dscp > 0x3f {
+            Err(error::invalid_value(
+                self.dscp as usize,
+                &"Ipv4Packet::DSCP",
+            ))
+        } else if self.ecn > 0x3 {
+            Err(error::invalid_value(self.ecn as usize, &"Ipv4Packet::ECN"))
+        } else if !self.offset.is_multiple_of(8) || self.offset > Self::OFFSET_MAX {
+            Err(error::invalid_value(self.offset, &"Ipv4Packet::Offset"))
+        } else if self.packet_len(payload_len) > u16::MAX as usize {
+            Err(error::invalid_value(
+                self.packet_len(payload_len),
+                &"Ipv4Packet::TotalLength",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Write the packet with the given payload into `buf`. Returns the number of bytes
+    /// written or an error if the buffer is too small or the header can not be represented.
+    pub fn write(&self, payload: &[u8], buf: &mut [u8]) -> Result<usize, error::Error> {
+        let len = self.packet_len(payload.len());
+        if buf.len() < len {
+            return Err(error::not_enough_space(len, buf.len()));
+        }
+        let header_len = self.write_header(payload.len(), buf)?;
+        buf[header_len..len].copy_from_slice(payload);
+        Ok(len)
+    }
+
+    /// Write only the header of a packet with a payload of `payload_len` bytes into `buf`,
+    /// leaving the payload to the caller. Returns the length of the header or an error if the
+    /// buffer is too small for the header or the header can not be represented.
+    pub fn write_header(&self, payload_len: usize, buf: &mut [u8]) -> Result<usize, error::Error> {
+        self.validate(payload_len)?;
+        let len = self.packet_len(payload_len);
+        if buf.len() < IPV4_BASE_HEADER_LEN {
+            return Err(error::not_enough_space(IPV4_BASE_HEADER_LEN, buf.len()));
+        }
+        let header = &mut buf[..IPV4_BASE_HEADER_LEN];
+        let flags_and_offset = if self.df { 0x4000 } else { 0 }
+            | if self.mf { 0x2000 } else { 0 }
+            | (self.offset / 8) as u16;
+        header[0] = 0x45;
+        header[1] = (self.dscp << 2) | self.ecn;
+        header[2..4].copy_from_slice(&(len as u16).to_be_bytes());
+        header[4..6].copy_from_slice(&self.identification.to_be_bytes());
+        header[6..8].copy_from_slice(&flags_and_offset.to_be_bytes());
+        header[8] = self.ttl;
+        header[9] = self.protocol;
+        header[IPV4_CHECKSUM_FIELD].fill(0);
+        header[12..16].copy_from_slice(&self.source.octets());
+        header[16..20].copy_from_slice(&self.destination.octets());
+        let sum = util::checksum::u16(header, &[IPV4_CHECKSUM_FIELD]);
+        header[IPV4_CHECKSUM_FIELD].copy_from_slice(&sum.to_be_bytes());
+        Ok(IPV4_BASE_HEADER_LEN)
+    }
+
+    /// Write the packet with the given payload into a newly allocated vector
+    #[cfg(feature = "alloc")]
+    pub fn to_vec(&self, payload: &[u8]) -> Result<alloc::vec::Vec<u8>, error::Error> {
+        let mut buf = alloc::vec![0u8; self.packet_len(payload.len())];
+        self.write(payload, &mut buf)?;
+        Ok(buf)
+    }
+
+    /// Split `payload` into fragments that fit into packets of at most `mtu` bytes. Every
+    /// fragment is returned with a builder that has the offset and MF flag set accordingly.
+    /// Returns an error if the DF flag is set and the payload does not fit, or if the MTU is too
+    /// small to carry any payload.
+    pub fn fragments<'p>(
+        &self,
+        payload: &'p [u8],
+        mtu: usize,
+    ) -> Result<Fragments<'p>, error::Error> {
+        let fragment_len = if self.packet_len(payload.len()) <= mtu {
+            // fits into a single packet, no need to align the fragment length
+            payload.len()
+        } else if self.df {
+            return Err(error::cannot_fragment(mtu));
+        } else {
+            mtu.saturating_sub(self.header_len()) & !7
+        };
+        if mtu < self.header_len() || (fragment_len == 0 && !payload.is_empty()) {
+            return Err(error::cannot_fragment(mtu));
+        }
+        self.validate(0)?;
+        if self.offset + payload.len() > Self::OFFSET_MAX + fragment_len {
+            return Err(error::invalid_value(
+                self.offset + payload.len(),
+                &"Ipv4Packet::Offset",
+            ));
+        }
+        Ok(Fragments {
+            builder: *self,
+            payload,
+            fragment_len,
+            pos: 0,
+        })
+    }
+}
+
+/// Iterator over the fragments of a payload, see [Ipv4PacketBuilder::fragments]
+#[derive(Debug, Clone)]
+pub struct Fragments<'p> {
+    builder: Ipv4PacketBuilder,
+    payload: &'p [u8],
+    fragment_len: usize,
+    pos: usize,
+}
+
+impl<'p> Iterator for Fragments<'p> {
+    type Item = (Ipv4PacketBuilder, &'p [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // an empty payload still yields a single packet
+        if self.pos >= self.payload.len() && !(self.pos == 0 && self.payload.is_empty()) {
+            return None;
+        }
+        let end = (self.pos + self.fragment_len).min(self.payload.len());
+        let last = end == self.payload.len();
+        let builder = self
+            .builder
+            .with_offset(self.builder.offset + self.pos)
+            .with_mf(!last || self.builder.mf);
+        let fragment = &self.payload[self.pos..end];
+        // move past the end to terminate after an empty payload
+        self.pos = end.max(1);
+        Some((builder, fragment))
+    }
+}
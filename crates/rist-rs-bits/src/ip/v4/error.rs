@@ -17,6 +17,24 @@ pub enum ErrorKind {
 
     /// The value of the IHL field is out of the legal bounds
     HeaderTooLong,
+
+    /// The buffer supplied for writing a packet is too small
+    NotEnoughSpace { need: usize, got: usize },
+
+    /// The value does not fit into the field it should be written to
+    InvalidValue {
+        value: usize,
+        field: &'static &'static str,
+    },
+
+    /// The payload can not be fragmented, either because the DF flag is set or the MTU is too small
+    CannotFragment { mtu: usize },
+
+    /// A fragment is inconsistent with the fragments of the same datagram received before
+    InvalidFragment,
+
+    /// A datagram can not be reassembled without exceeding the memory limit
+    ReassemblyLimitExceeded { limit: usize },
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -37,6 +55,30 @@ impl Display for Error {
             ErrorKind::HeaderTooLong => {
                 write!(f, "Header reported as longer than total packet size")
             }
+            ErrorKind::NotEnoughSpace { need, got } => {
+                write!(
+                    f,
+                    "Not enough space to write packet, need {need} bytes, got {got} bytes"
+                )
+            }
+            ErrorKind::InvalidValue { value, field } => {
+                write!(f, "Value {} does not fit into field [{}]", value, **field)
+            }
+            ErrorKind::CannotFragment { mtu } => {
+                write!(f, "Packet can not be fragmented for an MTU of {mtu} bytes")
+            }
+            ErrorKind::InvalidFragment => {
+                write!(
+                    f,
+                    "Fragment is inconsistent with the datagram it belongs to"
+                )
+            }
+            ErrorKind::ReassemblyLimitExceeded { limit } => {
+                write!(
+                    f,
+                    "Reassembling the datagram exceeds the memory limit of {limit} bytes"
+                )
+            }
         }
     }
 }
@@ -67,3 +109,28 @@ pub(super) fn not_enough_data(need: usize, got: usize, field: &'static &'static
 pub(super) fn header_to_long() -> Error {
     Error::new(ErrorKind::HeaderTooLong)
 }
+
+/// Make an error that indicates that the buffer is too small to write the packet
+pub(super) fn not_enough_space(need: usize, got: usize) -> Error {
+    Error::new(ErrorKind::NotEnoughSpace { need, got })
+}
+
+/// Make an error that indicates that a value does not fit into a header field
+pub(super) fn invalid_value(value: usize, field: &'static &'static str) -> Error {
+    Error::new(ErrorKind::InvalidValue { value, field })
+}
+
+/// Make an error that indicates that the packet can not be fragmented
+pub(super) fn cannot_fragment(mtu: usize) -> Error {
+    Error::new(ErrorKind::CannotFragment { mtu })
+}
+
+/// Make an error that indicates that a fragment is inconsistent with its datagram
+pub(super) fn invalid_fragment() -> Error {
+    Error::new(ErrorKind::InvalidFragment)
+}
+
+/// Make an error that indicates that reassembling a datagram exceeds the memory limit
+pub(super) fn reassembly_limit_exceeded(limit: usize) -> Error {
+    Error::new(ErrorKind::ReassemblyLimitExceeded { limit })
+}
@@ -1,8 +1,20 @@
+mod builder;
 /// Error types for Ipv4 packets
 pub mod error;
+#[cfg(feature = "alloc")]
+mod reassembly;
+mod view_mut;
+
+pub use builder::{Fragments, Ipv4PacketBuilder};
+#[cfg(feature = "alloc")]
+pub use reassembly::{FragmentKey, ReassemblyBuffer};
+pub use view_mut::Ipv4PacketViewMut;
 
 /// Minimum length of the Ipv4 header
-const IPV4_BASE_HEADER_LEN: usize = 20;
+pub(crate) const IPV4_BASE_HEADER_LEN: usize = 20;
+
+/// Position of the header checksum
+pub(crate) const IPV4_CHECKSUM_FIELD: core::ops::Range<usize> = 10..12;
 
 /// View over an immutable slice of data that can be interpreted as an Ipv4 packet.
 #[derive(Debug, Clone, Copy)]
@@ -144,6 +156,13 @@ impl<'a> Ipv4PacketView<'a> {
         super::util::read_int!(self.data, u16, 10)
     }
 
+    /// Verify the header checksum. Returns an error if the header length exceeds the slice
+    pub fn verify_checksum(&self) -> Result<bool, error::Error> {
+        self.valid_header_len().map(|len| {
+            super::util::checksum::u16(&self.data[..len], &[IPV4_CHECKSUM_FIELD]) == self.checksum()
+        })
+    }
+
     /// Get the source address
     pub fn source_addr(&self) -> rist_rs_types::net::Ipv4Addr {
         [self.data[12], self.data[13], self.data[14], self.data[15]].into()
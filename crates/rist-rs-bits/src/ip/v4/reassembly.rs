@@ -0,0 +1,274 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::{ops::Range, time::Duration};
+
+use rist_rs_types::{net::Ipv4Addr, traits::time::clock::TimePoint};
+
+use super::{error, Ipv4PacketView, Ipv4PacketViewMut};
+
+/// Identifies the fragments that belong to the same datagram (RFC791)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FragmentKey {
+    pub source: Ipv4Addr,
+    pub destination: Ipv4Addr,
+    pub protocol: u8,
+    pub identification: u16,
+}
+
+impl<'a> From<&Ipv4PacketView<'a>> for FragmentKey {
+    fn from(packet: &Ipv4PacketView<'a>) -> Self {
+        Self {
+            source: packet.source_addr(),
+            destination: packet.dest_addr(),
+            protocol: packet.protocol(),
+            identification: packet.identification(),
+        }
+    }
+}
+
+/// A datagram for which not all fragments have been received yet
+#[derive(Debug)]
+struct Partial<T> {
+    /// Time the first fragment was received
+    created: T,
+    /// Header of the fragment with offset 0, empty until it is received
+    header: Vec<u8>,
+    /// Payload received so far
+    data: Vec<u8>,
+    /// Sorted, non-overlapping ranges of the payload that have been received
+    received: Vec<Range<usize>>,
+    /// Length of the payload, known once the last fragment is received
+    end: Option<usize>,
+}
+
+impl<T> Partial<T> {
+    fn new(created: T) -> Self {
+        Self {
+            created,
+            header: Vec::new(),
+            data: Vec::new(),
+            received: Vec::new(),
+            end: None,
+        }
+    }
+
+    /// Number of bytes accounted against the memory limit
+    fn memory(&self) -> usize {
+        self.header.len() + self.data.len()
+    }
+
+    /// Check that a fragment covering `range` does not contradict the fragments received before
+    fn accepts(&self, range: &Range<usize>, last: bool) -> bool {
+        match self.end {
+            Some(end) if last => end == range.end,
+            Some(end) => range.end <= end,
+            None if last => self.received.last().is_none_or(|r| r.end <= range.end),
+            None => true,
+        }
+    }
+
+    /// Copy the payload of a fragment into place and mark its range as received
+    fn insert(&mut self, offset: usize, header: &[u8], payload: &[u8], last: bool) {
+        let range = offset..offset + payload.len();
+        if offset == 0 && self.header.is_empty() {
+            self.header.extend_from_slice(header);
+        }
+        if last {
+            self.end = Some(range.end);
+        }
+        if self.data.len() < range.end {
+            self.data.resize(range.end, 0);
+        }
+        self.data[range.clone()].copy_from_slice(payload);
+        if range.is_empty() {
+            return;
+        }
+        // merge with all adjacent or overlapping ranges
+        let mut merged = range;
+        self.received.retain(|r| {
+            if r.end < merged.start || r.start > merged.end {
+                true
+            } else {
+                merged.start = merged.start.min(r.start);
+                merged.end = merged.end.max(r.end);
+                false
+            }
+        });
+        let pos = self.received.partition_point(|r| r.start < merged.start);
+        self.received.insert(pos, merged);
+    }
+
+    fn is_complete(&self) -> bool {
+        match self.end {
+            Some(end) => {
+                !self.header.is_empty() && (end == 0 || self.received.first() == Some(&(0..end)))
+            }
+            None => false,
+        }
+    }
+}
+
+/// Reassembles fragmented Ipv4 datagrams. Fragments may arrive in any order and are
+/// collected per [FragmentKey] until the datagram is complete. Incomplete datagrams are
+/// dropped once they are older than the configured timeout, and the oldest incomplete
+/// datagrams are dropped if the memory limit would be exceeded otherwise.
+///
+/// The current time is supplied by the caller, so the buffer can be used without a clock
+/// implementation from std.
+#[derive(Debug)]
+pub struct ReassemblyBuffer<T> {
+    timeout: Duration,
+    memory_limit: usize,
+    memory: usize,
+    partials: BTreeMap<FragmentKey, Partial<T>>,
+}
+
+impl<T: TimePoint> ReassemblyBuffer<T> {
+    /// Reassembly timeout suggested by RFC791
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(15);
+
+    /// Create a new buffer that drops incomplete datagrams after `timeout` and holds at most
+    /// `memory_limit` bytes of headers and payload
+    pub fn new(timeout: Duration, memory_limit: usize) -> Self {
+        Self {
+            timeout,
+            memory_limit,
+            memory: 0,
+            partials: BTreeMap::new(),
+        }
+    }
+
+    /// Number of incomplete datagrams
+    pub fn len(&self) -> usize {
+        self.partials.len()
+    }
+
+    /// Returns true if there are no incomplete datagrams
+    pub fn is_empty(&self) -> bool {
+        self.partials.is_empty()
+    }
+
+    /// Number of bytes currently held for incomplete datagrams
+    pub fn memory_usage(&self) -> usize {
+        self.memory
+    }
+
+    /// Drop all incomplete datagrams
+    pub fn clear(&mut self) {
+        self.partials.clear();
+        self.memory = 0;
+    }
+
+    /// Drop all incomplete datagrams that are older than the timeout. Returns the number of
+    /// dropped datagrams
+    pub fn expire(&mut self, now: T) -> usize {
+        let before = self.partials.len();
+        let timeout = self.timeout;
+        let mut freed = 0;
+        self.partials.retain(|_, p| {
+            let keep = now.saturating_duration_since(p.created) < timeout;
+            if !keep {
+                freed += p.memory();
+            }
+            keep
+        });
+        self.memory -= freed;
+        before - self.partials.len()
+    }
+
+    /// Remove the incomplete datagram stored under `key`
+    fn remove(&mut self, key: &FragmentKey) -> Option<Partial<T>> {
+        let partial = self.partials.remove(key)?;
+        self.memory -= partial.memory();
+        Some(partial)
+    }
+
+    /// Drop the oldest incomplete datagrams other than `key` until `additional` bytes fit into
+    /// the memory limit. Returns false if that is not possible
+    fn make_room(&mut self, key: &FragmentKey, additional: usize) -> bool {
+        while self.memory + additional > self.memory_limit {
+            let oldest = self
+                .partials
+                .iter()
+                .filter(|(k, _)| *k != key)
+                .min_by_key(|(_, p)| p.created)
+                .map(|(k, _)| *k);
+            match oldest {
+                Some(oldest) => {
+                    self.remove(&oldest);
+                }
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Add a packet to the buffer. Returns the complete datagram if the packet was its last
+    /// missing fragment. Packets that are not fragmented are returned as they are.
+    ///
+    /// Returns an error if the packet is truncated, if the fragment contradicts the fragments
+    /// received before or if the datagram exceeds the memory limit. In the latter two cases
+    /// all fragments of the datagram are dropped.
+    pub fn push(
+        &mut self,
+        now: T,
+        packet: &Ipv4PacketView<'_>,
+    ) -> Result<Option<Vec<u8>>, error::Error> {
+        self.expire(now);
+        let payload = packet.payload()?;
+        let header = &packet.data[..packet.header_len()];
+        if !packet.is_fragmented() {
+            return Ok(Some(packet.data[..packet.total_len()].to_vec()));
+        }
+        let offset = packet.offset();
+        let last = !packet.mf();
+        let range = offset..offset + payload.len();
+        // all fragments but the last one carry a multiple of 8 bytes
+        if (!last && payload.len() % 8 != 0) || header.len() + range.end > u16::MAX as usize {
+            return Err(error::invalid_fragment());
+        }
+        let key = FragmentKey::from(packet);
+        let partial = self
+            .partials
+            .entry(key)
+            .or_insert_with(|| Partial::new(now));
+        if !partial.accepts(&range, last) {
+            self.remove(&key);
+            return Err(error::invalid_fragment());
+        }
+        let additional = range.end.saturating_sub(partial.data.len())
+            + if offset == 0 && partial.header.is_empty() {
+                header.len()
+            } else {
+                0
+            };
+        if !self.make_room(&key, additional) {
+            self.remove(&key);
+            return Err(error::reassembly_limit_exceeded(self.memory_limit));
+        }
+        let partial = self
+            .partials
+            .get_mut(&key)
+            .expect(rist_rs_types::internal::INTERNAL_ERR_PRE_VALIDATED);
+        partial.insert(offset, header, payload, last);
+        self.memory += additional;
+        if !partial.is_complete() {
+            return Ok(None);
+        }
+        let partial = self
+            .remove(&key)
+            .expect(rist_rs_types::internal::INTERNAL_ERR_PRE_VALIDATED);
+        let total_len = partial.header.len() + partial.data.len();
+        if total_len > u16::MAX as usize {
+            return Err(error::invalid_fragment());
+        }
+        let mut datagram = partial.header;
+        datagram.extend_from_slice(&partial.data);
+        let mut view = Ipv4PacketViewMut::try_from(datagram.as_mut_slice())
+            .expect(rist_rs_types::internal::INTERNAL_ERR_PRE_VALIDATED);
+        view.set_total_len(total_len as u16);
+        view.set_mf(false);
+        view.set_offset(0)?;
+        view.update_checksum()?;
+        Ok(Some(datagram))
+    }
+}
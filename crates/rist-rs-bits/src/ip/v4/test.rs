@@ -8,7 +8,7 @@ const IP_BASIC: [u8; 52] = [
     0x62, 0xed, 0xef, 0xf4,
 ];
 
-fn packet(data: &[u8]) -> Ipv4PacketView {
+fn packet(data: &[u8]) -> Ipv4PacketView<'_> {
     Ipv4PacketView::try_from(data).unwrap()
 }
 
@@ -150,7 +150,249 @@ fn broken_total_len() {
     ]);
 
     // broken total length -> no payload
-    assert!(matches!(ip.payload(), Err(_)));
+    assert!(ip.payload().is_err());
     // options still valid because header is valid
-    assert!(matches!(ip.options(), Ok(_)));
+    assert!(ip.options().is_ok());
+}
+
+#[test]
+fn verify_checksum() {
+    assert!(packet(&IP_BASIC).verify_checksum().unwrap());
+    let mut data = IP_BASIC;
+    data[8] = 47;
+    assert!(!packet(&data).verify_checksum().unwrap());
+}
+
+#[test]
+fn build() {
+    let data = Ipv4PacketBuilder::new()
+        .with_identification(0x615f)
+        .with_df(true)
+        .with_ttl(48)
+        .with_protocol(6)
+        .with_source_addr([84, 246, 251, 201].into())
+        .with_dest_addr([192, 168, 5, 89].into())
+        .to_vec(&IP_BASIC[20..])
+        .unwrap();
+    assert_eq!(data, IP_BASIC);
+}
+
+#[test]
+fn build_invalid() {
+    let mut buf = [0u8; 20];
+    assert!(Ipv4PacketBuilder::new()
+        .with_dscp(64)
+        .write(&[], &mut buf)
+        .is_err());
+    assert!(Ipv4PacketBuilder::new()
+        .with_ecn(4)
+        .write(&[], &mut buf)
+        .is_err());
+    assert!(Ipv4PacketBuilder::new()
+        .with_offset(7)
+        .write(&[], &mut buf)
+        .is_err());
+    assert!(Ipv4PacketBuilder::new().write(&[0], &mut buf).is_err());
+    assert!(Ipv4PacketBuilder::new()
+        .to_vec(&[0; u16::MAX as usize])
+        .is_err());
+}
+
+#[test]
+fn build_fragments() {
+    let payload = (0..100u8).collect::<Vec<_>>();
+    let builder = Ipv4PacketBuilder::new()
+        .with_identification(0xf5af)
+        .with_protocol(17);
+    let fragments = builder
+        .fragments(&payload, 60)
+        .unwrap()
+        .map(|(b, p)| b.to_vec(p).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(fragments.len(), 3);
+    let views = fragments.iter().map(|f| packet(f)).collect::<Vec<_>>();
+    assert!(views.iter().all(|v| v.total_len() <= 60));
+    assert!(views.iter().all(|v| v.verify_checksum().unwrap()));
+    assert!(views.iter().all(|v| v.identification() == 0xf5af));
+    assert_eq!(
+        views.iter().map(|v| v.offset()).collect::<Vec<_>>(),
+        [0, 40, 80]
+    );
+    assert_eq!(
+        views.iter().map(|v| v.mf()).collect::<Vec<_>>(),
+        [true, true, false]
+    );
+    let joined = views
+        .iter()
+        .flat_map(|v| v.payload().unwrap().iter().copied())
+        .collect::<Vec<_>>();
+    assert_eq!(joined, payload);
+
+    assert_eq!(builder.fragments(&[], 60).unwrap().count(), 1);
+    assert!(builder.fragments(&payload, 27).is_err());
+    assert!(builder.with_df(true).fragments(&payload, 60).is_err());
+    assert_eq!(
+        builder
+            .with_df(true)
+            .fragments(&payload, 120)
+            .unwrap()
+            .count(),
+        1
+    );
+}
+
+#[test]
+fn modify() {
+    let mut data = IP_BASIC;
+    let mut ip = Ipv4PacketViewMut::try_from(data.as_mut_slice()).unwrap();
+    assert_eq!(ip.decrement_ttl(), Some(47));
+    ip.set_source_addr([10, 0, 0, 1].into());
+    ip.set_dest_addr([10, 0, 0, 2].into());
+    ip.set_dscp(46).unwrap();
+    ip.set_ecn(1).unwrap();
+    ip.set_identification(0x1234);
+    ip.set_df(false);
+    ip.set_mf(true);
+    ip.set_offset(16).unwrap();
+    ip.set_protocol(17);
+    assert!(ip.set_offset(3).is_err());
+    assert!(ip.set_dscp(64).is_err());
+    ip.payload_mut().unwrap()[0] = 0xff;
+    let view = ip.as_view();
+    assert!(view.verify_checksum().unwrap());
+    assert_eq!(view.ttl(), 47);
+    assert_eq!(view.dscp(), 46);
+    assert_eq!(view.ecn(), 1);
+    assert_eq!(view.identification(), 0x1234);
+    assert!(!view.df());
+    assert!(view.mf());
+    assert_eq!(view.offset(), 16);
+    assert_eq!(view.protocol(), 17);
+    assert_eq!(view.source_addr(), [10, 0, 0, 1].into());
+    assert_eq!(view.payload().unwrap()[0], 0xff);
+    let sum = view.checksum();
+    assert_eq!(ip.update_checksum().unwrap(), sum);
+    ip.set_total_len(21);
+    let view = Ipv4PacketView::from(ip);
+    assert_eq!(view.payload().unwrap().len(), 1);
+    assert!(view.verify_checksum().unwrap());
+}
+
+#[cfg(test)]
+fn fragmented(identification: u16, payload: &[u8], mtu: usize) -> (Vec<u8>, Vec<Vec<u8>>) {
+    let builder = Ipv4PacketBuilder::new()
+        .with_identification(identification)
+        .with_protocol(17)
+        .with_source_addr([10, 0, 0, 1].into())
+        .with_dest_addr([10, 0, 0, 2].into());
+    let fragments = builder
+        .fragments(payload, mtu)
+        .unwrap()
+        .map(|(b, p)| b.to_vec(p).unwrap())
+        .collect();
+    (builder.to_vec(payload).unwrap(), fragments)
+}
+
+#[test]
+fn reassemble_out_of_order() {
+    let now = std::time::Instant::now();
+    let mut buffer = ReassemblyBuffer::new(
+        ReassemblyBuffer::<std::time::Instant>::DEFAULT_TIMEOUT,
+        4096,
+    );
+    let payload = (0..100u8).collect::<Vec<_>>();
+    let (datagram, fragments) = fragmented(1, &payload, 60);
+    let (other, other_fragments) = fragmented(2, &payload[..50], 60);
+    assert_eq!(fragments.len(), 3);
+    assert!(buffer.push(now, &packet(&fragments[2])).unwrap().is_none());
+    assert!(buffer
+        .push(now, &packet(&other_fragments[1]))
+        .unwrap()
+        .is_none());
+    assert!(buffer.push(now, &packet(&fragments[0])).unwrap().is_none());
+    // duplicates are ignored
+    assert!(buffer.push(now, &packet(&fragments[0])).unwrap().is_none());
+    assert_eq!(buffer.len(), 2);
+    assert_eq!(
+        buffer.push(now, &packet(&fragments[1])).unwrap(),
+        Some(datagram)
+    );
+    assert_eq!(buffer.len(), 1);
+    assert_eq!(
+        buffer.push(now, &packet(&other_fragments[0])).unwrap(),
+        Some(other)
+    );
+    assert!(buffer.is_empty());
+    assert_eq!(buffer.memory_usage(), 0);
+    // packets that are not fragmented pass through
+    assert_eq!(
+        buffer.push(now, &packet(&IP_BASIC)).unwrap().as_deref(),
+        Some(IP_BASIC.as_slice())
+    );
+}
+
+#[test]
+fn reassemble_timeout() {
+    let now = std::time::Instant::now();
+    let timeout = std::time::Duration::from_secs(1);
+    let mut buffer = ReassemblyBuffer::new(timeout, 4096);
+    let (_, fragments) = fragmented(1, &[0; 100], 60);
+    assert!(buffer.push(now, &packet(&fragments[0])).unwrap().is_none());
+    assert_eq!(buffer.expire(now + timeout / 2), 0);
+    assert_eq!(buffer.expire(now + timeout), 1);
+    assert_eq!(buffer.memory_usage(), 0);
+    // the remaining fragments do not complete the datagram anymore
+    let later = now + timeout;
+    assert!(buffer
+        .push(later, &packet(&fragments[1]))
+        .unwrap()
+        .is_none());
+    assert!(buffer
+        .push(later, &packet(&fragments[2]))
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn reassemble_memory_limit() {
+    let now = std::time::Instant::now();
+    let mut buffer =
+        ReassemblyBuffer::new(ReassemblyBuffer::<std::time::Instant>::DEFAULT_TIMEOUT, 100);
+    let (_, first) = fragmented(1, &[0; 100], 60);
+    let (second, second_fragments) = fragmented(2, &[0; 60], 60);
+    assert!(buffer.push(now, &packet(&first[0])).unwrap().is_none());
+    // the oldest datagram is dropped to make room
+    let later = now + std::time::Duration::from_millis(1);
+    assert!(buffer
+        .push(later, &packet(&second_fragments[0]))
+        .unwrap()
+        .is_none());
+    assert_eq!(buffer.len(), 1);
+    assert_eq!(
+        buffer.push(later, &packet(&second_fragments[1])).unwrap(),
+        Some(second)
+    );
+    // a single datagram that exceeds the limit is dropped
+    let (_, large) = fragmented(3, &[0; 200], 60);
+    assert!(buffer.push(later, &packet(&large[4])).is_err());
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn reassemble_inconsistent() {
+    let now = std::time::Instant::now();
+    let mut buffer = ReassemblyBuffer::new(
+        ReassemblyBuffer::<std::time::Instant>::DEFAULT_TIMEOUT,
+        4096,
+    );
+    let (_, long) = fragmented(1, &[0; 100], 60);
+    let (_, short) = fragmented(1, &[0; 60], 60);
+    assert!(buffer.push(now, &packet(&long[2])).unwrap().is_none());
+    assert!(buffer.push(now, &packet(&short[1])).is_err());
+    assert!(buffer.is_empty());
+    // fragments other than the last one must carry a multiple of 8 bytes
+    let builder = Ipv4PacketBuilder::new().with_mf(true);
+    assert!(buffer
+        .push(now, &packet(&builder.to_vec(&[0; 7]).unwrap()))
+        .is_err());
 }
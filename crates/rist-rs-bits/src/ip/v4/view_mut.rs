@@ -0,0 +1,154 @@
+use rist_rs_types::net::Ipv4Addr;
+
+use super::{error, Ipv4PacketView, IPV4_CHECKSUM_FIELD};
+use crate::util;
+
+/// View over a mutable slice of data that can be interpreted as an Ipv4 packet. Setters
+/// update the header checksum incrementally (RFC1624), so a packet with a valid checksum
+/// stays valid after modification.
+#[derive(Debug)]
+pub struct Ipv4PacketViewMut<'a> {
+    data: &'a mut [u8],
+}
+
+impl<'a> TryFrom<&'a mut [u8]> for Ipv4PacketViewMut<'a> {
+    type Error = crate::ip::error::Error;
+
+    fn try_from(data: &'a mut [u8]) -> Result<Self, Self::Error> {
+        Ipv4PacketView::try_from(&*data)?;
+        Ok(Self { data })
+    }
+}
+
+impl<'a> From<Ipv4PacketViewMut<'a>> for Ipv4PacketView<'a> {
+    fn from(view: Ipv4PacketViewMut<'a>) -> Self {
+        Ipv4PacketView { data: view.data }
+    }
+}
+
+impl<'a> Ipv4PacketViewMut<'a> {
+    /// Get a read-only view over the packet
+    pub fn as_view(&self) -> Ipv4PacketView<'_> {
+        Ipv4PacketView { data: self.data }
+    }
+
+    /// Replace the 16-bit word at `offset` and update the checksum
+    fn replace_word(&mut self, offset: usize, new: [u8; 2]) {
+        let sum = util::read_int!(self.data, u16, IPV4_CHECKSUM_FIELD.start);
+        let sum = util::checksum::update(sum, &self.data[offset..offset + 2], &new);
+        self.data[offset..offset + 2].copy_from_slice(&new);
+        self.data[IPV4_CHECKSUM_FIELD].copy_from_slice(&sum.to_be_bytes());
+    }
+
+    /// Set the DSCP value. Returns an error if the value does not fit into 6 bits
+    pub fn set_dscp(&mut self, dscp: u8) -> Result<(), error::Error> {
+        if dscp > 0x3f {
+            return Err(error::invalid_value(dscp as usize, &"Ipv4Packet::DSCP"));
+        }
+        self.replace_word(0, [self.data[0], (dscp << 2) | (self.data[1] & 0x3)]);
+        Ok(())
+    }
+
+    /// Set the ECN value. Returns an error if the value does not fit into 2 bits
+    pub fn set_ecn(&mut self, ecn: u8) -> Result<(), error::Error> {
+        if ecn > 0x3 {
+            return Err(error::invalid_value(ecn as usize, &"Ipv4Packet::ECN"));
+        }
+        self.replace_word(0, [self.data[0], (self.data[1] & 0xfc) | ecn]);
+        Ok(())
+    }
+
+    /// Set the value of the total length field
+    pub fn set_total_len(&mut self, len: u16) {
+        self.replace_word(2, len.to_be_bytes());
+    }
+
+    /// Set the fragmentation identification value
+    pub fn set_identification(&mut self, identification: u16) {
+        self.replace_word(4, identification.to_be_bytes());
+    }
+
+    /// Set the DF (Don't Fragment) flag
+    pub fn set_df(&mut self, df: bool) {
+        let b = if df {
+            self.data[6] | 0x40
+        } else {
+            self.data[6] & !0x40
+        };
+        self.replace_word(6, [b, self.data[7]]);
+    }
+
+    /// Set the MF (More Fragments) flag
+    pub fn set_mf(&mut self, mf: bool) {
+        let b = if mf {
+            self.data[6] | 0x20
+        } else {
+            self.data[6] & !0x20
+        };
+        self.replace_word(6, [b, self.data[7]]);
+    }
+
+    /// Set the fragment offset in bytes. Returns an error if the offset is not a multiple of 8
+    /// or too large
+    pub fn set_offset(&mut self, offset: usize) -> Result<(), error::Error> {
+        if !offset.is_multiple_of(8) || offset / 8 > 0x1fff {
+            return Err(error::invalid_value(offset, &"Ipv4Packet::Offset"));
+        }
+        let [o0, o1] = ((offset / 8) as u16).to_be_bytes();
+        self.replace_word(6, [(self.data[6] & 0xe0) | o0, o1]);
+        Ok(())
+    }
+
+    /// Set the TTL (Time To Live)
+    pub fn set_ttl(&mut self, ttl: u8) {
+        self.replace_word(8, [ttl, self.data[9]]);
+    }
+
+    /// Decrement the TTL, as done by a router when forwarding a packet. Returns the new TTL
+    /// or `None` if the TTL already is 0
+    pub fn decrement_ttl(&mut self) -> Option<u8> {
+        let ttl = self.data[8].checked_sub(1)?;
+        self.set_ttl(ttl);
+        Some(ttl)
+    }
+
+    /// Set the protocol
+    pub fn set_protocol(&mut self, protocol: u8) {
+        self.replace_word(8, [self.data[8], protocol]);
+    }
+
+    /// Set the source address
+    pub fn set_source_addr(&mut self, addr: Ipv4Addr) {
+        let [a, b, c, d] = addr.octets();
+        self.replace_word(12, [a, b]);
+        self.replace_word(14, [c, d]);
+    }
+
+    /// Set the destination address
+    pub fn set_dest_addr(&mut self, addr: Ipv4Addr) {
+        let [a, b, c, d] = addr.octets();
+        self.replace_word(16, [a, b]);
+        self.replace_word(18, [c, d]);
+    }
+
+    /// Recompute the header checksum from scratch. Returns an error if the header length
+    /// exceeds the slice
+    pub fn update_checksum(&mut self) -> Result<u16, error::Error> {
+        let len = self.as_view().valid_header_len()?;
+        let sum = util::checksum::u16(&self.data[..len], &[IPV4_CHECKSUM_FIELD]);
+        self.data[IPV4_CHECKSUM_FIELD].copy_from_slice(&sum.to_be_bytes());
+        Ok(sum)
+    }
+
+    /// Get the payload for modification. Returns an error if there is not enough data
+    pub fn payload_mut(&mut self) -> Result<&mut [u8], error::Error> {
+        let view = self.as_view();
+        let header = view.valid_header_len()?;
+        let total = view.valid_total_len()?;
+        if header > total {
+            Err(error::header_to_long())
+        } else {
+            Ok(&mut self.data[header..total])
+        }
+    }
+}
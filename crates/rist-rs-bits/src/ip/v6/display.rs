@@ -0,0 +1,48 @@
+use core::fmt::Display;
+
+impl<'a> Display for super::Ipv6PacketView<'a> {
+    #[rustfmt::skip]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if f.alternate() {
+            writeln!(f, "IPv6Packet ({} bytes) {{", super::IPV6_HEADER_LEN + self.payload_len())?;
+            writeln!(f, "    IPv6Header ({} bytes) {{", super::IPV6_HEADER_LEN)?;
+            writeln!(f, "        Addresses: {} -> {}",
+                self.source_addr(),
+                self.dest_addr()
+            )?;
+            writeln!(f, "        NextHeader: {}", self.next_header())?;
+            writeln!(f, "        Contol: [DSCP: {}] [ECN: {}] [FlowLabel: {:#07x}]",
+                self.dscp(),
+                self.ecn(),
+                self.flow_label()
+            )?;
+            writeln!(f, "        HopLimit: {}", self.hop_limit())?;
+            writeln!(f, "    }}")?;
+            for header in self.extension_headers() {
+                match header {
+                    Ok(h) => {
+                        writeln!(f, "    Ipv6ExtensionHeader ({} bytes) {{", h.data().len())?;
+                        writeln!(f, "        Type: {}", h.header_type())?;
+                        writeln!(f, "    }}")?;
+                    }
+                    Err(e) => {
+                        writeln!(f, "    Ipv6ExtensionHeader (? bytes) {{")?;
+                        writeln!(f, "        [broken: {e}]")?;
+                        writeln!(f, "    }}")?;
+                    }
+                }
+            }
+            write!(f, "}}")
+        } else {
+            write!(
+                f,
+                "Ipv6Packet {{ [{} -> {}] [{} bytes] [{} bytes header + {} bytes payload] }}",
+                self.source_addr(),
+                self.dest_addr(),
+                super::IPV6_HEADER_LEN + self.payload_len(),
+                super::IPV6_HEADER_LEN,
+                self.payload_len()
+            )
+        }
+    }
+}
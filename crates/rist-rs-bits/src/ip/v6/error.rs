@@ -0,0 +1,65 @@
+use core::fmt::Display;
+
+#[derive(Debug, Clone, Copy)]
+pub enum ErrorKind {
+    /// Wrong value for the ip packet version
+    WrongVersion(u8, u8),
+
+    /// Not enough data was supplied to read the value of a specific field
+    NotEnoughData {
+        need: usize,
+        got: usize,
+        field: &'static &'static str,
+    },
+
+    /// The extension headers are longer than the payload length reported by the header
+    ExtensionHeaderTooLong,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Error {
+    kind: ErrorKind,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.kind {
+            ErrorKind::WrongVersion(expected, got) => {
+                write!(f, "Wrong ip version, expected: {expected}, got: {got}")
+            }
+            ErrorKind::NotEnoughData { need, got, field } => {
+                write!(f, "Not enough data to read value(s) from field [{}], need at least {} bytes, got {} bytes", **field, need, got)
+            }
+            ErrorKind::ExtensionHeaderTooLong => {
+                write!(f, "Extension header reported as longer than payload length")
+            }
+        }
+    }
+}
+
+impl Error {
+    /// Create a new error from a given ErrorKind
+    fn new(kind: ErrorKind) -> Self {
+        Self { kind }
+    }
+
+    /// Extract the error kind
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+/// Make an error that indicates a wrong value of the ip version field
+pub(super) fn wrong_version(expected: u8, got: u8) -> Error {
+    Error::new(ErrorKind::WrongVersion(expected, got))
+}
+
+/// Make an error that indicates that not enough data was supplied to read the value of a part of the IP packet
+pub(super) fn not_enough_data(need: usize, got: usize, field: &'static &'static str) -> Error {
+    Error::new(ErrorKind::NotEnoughData { need, got, field })
+}
+
+/// Make an error that indicates that an extension header exceeds the payload of the packet
+pub(super) fn extension_header_too_long() -> Error {
+    Error::new(ErrorKind::ExtensionHeaderTooLong)
+}
@@ -0,0 +1,249 @@
+/// Error types for Ipv6 packets
+pub mod error;
+
+/// Length of the fixed Ipv6 header
+const IPV6_HEADER_LEN: usize = 40;
+
+/// Next header value that indicates that no header follows
+pub const NO_NEXT_HEADER: u8 = 59;
+
+/// Hop-by-Hop Options extension header
+pub const EXT_HOP_BY_HOP: u8 = 0;
+
+/// Routing extension header
+pub const EXT_ROUTING: u8 = 43;
+
+/// Fragment extension header
+pub const EXT_FRAGMENT: u8 = 44;
+
+/// Authentication header
+pub const EXT_AUTHENTICATION: u8 = 51;
+
+/// Destination Options extension header
+pub const EXT_DESTINATION_OPTIONS: u8 = 60;
+
+/// Mobility extension header
+pub const EXT_MOBILITY: u8 = 135;
+
+/// Host Identity Protocol extension header
+pub const EXT_HIP: u8 = 139;
+
+/// Shim6 extension header
+pub const EXT_SHIM6: u8 = 140;
+
+/// Check if the next header value `header_type` identifies an extension header
+/// rather than an upper-layer protocol
+pub fn is_extension_header(header_type: u8) -> bool {
+    matches!(
+        header_type,
+        EXT_HOP_BY_HOP
+            | EXT_ROUTING
+            | EXT_FRAGMENT
+            | EXT_AUTHENTICATION
+            | EXT_DESTINATION_OPTIONS
+            | EXT_MOBILITY
+            | EXT_HIP
+            | EXT_SHIM6
+    )
+}
+
+/// View over an immutable slice of data that can be interpreted as an Ipv6 packet.
+#[derive(Debug, Clone, Copy)]
+pub struct Ipv6PacketView<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for Ipv6PacketView<'a> {
+    type Error = super::error::Error;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let pv = crate::ip::read_ip_version(data)?;
+        // need protocol version 6
+        if pv != 6 {
+            Err(error::wrong_version(6, pv).into())
+        }
+        // need the full fixed header
+        else if data.len() < IPV6_HEADER_LEN {
+            Err(error::not_enough_data(IPV6_HEADER_LEN, data.len(), &"Ipv6Packet::Header").into())
+        }
+        // ok, return the packet view
+        else {
+            Ok(Self { data })
+        }
+    }
+}
+
+impl<'a> Ipv6PacketView<'a> {
+    /// Get the traffic class
+    pub fn traffic_class(&self) -> u8 {
+        (self.data[0] << 4) | (self.data[1] >> 4)
+    }
+
+    /// Get the DSCP value from the traffic class
+    pub fn dscp(&self) -> u8 {
+        self.traffic_class() >> 2
+    }
+
+    /// Get the ECN value from the traffic class
+    pub fn ecn(&self) -> u8 {
+        self.traffic_class() & 0x3
+    }
+
+    /// Get the 20 bit flow label
+    pub fn flow_label(&self) -> u32 {
+        u32::from_be_bytes([0, self.data[1] & 0xf, self.data[2], self.data[3]])
+    }
+
+    /// Get the value of the payload length field. Includes extension headers
+    pub fn payload_len(&self) -> usize {
+        super::util::read_int!(self.data, u16, 4) as usize
+    }
+
+    /// Get the value of the next header field of the fixed header
+    pub fn next_header(&self) -> u8 {
+        self.data[6]
+    }
+
+    /// Get the hop limit
+    pub fn hop_limit(&self) -> u8 {
+        self.data[7]
+    }
+
+    /// Get the source address
+    pub fn source_addr(&self) -> rist_rs_types::net::Ipv6Addr {
+        super::util::into_array::<[u8; 16], _>(&self.data[8..24]).into()
+    }
+
+    /// Get the destination address
+    pub fn dest_addr(&self) -> rist_rs_types::net::Ipv6Addr {
+        super::util::into_array::<[u8; 16], _>(&self.data[24..40]).into()
+    }
+
+    /// Check if the packet is a multicast packet by looking at the destination address
+    pub fn is_multicast(&self) -> bool {
+        self.data[24] == 0xff
+    }
+
+    /// Get everything following the fixed header, including extension headers. Returns
+    /// an error if there is not enough data
+    pub fn extended_payload(&self) -> Result<&'a [u8], error::Error> {
+        let total = IPV6_HEADER_LEN + self.payload_len();
+        if self.data.len() < total {
+            Err(error::not_enough_data(
+                total,
+                self.data.len(),
+                &"Ipv6Packet::Payload",
+            ))
+        } else {
+            Ok(&self.data[IPV6_HEADER_LEN..total])
+        }
+    }
+
+    /// Iterate over the extension headers. Stops after the first error
+    pub fn extension_headers(&self) -> ExtensionHeaderIter<'a> {
+        match self.extended_payload() {
+            Ok(data) => ExtensionHeaderIter {
+                next: self.next_header(),
+                data,
+                error: None,
+            },
+            Err(e) => ExtensionHeaderIter {
+                next: NO_NEXT_HEADER,
+                data: &[],
+                error: Some(e),
+            },
+        }
+    }
+
+    /// Walk the extension headers and return the upper-layer protocol together with its payload
+    pub fn upper_layer(&self) -> Result<(u8, &'a [u8]), error::Error> {
+        let mut iter = self.extension_headers();
+        for header in iter.by_ref() {
+            header?;
+        }
+        Ok((iter.next, iter.data))
+    }
+
+    /// Get the upper-layer protocol after all extension headers
+    pub fn protocol(&self) -> Result<u8, error::Error> {
+        self.upper_layer().map(|(protocol, _)| protocol)
+    }
+
+    /// Get the upper-layer payload after all extension headers
+    pub fn payload(&self) -> Result<&'a [u8], error::Error> {
+        self.upper_layer().map(|(_, payload)| payload)
+    }
+}
+
+/// A single Ipv6 extension header
+#[derive(Debug, Clone, Copy)]
+pub struct ExtensionHeader<'a> {
+    header_type: u8,
+    data: &'a [u8],
+}
+
+impl<'a> ExtensionHeader<'a> {
+    /// Type of this extension header, as given by the next header field of the previous header
+    pub fn header_type(&self) -> u8 {
+        self.header_type
+    }
+
+    /// Type of the header following this extension header
+    pub fn next_header(&self) -> u8 {
+        self.data[0]
+    }
+
+    /// The complete extension header including the next header and length fields
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+/// Iterator over the extension headers of an Ipv6 packet
+#[derive(Debug, Clone)]
+pub struct ExtensionHeaderIter<'a> {
+    next: u8,
+    data: &'a [u8],
+    error: Option<error::Error>,
+}
+
+impl<'a> Iterator for ExtensionHeaderIter<'a> {
+    type Item = Result<ExtensionHeader<'a>, error::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+        if !is_extension_header(self.next) {
+            return None;
+        }
+        if self.data.len() < 2 {
+            self.next = NO_NEXT_HEADER;
+            self.data = &[];
+            return Some(Err(error::extension_header_too_long()));
+        }
+        let len = match self.next {
+            EXT_FRAGMENT => 8,
+            EXT_AUTHENTICATION => (self.data[1] as usize + 2) * 4,
+            _ => (self.data[1] as usize + 1) * 8,
+        };
+        if self.data.len() < len {
+            self.next = NO_NEXT_HEADER;
+            self.data = &[];
+            return Some(Err(error::extension_header_too_long()));
+        }
+        let header = ExtensionHeader {
+            header_type: self.next,
+            data: &self.data[..len],
+        };
+        self.next = header.next_header();
+        self.data = &self.data[len..];
+        Some(Ok(header))
+    }
+}
+
+// Implements display for Ipv6PacketView to pretty print packets
+mod display;
+
+// Tests
+mod test;
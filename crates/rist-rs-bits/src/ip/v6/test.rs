@@ -0,0 +1,110 @@
+use super::*;
+use core::str::FromStr;
+
+/// Ipv6 UDP packet from fe80::1 to ff02::fb without extension headers
+const IP_BASIC: [u8; 52] = [
+    0x60, 0x0a, 0xbc, 0xde, 0x00, 0x0c, 0x11, 0xff, 0xfe, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0xff, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xfb, // udp
+    0x14, 0xe9, 0x14, 0xe9, 0x00, 0x0c, 0x00, 0x00, 0x01, 0x02, 0x03, 0x04,
+];
+
+/// Ipv6 UDP packet with a hop-by-hop options header and a fragment header
+const IP_EXT: [u8; 68] = [
+    0x60, 0x00, 0x00, 0x00, 0x00, 0x1c, 0x00, 0x40, 0x20, 0x01, 0x0d, 0xb8, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x20, 0x01, 0x0d, 0xb8, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, // hop-by-hop
+    0x2c, 0x00, 0x01, 0x04, 0x00, 0x00, 0x00, 0x00, // fragment
+    0x11, 0x00, 0x00, 0x00, 0x12, 0x34, 0x56, 0x78, // udp
+    0x14, 0xe9, 0x14, 0xe9, 0x00, 0x0c, 0x00, 0x00, 0x01, 0x02, 0x03, 0x04,
+];
+
+fn packet(data: &[u8]) -> Ipv6PacketView<'_> {
+    Ipv6PacketView::try_from(data).unwrap()
+}
+
+#[test]
+fn basic() {
+    let ip = packet(&IP_BASIC);
+    assert_eq!(ip.traffic_class(), 0);
+    assert_eq!(ip.flow_label(), 0xabcde);
+    assert_eq!(ip.payload_len(), 12);
+    assert_eq!(ip.next_header(), 17);
+    assert_eq!(ip.hop_limit(), 255);
+    assert!(ip.is_multicast());
+    assert_eq!(
+        std::net::IpAddr::from_str("fe80::1").unwrap(),
+        std::net::IpAddr::from(ip.source_addr())
+    );
+    assert_eq!(
+        std::net::IpAddr::from_str("ff02::fb").unwrap(),
+        std::net::IpAddr::from(ip.dest_addr())
+    );
+    assert_eq!(ip.extension_headers().count(), 0);
+    assert_eq!(ip.upper_layer().unwrap(), (17, &IP_BASIC[40..]));
+}
+
+#[test]
+fn extension_headers() {
+    let ip = packet(&IP_EXT);
+    let headers = ip
+        .extension_headers()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(headers.len(), 2);
+    assert_eq!(headers[0].header_type(), EXT_HOP_BY_HOP);
+    assert_eq!(headers[0].next_header(), EXT_FRAGMENT);
+    assert_eq!(headers[1].header_type(), EXT_FRAGMENT);
+    assert_eq!(headers[1].data(), &IP_EXT[48..56]);
+    assert_eq!(ip.protocol().unwrap(), 17);
+    assert_eq!(ip.payload().unwrap(), &IP_EXT[56..]);
+}
+
+#[test]
+fn broken_extension_header() {
+    let mut data = IP_EXT;
+    // hop-by-hop header claims 16 bytes more than available
+    data[41] = 3;
+    let ip = packet(&data);
+    assert!(ip.payload().is_err());
+    let mut headers = ip.extension_headers();
+    assert!(headers.next().unwrap().is_err());
+    assert!(headers.next().is_none());
+}
+
+#[test]
+fn broken_payload_len() {
+    let mut data = IP_BASIC;
+    data[5] = 0x0d;
+    let ip = packet(&data);
+    assert!(ip.payload().is_err());
+    assert!(ip.extension_headers().next().unwrap().is_err());
+}
+
+#[test]
+fn wrong_version() {
+    assert!(Ipv6PacketView::try_from(&IP_BASIC[..39]).is_err());
+    let mut data = IP_BASIC;
+    data[0] = 0x45;
+    assert!(Ipv6PacketView::try_from(data.as_slice()).is_err());
+}
+
+#[test]
+fn display() {
+    let ip = packet(&IP_EXT);
+    assert_eq!(
+        format!("{ip}"),
+        "Ipv6Packet { [2001:db8::1 -> 2001:db8::2] [68 bytes] [40 bytes header + 28 bytes payload] }"
+    );
+}
+
+#[test]
+fn ip_packet_view() {
+    let ip = crate::ip::IpPacketView::try_from(IP_EXT.as_slice()).unwrap();
+    assert!(ip.v6().is_some());
+    assert!(ip.v4().is_none());
+    assert_eq!(
+        std::net::IpAddr::from_str("2001:db8::2").unwrap(),
+        std::net::IpAddr::from(ip.dest_addr())
+    );
+}
@@ -11,5 +11,6 @@ pub mod ip;
 pub mod rist;
 pub mod rtcp;
 pub mod rtp;
+pub mod ts;
 pub mod udp;
 pub mod util;
@@ -1,62 +1,272 @@
 use core::convert::TryFrom;
 
-const HEADER_SIZE: usize = 8;
+use rist_rs_macros::PacketView;
 
-#[derive(Debug)]
-struct Error {}
+/// Length of the MAC address and the capability flags in front of the optional JSON payload
+pub const HEADER_SIZE: usize = KeepAliveHeaderView::HEADER_LEN;
 
-struct KeepAlivePacket<'a> {
-    data: &'a [u8],
+/// Length of the MAC address field
+const MAC_ADDRESS_LEN: usize = 6;
+
+/// Layout of the keep-alive header
+#[derive(PacketView, Debug, Clone, Copy, PartialEq, Eq)]
+#[packet(
+    view = KeepAliveHeaderView,
+    view_mut = KeepAliveHeaderViewMut,
+    builder = KeepAliveHeaderBuilder
+)]
+pub struct KeepAliveHeader {
+    /// MAC address of the sender
+    #[field(offset = 0)]
+    pub mac_address: [u8; 6],
+    #[field(offset = 6, bits = 0)]
+    pub cap_more: bool,
+    #[field(offset = 6, bits = 1)]
+    pub cap_routing: bool,
+    #[field(offset = 6, bits = 2)]
+    pub cap_bonding: bool,
+    #[field(offset = 6, bits = 3)]
+    pub cap_adaptive_enc: bool,
+    #[field(offset = 6, bits = 4)]
+    pub cap_fec: bool,
+    #[field(offset = 6, bits = 5)]
+    pub cap_dash7: bool,
+    #[field(offset = 6, bits = 6)]
+    pub cap_load_sharing: bool,
+    #[field(offset = 6, bits = 7)]
+    pub cap_npd: bool,
+    #[field(offset = 7, bits = 0)]
+    pub is_disconnect: bool,
+    #[field(offset = 7, bits = 1)]
+    pub is_reconnect: bool,
+    #[field(offset = 7, bits = 2)]
+    pub cap_reduced_overhead: bool,
+    #[field(offset = 7, bits = 3)]
+    pub cap_json_processing: bool,
+    #[field(offset = 7, bits = 4)]
+    pub cap_psk_change: bool,
+}
+
+pub mod error {
+    use core::fmt::Display;
+
+    #[derive(Debug, Clone, Copy)]
+    pub enum ErrorKind {
+        /// Not enough data was supplied to read the keep-alive header
+        NotEnoughData { need: usize, got: usize },
+
+        /// The buffer supplied for writing a message is too small
+        NotEnoughSpace { need: usize, got: usize },
+
+        /// Both the disconnect and the reconnect flag are set
+        InvalidState,
+
+        /// The JSON payload is not valid UTF-8
+        InvalidJson(core::str::Utf8Error),
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct Error {
+        kind: ErrorKind,
+    }
+
+    impl Display for Error {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match &self.kind {
+                ErrorKind::NotEnoughData { need, got } => write!(
+                    f,
+                    "Not enough data to read keep-alive message, need at least {need} bytes, got {got} bytes"
+                ),
+                ErrorKind::NotEnoughSpace { need, got } => write!(
+                    f,
+                    "Not enough space to write keep-alive message, need {need} bytes, got {got} bytes"
+                ),
+                ErrorKind::InvalidState => {
+                    write!(f, "Keep-alive message is flagged as disconnect and reconnect")
+                }
+                ErrorKind::InvalidJson(e) => write!(f, "Invalid JSON payload: {e}"),
+            }
+        }
+    }
+
+    impl Error {
+        pub(super) fn new(kind: ErrorKind) -> Self {
+            Self { kind }
+        }
+
+        /// Extract the error kind
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+}
+
+use error::{Error, ErrorKind};
+
+/// View over a RIST Main Profile keep-alive message (VSF TR-06-2): the MAC address of the
+/// sender, capability flags and an optional JSON payload
+#[derive(Debug, Clone, Copy)]
+pub struct KeepAlivePacket<'a> {
+    header: KeepAliveHeaderView<'a>,
 }
 
 impl<'a> TryFrom<&'a [u8]> for KeepAlivePacket<'a> {
     type Error = Error;
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
-        if data.len() < 8 {
-            Err(Error {})
+        if data.len() < HEADER_SIZE {
+            Err(Error::new(ErrorKind::NotEnoughData {
+                need: HEADER_SIZE,
+                got: data.len(),
+            }))
         } else {
-            Ok(KeepAlivePacket { data })
+            Ok(KeepAlivePacket {
+                header: KeepAliveHeaderView::try_new(data)
+                    .expect(rist_rs_types::internal::INTERNAL_ERR_PRE_VALIDATED),
+            })
         }
     }
 }
 
+impl<'a> KeepAlivePacket<'a> {
+    /// View over the fixed header
+    pub fn header(&self) -> KeepAliveHeaderView<'a> {
+        self.header
+    }
+
+    /// MAC address of the sender
+    pub fn mac_address(&self) -> [u8; MAC_ADDRESS_LEN] {
+        self.header.mac_address()
+    }
+
+    /// Raw capability flags
+    pub fn flags(&self) -> [u8; 2] {
+        crate::util::into_array(&self.header.data[MAC_ADDRESS_LEN..HEADER_SIZE])
+    }
+
+    /// Get the optional JSON payload following the flags. Returns `None` if the message
+    /// carries no payload and an error if the payload is not valid UTF-8
+    pub fn json(&self) -> Option<Result<&'a str, Error>> {
+        let payload = self.header.payload();
+        (!payload.is_empty()).then(|| {
+            core::str::from_utf8(payload).map_err(|e| Error::new(ErrorKind::InvalidJson(e)))
+        })
+    }
+}
+
+/// State signalled by a keep-alive message
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeepAliveState {
+    #[default]
+    KeepAlive,
+    Reconnect,
+    Disconnect,
+}
+
+/// Builder for keep-alive messages
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeepAliveBuilder<'a> {
+    header: KeepAliveHeaderBuilder,
+    json: Option<&'a str>,
+}
+
+impl<'a> KeepAliveBuilder<'a> {
+    /// Create a new builder with a zero MAC address and all flags cleared
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the MAC address of the sender
+    pub fn with_mac_address(mut self, mac_address: [u8; MAC_ADDRESS_LEN]) -> Self {
+        self.header = self.header.with_mac_address(mac_address);
+        self
+    }
+
+    /// Set the connection state. Sets the disconnect and reconnect flags accordingly
+    pub fn with_state(mut self, state: KeepAliveState) -> Self {
+        self.header = self
+            .header
+            .with_is_disconnect(state == KeepAliveState::Disconnect)
+            .with_is_reconnect(state == KeepAliveState::Reconnect);
+        self
+    }
+
+    /// Append a JSON payload to the message
+    pub fn with_json(mut self, json: &'a str) -> Self {
+        self.json = Some(json);
+        self
+    }
+
+    /// Length of the message written by this builder
+    pub fn packet_len(&self) -> usize {
+        HEADER_SIZE + self.json.map(str::len).unwrap_or(0)
+    }
+
+    /// Write the message into `buf`. Returns the number of bytes written or an error if
+    /// the buffer is too small
+    pub fn write(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        let len = self.packet_len();
+        if buf.len() < len {
+            return Err(Error::new(ErrorKind::NotEnoughSpace {
+                need: len,
+                got: buf.len(),
+            }));
+        }
+        let json = self.json.map(str::as_bytes).unwrap_or_default();
+        self.header
+            .write(json, buf)
+            .expect(rist_rs_types::internal::INTERNAL_ERR_PRE_VALIDATED);
+        Ok(len)
+    }
+
+    /// Write the message into a newly allocated vector
+    #[cfg(feature = "alloc")]
+    pub fn to_vec(&self) -> alloc::vec::Vec<u8> {
+        let mut buf = alloc::vec![0u8; self.packet_len()];
+        self.write(&mut buf)
+            .expect(rist_rs_types::internal::INTERNAL_ERR_PRE_VALIDATED);
+        buf
+    }
+}
+
 macro_rules! msg_flag {
-    ($flag_name:tt, $val:expr, $fun_name:tt, $index:expr, $p:tt) => {
-        const $flag_name: u8 = $val;
-        impl <'a> KeepAlivePacket<'a> {
-            $p fn $fun_name(&self) -> bool {
-                (self.data[6 + $index] & $flag_name) != 0
+    ($fun_name:tt, $setter:tt) => {
+        msg_flag!($fun_name);
+        impl<'a> KeepAliveBuilder<'a> {
+            pub fn $setter(mut self, set: bool) -> Self {
+                self.header = self.header.$setter(set);
+                self
             }
         }
     };
-    ($flag_name:tt, $val:expr, $fun_name:tt, $index:expr) => {
-        const $flag_name: u8 = $val;
-        impl <'a> KeepAlivePacket<'a> {
-            fn $fun_name(&self) -> bool {
-                (self.data[6 + $index] & $flag_name) != 0
+    ($fun_name:tt) => {
+        impl<'a> KeepAlivePacket<'a> {
+            pub fn $fun_name(&self) -> bool {
+                self.header.$fun_name()
             }
         }
     };
 }
 
 // flags part 1
-msg_flag!(F0_CAP_MORE, 0x80, cap_more, 0, pub);
-msg_flag!(F0_CAP_ROUTING, 0x40, cap_routing, 0, pub);
-msg_flag!(F0_CAP_BONDING, 0x20, cap_bonding, 0, pub);
-msg_flag!(F0_CAP_ADAPTIVE_ENC, 0x10, cap_adaptive_enc, 0, pub);
-msg_flag!(F0_CAP_FEC, 0x8, cap_fec, 0, pub);
-msg_flag!(F0_CAP_DASH7, 0x4, cap_dash7, 0, pub);
-msg_flag!(F0_CAP_LOAD_SHARING, 0x2, cap_load_sharing, 0, pub);
-msg_flag!(F0_CAP_NULL_PACKET_DELETION, 0x1, cap_npd, 0, pub);
+msg_flag!(cap_more, with_cap_more);
+msg_flag!(cap_routing, with_cap_routing);
+msg_flag!(cap_bonding, with_cap_bonding);
+msg_flag!(cap_adaptive_enc, with_cap_adaptive_enc);
+msg_flag!(cap_fec, with_cap_fec);
+msg_flag!(cap_dash7, with_cap_dash7);
+msg_flag!(cap_load_sharing, with_cap_load_sharing);
+msg_flag!(cap_npd, with_cap_npd);
 
 // flags part 2
-msg_flag!(F1_IS_DISCONNECT, 0x80, is_disconnect, 1);
-msg_flag!(F1_IS_RECONNECT, 0x40, is_reconnect, 1);
-msg_flag!(F1_CAP_REDUCED_OVERHEAD, 0x20, cap_reduced_overhead, 1, pub);
-msg_flag!(F1_CAP_JSON_PROCESSING, 0x10, cap_json_processing, 1, pub);
-msg_flag!(F1_CAP_PSK_CHANGE, 0x8, cap_psk_change, 1, pub);
+msg_flag!(is_disconnect);
+msg_flag!(is_reconnect);
+msg_flag!(cap_reduced_overhead, with_cap_reduced_overhead);
+msg_flag!(cap_json_processing, with_cap_json_processing);
+msg_flag!(cap_psk_change, with_cap_psk_change);
 
-enum KeepAliveMessage<'a> {
+/// A keep-alive message classified by the connection state it signals
+#[derive(Debug, Clone, Copy)]
+pub enum KeepAliveMessage<'a> {
     KeepAlive(KeepAlivePacket<'a>),
     Reconnect(KeepAlivePacket<'a>),
     Disconnect(KeepAlivePacket<'a>),
@@ -67,7 +277,7 @@ impl<'a> TryFrom<&'a [u8]> for KeepAliveMessage<'a> {
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
         let packet = KeepAlivePacket::try_from(data)?;
         if packet.is_reconnect() && packet.is_disconnect() {
-            Err(Error {})
+            Err(Error::new(ErrorKind::InvalidState))
         } else if packet.is_disconnect() {
             Ok(KeepAliveMessage::Disconnect(packet))
         } else if packet.is_reconnect() {
@@ -78,6 +288,25 @@ impl<'a> TryFrom<&'a [u8]> for KeepAliveMessage<'a> {
     }
 }
 
+impl<'a> KeepAliveMessage<'a> {
+    /// Get the connection state signalled by the message
+    pub fn state(&self) -> KeepAliveState {
+        match self {
+            Self::KeepAlive(_) => KeepAliveState::KeepAlive,
+            Self::Reconnect(_) => KeepAliveState::Reconnect,
+            Self::Disconnect(_) => KeepAliveState::Disconnect,
+        }
+    }
+
+    /// Get the underlying packet
+    pub fn packet(&self) -> KeepAlivePacket<'a> {
+        match self {
+            Self::KeepAlive(p) | Self::Reconnect(p) | Self::Disconnect(p) => *p,
+        }
+    }
+}
+
+#[allow(unused)]
 mod test {
 
     use super::*;
@@ -92,4 +321,68 @@ mod test {
             KeepAliveMessage::Disconnect(packet) => {}
         }
     }
+
+    #[test]
+    fn build_and_parse() {
+        let json = r#"{"version":"1.0"}"#;
+        let data = KeepAliveBuilder::new()
+            .with_mac_address([0x02, 0x42, 0xac, 0x11, 0x00, 0x02])
+            .with_cap_bonding(true)
+            .with_cap_fec(true)
+            .with_cap_reduced_overhead(true)
+            .with_cap_json_processing(true)
+            .with_state(KeepAliveState::Reconnect)
+            .with_json(json)
+            .to_vec();
+        assert_eq!(&data[..8], [0x02, 0x42, 0xac, 0x11, 0x00, 0x02, 0x28, 0x70]);
+        let msg = KeepAliveMessage::try_from(data.as_slice()).unwrap();
+        assert_eq!(msg.state(), KeepAliveState::Reconnect);
+        let packet = msg.packet();
+        assert_eq!(packet.mac_address(), [0x02, 0x42, 0xac, 0x11, 0x00, 0x02]);
+        assert!(packet.cap_bonding());
+        assert!(packet.cap_fec());
+        assert!(!packet.cap_npd());
+        assert!(packet.cap_reduced_overhead());
+        assert!(packet.cap_json_processing());
+        assert!(!packet.cap_psk_change());
+        assert_eq!(packet.json().unwrap().unwrap(), json);
+        let header = packet.header().decode();
+        assert_eq!(header.mac_address, packet.mac_address());
+        assert!(header.is_reconnect && !header.is_disconnect);
+        assert!(header.cap_bonding && !header.cap_dash7);
+    }
+
+    #[test]
+    fn state_and_flags() {
+        let builder = KeepAliveBuilder::new()
+            .with_cap_psk_change(true)
+            .with_state(KeepAliveState::Disconnect);
+        let data = builder.to_vec();
+        let msg = KeepAliveMessage::try_from(data.as_slice()).unwrap();
+        assert_eq!(msg.state(), KeepAliveState::Disconnect);
+        assert!(msg.packet().json().is_none());
+        let data = builder
+            .with_cap_psk_change(false)
+            .with_state(KeepAliveState::KeepAlive)
+            .to_vec();
+        assert_eq!(data, [0; 8]);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(KeepAliveMessage::try_from([0u8; 7].as_slice()).is_err());
+        let data = [0, 0, 0, 0, 0, 0, 0, 0xc0];
+        assert!(KeepAliveMessage::try_from(data.as_slice()).is_err());
+        let data = [0, 0, 0, 0, 0, 0, 0, 0, 0xff];
+        assert!(KeepAlivePacket::try_from(data.as_slice())
+            .unwrap()
+            .json()
+            .unwrap()
+            .is_err());
+        let mut buf = [0u8; 8];
+        assert!(KeepAliveBuilder::new()
+            .with_json("{}")
+            .write(&mut buf)
+            .is_err());
+    }
 }
@@ -1,2 +1,3 @@
 #![allow(unused)]
-mod keep_alive;
+pub mod keep_alive;
+pub mod npd;
@@ -0,0 +1,317 @@
+use crate::rtp::ext::rist::{Extension, ExtensionBuilder, TS_PACKET_COUNT_MAX};
+use crate::ts;
+
+/// Header of the null packets removed and re-inserted by null packet deletion
+pub const NULL_PACKET_HEADER: [u8; 4] = [ts::SYNC_BYTE, 0x1f, 0xff, 0x10];
+
+/// Value of all bytes following the header of a null packet
+pub const NULL_PACKET_STUFFING: u8 = 0xff;
+
+pub mod error {
+    use core::fmt::Display;
+
+    #[derive(Debug, Clone, Copy)]
+    pub enum ErrorKind {
+        /// The buffer supplied for writing the payload is too small
+        NotEnoughSpace { need: usize, got: usize },
+
+        /// The TS packet size is neither 188 nor 204 bytes
+        InvalidPacketSize(usize),
+
+        /// The payload length is not a multiple of the TS packet size, holds the number of
+        /// trailing bytes
+        TrailingData(usize),
+
+        /// The payload holds more TS packets than can be described by the RIST header extension
+        TooManyPackets(usize),
+
+        /// The number of TS packets in the payload does not match the RIST header extension
+        PacketCountMismatch { expected: usize, got: usize },
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct Error {
+        kind: ErrorKind,
+    }
+
+    impl Display for Error {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match &self.kind {
+                ErrorKind::NotEnoughSpace { need, got } => write!(
+                    f,
+                    "Not enough space to write TS packets, need {need} bytes, got {got} bytes"
+                ),
+                ErrorKind::InvalidPacketSize(len) => write!(f, "Invalid TS packet size: {len}"),
+                ErrorKind::TrailingData(len) => {
+                    write!(f, "{len} bytes of trailing data after the last TS packet")
+                }
+                ErrorKind::TooManyPackets(n) => {
+                    write!(f, "{n} TS packets exceed the null packet deletion bitmap")
+                }
+                ErrorKind::PacketCountMismatch { expected, got } => write!(
+                    f,
+                    "Expected {expected} TS packets after null packet deletion, got {got}"
+                ),
+            }
+        }
+    }
+
+    impl Error {
+        pub(super) fn new(kind: ErrorKind) -> Self {
+            Self { kind }
+        }
+
+        /// Extract the error kind
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+}
+
+use error::{Error, ErrorKind};
+
+/// Check if `packet` is a null packet exactly as it would be re-inserted by [restore]. For
+/// 204 byte packets this includes the 16 trailing bytes, which must be 0xFF as well
+pub fn is_null_packet(packet: &[u8]) -> bool {
+    packet.len() >= NULL_PACKET_HEADER.len()
+        && packet[..NULL_PACKET_HEADER.len()] == NULL_PACKET_HEADER
+        && packet[NULL_PACKET_HEADER.len()..]
+            .iter()
+            .all(|b| *b == NULL_PACKET_STUFFING)
+}
+
+/// Write a null packet filling all of `buf`
+fn write_null_packet(buf: &mut [u8]) {
+    buf[..NULL_PACKET_HEADER.len()].copy_from_slice(&NULL_PACKET_HEADER);
+    buf[NULL_PACKET_HEADER.len()..].fill(NULL_PACKET_STUFFING);
+}
+
+/// Split `payload` into TS packets of `packet_len` bytes. Returns the number of packets
+fn packet_count(payload: &[u8], packet_len: usize) -> Result<usize, Error> {
+    if packet_len != ts::PACKET_LEN && packet_len != ts::PACKET_LEN_204 {
+        Err(Error::new(ErrorKind::InvalidPacketSize(packet_len)))
+    } else if !payload.len().is_multiple_of(packet_len) {
+        Err(Error::new(ErrorKind::TrailingData(
+            payload.len() % packet_len,
+        )))
+    } else {
+        Ok(payload.len() / packet_len)
+    }
+}
+
+/// Remove null packets from `payload`, a sequence of at most 7 TS packets of `packet_len` bytes,
+/// and write the remaining packets into `buf`. Returns the number of bytes written and a
+/// RIST header extension builder with the packet count, packet size and deletion bitmap set.
+///
+/// Only null packets that [restore] re-inserts byte for byte are removed, other packets with
+/// PID 0x1FFF are kept. This includes 204 byte null packets that carry Reed-Solomon parity
+/// bytes instead of 0xFF stuffing in their last 16 bytes, so null packet deletion has no
+/// effect on such streams.
+pub fn delete_null_packets(
+    payload: &[u8],
+    packet_len: usize,
+    buf: &mut [u8],
+) -> Result<(usize, ExtensionBuilder), Error> {
+    let count = packet_count(payload, packet_len)?;
+    if count > TS_PACKET_COUNT_MAX as usize {
+        return Err(Error::new(ErrorKind::TooManyPackets(count)));
+    }
+    let need = payload
+        .chunks_exact(packet_len)
+        .filter(|packet| !is_null_packet(packet))
+        .count()
+        * packet_len;
+    if buf.len() < need {
+        return Err(Error::new(ErrorKind::NotEnoughSpace {
+            need,
+            got: buf.len(),
+        }));
+    }
+    let mut bits = 0u8;
+    let mut len = 0;
+    for (i, packet) in payload.chunks_exact(packet_len).enumerate() {
+        if is_null_packet(packet) {
+            bits |= 0x40 >> i;
+        } else {
+            buf[len..len + packet_len].copy_from_slice(packet);
+            len += packet_len;
+        }
+    }
+    let ext = ExtensionBuilder::new()
+        .with_ts_packet_count(count as u8)
+        .with_ts_packet_size_204(packet_len == ts::PACKET_LEN_204)
+        .with_npd_bits(bits);
+    Ok((len, ext))
+}
+
+/// Length of the payload after re-inserting the null packets described by `ext` into `payload`
+pub fn restored_len(ext: &Extension, payload: &[u8]) -> Result<usize, Error> {
+    if !ext.has_npd() {
+        return Ok(payload.len());
+    }
+    let packet_len = ext.ts_packet_size();
+    let got = packet_count(payload, packet_len)?;
+    let count = ext.ts_packet_count() as usize;
+    let deleted = (0..count)
+        .filter(|i| ext.is_null_packet_deleted(*i))
+        .count();
+    if got + deleted != count {
+        Err(Error::new(ErrorKind::PacketCountMismatch {
+            expected: count - deleted,
+            got,
+        }))
+    } else {
+        Ok(count * packet_len)
+    }
+}
+
+/// Re-insert the null packets described by the RIST header extension `ext` into `payload` and
+/// write the result into `buf`. Returns the number of bytes written. If null packet deletion is
+/// not active for the packet the payload is copied unchanged.
+pub fn restore(ext: &Extension, payload: &[u8], buf: &mut [u8]) -> Result<usize, Error> {
+    let len = restored_len(ext, payload)?;
+    if buf.len() < len {
+        return Err(Error::new(ErrorKind::NotEnoughSpace {
+            need: len,
+            got: buf.len(),
+        }));
+    }
+    if !ext.has_npd() {
+        buf[..len].copy_from_slice(payload);
+        return Ok(len);
+    }
+    let packet_len = ext.ts_packet_size();
+    let mut packets = payload.chunks_exact(packet_len);
+    for (i, out) in buf[..len].chunks_exact_mut(packet_len).enumerate() {
+        if ext.is_null_packet_deleted(i) {
+            write_null_packet(out);
+        } else {
+            out.copy_from_slice(
+                packets
+                    .next()
+                    .expect(rist_rs_types::internal::INTERNAL_ERR_PRE_VALIDATED),
+            );
+        }
+    }
+    Ok(len)
+}
+
+/// Re-insert the null packets described by `ext` into a newly allocated vector
+#[cfg(feature = "alloc")]
+pub fn restore_to_vec(ext: &Extension, payload: &[u8]) -> Result<alloc::vec::Vec<u8>, Error> {
+    let mut buf = alloc::vec![0u8; restored_len(ext, payload)?];
+    restore(ext, payload, &mut buf)?;
+    Ok(buf)
+}
+
+#[allow(unused)]
+mod test {
+    use super::*;
+    use crate::rtp::{RTPBuilder, RTPView};
+
+    fn packet(pid: u16, fill: u8) -> [u8; ts::PACKET_LEN_204] {
+        let mut data = [fill; ts::PACKET_LEN_204];
+        data[..4].copy_from_slice(&[ts::SYNC_BYTE, (pid >> 8) as u8, pid as u8, 0x10]);
+        data
+    }
+
+    fn stream(pids: &[u16], len: usize, buf: &mut [u8]) -> usize {
+        for (i, pid) in pids.iter().enumerate() {
+            let fill = if *pid == ts::PID_NULL { 0xff } else { i as u8 };
+            buf[i * len..(i + 1) * len].copy_from_slice(&packet(*pid, fill)[..len]);
+        }
+        pids.len() * len
+    }
+
+    #[test]
+    fn round_trip() {
+        let pids = [
+            0x100,
+            ts::PID_NULL,
+            0x101,
+            ts::PID_NULL,
+            ts::PID_NULL,
+            0x100,
+            0x20,
+        ];
+        for len in [ts::PACKET_LEN, ts::PACKET_LEN_204] {
+            let mut buf = [0u8; 7 * ts::PACKET_LEN_204];
+            let input_len = stream(&pids, len, &mut buf);
+            let input = &buf[..input_len];
+            let mut stripped = [0u8; 7 * ts::PACKET_LEN_204];
+            let (stripped_len, ext) = delete_null_packets(input, len, &mut stripped).unwrap();
+            assert_eq!(stripped_len, 4 * len);
+            let ext = ext.with_extended_sequence_number(0x10000);
+            let rtp = RTPBuilder::new()
+                .with_extension(crate::rtp::ext::rist::PROFILE, &ext.build())
+                .to_vec(&stripped[..stripped_len])
+                .unwrap();
+            let rtp = RTPView::try_new(&rtp).unwrap();
+            let ext = rtp.rist_extension().unwrap().unwrap();
+            assert!(ext.has_npd());
+            assert_eq!(ext.ts_packet_count(), 7);
+            assert_eq!(ext.npd_bits(), 0b0101100);
+            let mut output = [0u8; 7 * ts::PACKET_LEN_204];
+            let output_len = restore(&ext, rtp.payload().unwrap(), &mut output).unwrap();
+            assert_eq!(&output[..output_len], input);
+            assert_eq!(restore_to_vec(&ext, rtp.payload().unwrap()).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn keeps_non_canonical_null_packets() {
+        let mut input = [0u8; 2 * ts::PACKET_LEN];
+        input[..ts::PACKET_LEN].copy_from_slice(&packet(ts::PID_NULL, 0)[..ts::PACKET_LEN]);
+        input[ts::PACKET_LEN..].copy_from_slice(&packet(ts::PID_NULL, 0xff)[..ts::PACKET_LEN]);
+        let mut stripped = [0u8; 2 * ts::PACKET_LEN];
+        let (len, ext) = delete_null_packets(&input, ts::PACKET_LEN, &mut stripped).unwrap();
+        assert_eq!(len, ts::PACKET_LEN);
+        assert_eq!(
+            ext,
+            ExtensionBuilder::new()
+                .with_ts_packet_count(2)
+                .with_npd_bits(0x20)
+        );
+    }
+
+    #[test]
+    fn keeps_null_packets_with_parity() {
+        let mut input = packet(ts::PID_NULL, 0xff);
+        input[ts::PACKET_LEN..].fill(0x5a);
+        let mut stripped = [0u8; ts::PACKET_LEN_204];
+        let (len, ext) = delete_null_packets(&input, ts::PACKET_LEN_204, &mut stripped).unwrap();
+        assert_eq!(len, ts::PACKET_LEN_204);
+        assert_eq!(
+            ext,
+            ExtensionBuilder::new()
+                .with_ts_packet_count(1)
+                .with_ts_packet_size_204(true)
+                .with_npd_bits(0)
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        let mut buf = [0u8; 8 * ts::PACKET_LEN];
+        assert!(delete_null_packets(&[0; 8 * ts::PACKET_LEN], ts::PACKET_LEN, &mut buf).is_err());
+        assert!(delete_null_packets(&[0; ts::PACKET_LEN + 1], ts::PACKET_LEN, &mut buf).is_err());
+        assert!(delete_null_packets(&[0; 190], 190, &mut buf).is_err());
+        assert!(delete_null_packets(&[0; ts::PACKET_LEN], ts::PACKET_LEN, &mut []).is_err());
+        // 3 packets with one deleted, but only one packet in the payload
+        let ext = ExtensionBuilder::new()
+            .with_ts_packet_count(3)
+            .with_npd_bits(0x40)
+            .build();
+        let mut data = [0u8; 8];
+        data[..2].copy_from_slice(&crate::rtp::ext::rist::PROFILE);
+        data[3] = 1;
+        data[4..].copy_from_slice(&ext);
+        let ext = Extension::try_from(data.as_slice()).unwrap();
+        assert!(restore(&ext, &[0; ts::PACKET_LEN], &mut buf).is_err());
+        assert_eq!(
+            restore(&ext, &[0; 2 * ts::PACKET_LEN], &mut buf).unwrap(),
+            3 * ts::PACKET_LEN
+        );
+        assert!(restore(&ext, &[0; 2 * ts::PACKET_LEN], &mut buf[..ts::PACKET_LEN]).is_err());
+    }
+}
@@ -91,6 +91,11 @@ impl<'a> ApplicationSpecificMessageView<'a> {
         self.subtype
     }
 
+    /// Application-dependent data following the name field
+    pub fn data(&self) -> &'a [u8] {
+        &self.data[Self::DATA_OFFSET..]
+    }
+
     pub fn message(&self) -> Result<MessageView<'a>, error::Error> {
         self.name()
             .map_err(error::Error::from)
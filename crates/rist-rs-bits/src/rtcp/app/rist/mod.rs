@@ -1,6 +1,9 @@
 pub mod range_nack;
 pub mod rtt;
 
+/// Name field of all RIST application-defined packets
+pub const NAME: [u8; 4] = *b"RIST";
+
 pub mod error {
 
     #[derive(Debug, Clone, Copy)]
@@ -13,6 +13,8 @@ pub struct RangeNackMessage<'a> {
     data: &'a [u8],
 }
 
+/// Request for retransmission of `count` packets following the packet with sequence number `seq_start`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PacketRangeRequest {
     pub seq_start: u16,
     pub count: u16,
@@ -27,6 +29,14 @@ impl From<[u8; 4]> for PacketRangeRequest {
     }
 }
 
+impl From<&PacketRangeRequest> for [u8; 4] {
+    fn from(req: &PacketRangeRequest) -> Self {
+        let [s0, s1] = req.seq_start.to_be_bytes();
+        let [c0, c1] = req.count.to_be_bytes();
+        [s0, s1, c0, c1]
+    }
+}
+
 impl<'a> RangeNackMessage<'a> {
     pub fn try_new<T, U>(bytes: &'a T) -> Result<Self, error::Error>
     where
@@ -35,7 +45,7 @@ impl<'a> RangeNackMessage<'a> {
         &'a U: Into<&'a [u8]>,
     {
         let data: &'a [u8] = bytes.as_ref().into();
-        if data.len() % 4 != 0 {
+        if !data.len().is_multiple_of(4) {
             Err(error::Error::InvalidPacketLength)
         } else {
             Ok(Self { data })
@@ -1,3 +1,7 @@
+use core::time::Duration;
+
+use rist_rs_types::time::ntp::Timestamp;
+
 pub mod error {
 
     #[derive(Debug, Clone, Copy)]
@@ -9,13 +13,19 @@ pub mod error {
 pub const SUBTYPE_RTT_ECHO_REQ: u8 = 2;
 pub const SUBTYPE_RTT_ECHO_RES: u8 = 3;
 
+/// Length of the application-dependent data of an echo request or response
+pub const ECHO_LEN: usize = 12;
+
 #[derive(Debug, Clone, Copy)]
 pub struct EchoMessage<'a> {
     data: &'a [u8],
 }
 
 impl<'a> EchoMessage<'a> {
-    const PACKET_LEN_MIN: usize = 12;
+    const PACKET_LEN_MIN: usize = ECHO_LEN;
+    const TS_MSW_OFFSET: usize = 0;
+    const TS_LSW_OFFSET: usize = Self::TS_MSW_OFFSET + 4;
+    const DELAY_OFFSET: usize = Self::TS_LSW_OFFSET + 4;
 
     pub fn try_new<T, U>(bytes: &'a T) -> Result<Self, error::Error>
     where
@@ -31,7 +41,27 @@ impl<'a> EchoMessage<'a> {
         }
     }
 
-    pub fn timestamp() -> rist_rs_types::time::ntp::Timestamp {
-        todo!()
+    /// NTP timestamp of the echo request. Echo responses carry the timestamp of the
+    /// request they answer
+    pub fn timestamp(&self) -> Timestamp {
+        Timestamp::new(
+            crate::util::read_int!(self.data, u32, Self::TS_MSW_OFFSET),
+            crate::util::read_int!(self.data, u32, Self::TS_LSW_OFFSET),
+        )
     }
+
+    /// Time the responder took to process the request, transmitted in microseconds.
+    /// Always zero for echo requests
+    pub fn processing_delay(&self) -> Duration {
+        Duration::from_micros(crate::util::read_int!(self.data, u32, Self::DELAY_OFFSET) as u64)
+    }
+}
+
+/// Write the application-dependent data of an echo request or response. The processing delay
+/// saturates at `u32::MAX` microseconds
+pub fn write_echo(buf: &mut [u8; ECHO_LEN], timestamp: Timestamp, processing_delay: Duration) {
+    let delay = u32::try_from(processing_delay.as_micros()).unwrap_or(u32::MAX);
+    buf[0..4].copy_from_slice(&timestamp.seconds().to_be_bytes());
+    buf[4..8].copy_from_slice(&timestamp.frac().to_be_bytes());
+    buf[8..12].copy_from_slice(&delay.to_be_bytes());
 }
@@ -0,0 +1,318 @@
+use core::time::Duration;
+
+use rist_rs_types::time::ntp::Timestamp;
+
+use super::app::rist::{self, range_nack, rtt};
+use super::rx_report::{ReceptionReport, RX_REPORT_LEN};
+use super::{
+    bye, error, nack, sdes, sr, xr, RTCP_PT_APP, RTCP_PT_BYE, RTCP_PT_NACK, RTCP_PT_RR,
+    RTCP_PT_SDES, RTCP_PT_SR, RTCP_PT_XR,
+};
+
+/// Builds compound RTCP packets by appending RTCP packets to a caller-supplied buffer.
+/// The length field of every packet is calculated from the written content, the result
+/// can be parsed with [super::RTCPPacketViewIterator].
+#[derive(Debug)]
+pub struct RTCPCompoundBuilder<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+    last: Option<usize>,
+}
+
+impl<'a> RTCPCompoundBuilder<'a> {
+    /// Length of the common RTCP header
+    const HEADER_LEN: usize = 4;
+
+    /// Maximum value of the 5 bit item count/subtype field
+    const AUX_MAX: usize = 0x1f;
+
+    /// Create a new builder that writes packets to the start of `buf`
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self {
+            buf,
+            len: 0,
+            last: None,
+        }
+    }
+
+    /// Number of bytes written so far
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// True if no packets were written yet
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the compound packet written so far
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+
+    /// Consume the builder and return the written compound packet
+    pub fn finish(self) -> &'a [u8] {
+        &self.buf[..self.len]
+    }
+
+    fn aux(count: usize) -> Result<u8, error::Error> {
+        if count > Self::AUX_MAX {
+            Err(error::too_many_items(count))
+        } else {
+            Ok(count as u8)
+        }
+    }
+
+    /// Append the common header of a new packet with a body of `body_len` bytes and
+    /// return the (uninitialized) body
+    fn append(&mut self, aux: u8, pt: u8, body_len: usize) -> Result<&mut [u8], error::Error> {
+        debug_assert_eq!(body_len % 4, 0);
+        let packet_len = Self::HEADER_LEN + body_len;
+        if packet_len / 4 - 1 > u16::MAX as usize {
+            return Err(error::packet_too_long(packet_len));
+        }
+        let end = self.len + packet_len;
+        if end > self.buf.len() {
+            return Err(error::not_enough_space(end, self.buf.len()));
+        }
+        let packet = &mut self.buf[self.len..end];
+        packet[0] = 0x80 | aux;
+        packet[1] = pt;
+        packet[2..4].copy_from_slice(&((packet_len / 4 - 1) as u16).to_be_bytes());
+        self.last = Some(self.len);
+        self.len = end;
+        Ok(&mut packet[Self::HEADER_LEN..])
+    }
+
+    fn write_reception_reports(buf: &mut [u8], reports: &[ReceptionReport]) {
+        for (chunk, report) in buf.chunks_exact_mut(RX_REPORT_LEN).zip(reports) {
+            report.write(
+                chunk
+                    .try_into()
+                    .expect(rist_rs_types::internal::INTERNAL_ERR_PRE_VALIDATED),
+            );
+        }
+    }
+
+    /// Append a sender report (SR) with the given reception report blocks
+    pub fn sender_report(
+        &mut self,
+        info: &sr::SenderInfo,
+        reports: &[ReceptionReport],
+    ) -> Result<&mut Self, error::Error> {
+        let aux = Self::aux(reports.len())?;
+        let body = self.append(
+            aux,
+            RTCP_PT_SR,
+            sr::SenderInfo::LEN + reports.len() * RX_REPORT_LEN,
+        )?;
+        let (info_buf, reports_buf) = body.split_at_mut(sr::SenderInfo::LEN);
+        info.write(
+            info_buf
+                .try_into()
+                .expect(rist_rs_types::internal::INTERNAL_ERR_PRE_VALIDATED),
+        );
+        Self::write_reception_reports(reports_buf, reports);
+        Ok(self)
+    }
+
+    /// Append a receiver report (RR) with the given reception report blocks
+    pub fn receiver_report(
+        &mut self,
+        ssrc: u32,
+        reports: &[ReceptionReport],
+    ) -> Result<&mut Self, error::Error> {
+        let aux = Self::aux(reports.len())?;
+        let body = self.append(aux, RTCP_PT_RR, 4 + reports.len() * RX_REPORT_LEN)?;
+        body[..4].copy_from_slice(&ssrc.to_be_bytes());
+        Self::write_reception_reports(&mut body[4..], reports);
+        Ok(self)
+    }
+
+    /// Append a source description (SDES) packet. Every item is written as a separate chunk,
+    /// so the source count holds the number of items, even if several items describe the same
+    /// source
+    pub fn source_description(
+        &mut self,
+        items: &[sdes::SourceDescriptionItem],
+    ) -> Result<&mut Self, error::Error> {
+        if let Some(item) = items
+            .iter()
+            .find(|item| item.payload.value().len() > u8::MAX as usize)
+        {
+            return Err(sdes::error::Error::ItemTooLong(item.payload.value().len()).into());
+        }
+        let aux = Self::aux(items.len())?;
+        let body_len = items.iter().map(|item| item.chunk_len()).sum();
+        let body = self.append(aux, RTCP_PT_SDES, body_len)?;
+        let mut pos = 0;
+        for item in items {
+            let len = item.chunk_len();
+            item.write(&mut body[pos..pos + len])
+                .expect(rist_rs_types::internal::INTERNAL_ERR_PRE_VALIDATED);
+            pos += len;
+        }
+        Ok(self)
+    }
+
+    /// Append a goodbye (BYE) packet for the sources `ssrcs` with an optional reason for leaving
+    pub fn bye(&mut self, ssrcs: &[u32], reason: Option<&str>) -> Result<&mut Self, error::Error> {
+        let aux = Self::aux(ssrcs.len())?;
+        if let Some(reason) = reason.filter(|r| r.len() > u8::MAX as usize) {
+            return Err(bye::error::Error::ReasonTooLong(reason.len()).into());
+        }
+        let body = self.append(aux, RTCP_PT_BYE, bye::body_len(ssrcs.len(), reason))?;
+        bye::write(ssrcs, reason, body)?;
+        Ok(self)
+    }
+
+    /// Append an extended report (XR) packet from `ssrc` carrying the given report blocks
+    pub fn extended_report(
+        &mut self,
+        ssrc: u32,
+        blocks: &[xr::ReportBlock],
+    ) -> Result<&mut Self, error::Error> {
+        let body_len = 4 + blocks.iter().map(|block| block.len()).sum::<usize>();
+        let body = self.append(0, RTCP_PT_XR, body_len)?;
+        body[..4].copy_from_slice(&ssrc.to_be_bytes());
+        let mut pos = 4;
+        for block in blocks {
+            let len = block.len();
+            block.write(&mut body[pos..pos + len])?;
+            pos += len;
+        }
+        Ok(self)
+    }
+
+    /// Append an application-defined (APP) packet. The length of `data` must be a multiple of 4 bytes.
+    pub fn application_specific(
+        &mut self,
+        subtype: u8,
+        ssrc: u32,
+        name: [u8; 4],
+        data: &[u8],
+    ) -> Result<&mut Self, error::Error> {
+        let aux = Self::aux(subtype as usize)?;
+        if !data.len().is_multiple_of(4) {
+            return Err(error::unaligned_length(data.len()));
+        }
+        let body = self.append(aux, RTCP_PT_APP, 8 + data.len())?;
+        body[..4].copy_from_slice(&ssrc.to_be_bytes());
+        body[4..8].copy_from_slice(&name);
+        body[8..].copy_from_slice(data);
+        Ok(self)
+    }
+
+    /// Append a RIST application-defined packet and return its application-dependent data
+    fn append_rist(
+        &mut self,
+        subtype: u8,
+        ssrc: u32,
+        data_len: usize,
+    ) -> Result<&mut [u8], error::Error> {
+        let body = self.append(subtype, RTCP_PT_APP, 8 + data_len)?;
+        body[..4].copy_from_slice(&ssrc.to_be_bytes());
+        body[4..8].copy_from_slice(&rist::NAME);
+        Ok(&mut body[8..])
+    }
+
+    /// Append a RIST range NACK (APP subtype 0) for the media source `ssrc`
+    pub fn rist_range_nack(
+        &mut self,
+        ssrc: u32,
+        requests: &[range_nack::PacketRangeRequest],
+    ) -> Result<&mut Self, error::Error> {
+        let data = self.append_rist(range_nack::SUBTYPE_RANGE_NACK, ssrc, requests.len() * 4)?;
+        for (chunk, request) in data.chunks_exact_mut(4).zip(requests) {
+            chunk.copy_from_slice(&<[u8; 4]>::from(request));
+        }
+        Ok(self)
+    }
+
+    /// Append a RIST RTT echo request (APP subtype 2) carrying the NTP time at which it is sent
+    pub fn rist_rtt_echo_request(
+        &mut self,
+        ssrc: u32,
+        timestamp: Timestamp,
+    ) -> Result<&mut Self, error::Error> {
+        self.rist_rtt_echo(rtt::SUBTYPE_RTT_ECHO_REQ, ssrc, timestamp, Duration::ZERO)
+    }
+
+    /// Append a RIST RTT echo response (APP subtype 3) that returns the `timestamp` of the
+    /// answered request together with the time it took to process it
+    pub fn rist_rtt_echo_response(
+        &mut self,
+        ssrc: u32,
+        timestamp: Timestamp,
+        processing_delay: Duration,
+    ) -> Result<&mut Self, error::Error> {
+        self.rist_rtt_echo(rtt::SUBTYPE_RTT_ECHO_RES, ssrc, timestamp, processing_delay)
+    }
+
+    fn rist_rtt_echo(
+        &mut self,
+        subtype: u8,
+        ssrc: u32,
+        timestamp: Timestamp,
+        processing_delay: Duration,
+    ) -> Result<&mut Self, error::Error> {
+        let data = self.append_rist(subtype, ssrc, rtt::ECHO_LEN)?;
+        rtt::write_echo(
+            data.try_into()
+                .expect(rist_rs_types::internal::INTERNAL_ERR_PRE_VALIDATED),
+            timestamp,
+            processing_delay,
+        );
+        Ok(self)
+    }
+
+    /// Append a generic NACK feedback message (PT 205, FMT 1) requesting retransmission of the
+    /// `lost` sequence numbers, compressed into the minimal number of PID/BLP entries. Lost
+    /// sequence numbers must be given in ascending (wrapping) order.
+    pub fn generic_nack(
+        &mut self,
+        sender_ssrc: u32,
+        media_ssrc: u32,
+        lost: &[u16],
+    ) -> Result<&mut Self, error::Error> {
+        nack::check_ascending(lost)?;
+        let entries = nack::compress(lost.iter().copied()).count();
+        let body = self.append(nack::FMT_GENERIC_NACK, RTCP_PT_NACK, 8 + entries * 4)?;
+        body[..4].copy_from_slice(&sender_ssrc.to_be_bytes());
+        body[4..8].copy_from_slice(&media_ssrc.to_be_bytes());
+        for (chunk, entry) in body[8..]
+            .chunks_exact_mut(4)
+            .zip(nack::compress(lost.iter().copied()))
+        {
+            chunk.copy_from_slice(&<[u8; 4]>::from(entry));
+        }
+        Ok(self)
+    }
+
+    /// Add `len` bytes of padding to the packet appended last. `len` includes the trailing
+    /// padding-length byte and must be a non-zero multiple of 4. RFC3550 only allows padding
+    /// on the last packet of a compound packet, the caller is responsible for not appending
+    /// further packets.
+    pub fn padding(&mut self, len: u8) -> Result<&mut Self, error::Error> {
+        let start = match self.last {
+            Some(start) if len != 0 && len.is_multiple_of(4) && self.buf[start] & 0x20 == 0 => {
+                start
+            }
+            _ => return Err(error::invalid_padding()),
+        };
+        let end = self.len + len as usize;
+        if end > self.buf.len() {
+            return Err(error::not_enough_space(end, self.buf.len()));
+        }
+        if (end - start) / 4 - 1 > u16::MAX as usize {
+            return Err(error::packet_too_long(end - start));
+        }
+        self.buf[self.len..end - 1].fill(0);
+        self.buf[end - 1] = len;
+        self.buf[start] |= 0x20;
+        self.buf[start + 2..start + 4]
+            .copy_from_slice(&(((end - start) / 4 - 1) as u16).to_be_bytes());
+        self.len = end;
+        Ok(self)
+    }
+}
@@ -0,0 +1,141 @@
+use core::str::from_utf8;
+
+pub mod error {
+    use core::str::Utf8Error;
+
+    #[derive(Debug, Clone, Copy)]
+    pub enum Error {
+        /// The packet is too short for the number of sources in the source count field
+        InvalidPacketLen(usize),
+        /// The reason for leaving exceeds the packet
+        EndOfPacketReached,
+        /// The reason for leaving contains invalid utf8 characters
+        Utf8(Utf8Error),
+        /// The reason for leaving is longer than 255 bytes and can not be written
+        ReasonTooLong(usize),
+    }
+
+    impl From<Utf8Error> for Error {
+        fn from(e: Utf8Error) -> Self {
+            Self::Utf8(e)
+        }
+    }
+}
+
+/// Length of a single SSRC/CSRC entry
+const SSRC_LEN: usize = 4;
+
+/// Number of bytes the body of a BYE packet occupies for `sources` sources and an optional
+/// reason, including padding to the next 32-bit boundary
+pub fn body_len(sources: usize, reason: Option<&str>) -> usize {
+    sources * SSRC_LEN + reason.map_or(0, |r| (1 + r.len()).next_multiple_of(4))
+}
+
+/// Write the body of a BYE packet into `buf` which must be exactly [body_len] bytes long.
+/// Returns an error if the reason is too long to be represented in the length field
+pub fn write(ssrcs: &[u32], reason: Option<&str>, buf: &mut [u8]) -> Result<(), error::Error> {
+    if let Some(reason) = reason.filter(|r| r.len() > u8::MAX as usize) {
+        return Err(error::Error::ReasonTooLong(reason.len()));
+    }
+    debug_assert_eq!(buf.len(), body_len(ssrcs.len(), reason));
+    for (chunk, ssrc) in buf.chunks_exact_mut(SSRC_LEN).zip(ssrcs) {
+        chunk.copy_from_slice(&ssrc.to_be_bytes());
+    }
+    if let Some(reason) = reason {
+        let buf = &mut buf[ssrcs.len() * SSRC_LEN..];
+        buf[0] = reason.len() as u8;
+        buf[1..1 + reason.len()].copy_from_slice(reason.as_bytes());
+        buf[1 + reason.len()..].fill(0);
+    }
+    Ok(())
+}
+
+/// View over the body of a goodbye (BYE) packet (RFC3550 6.6)
+#[derive(Debug, Clone, Copy)]
+pub struct ByePacketView<'a> {
+    data: &'a [u8],
+    count: u8,
+}
+
+impl<'a> TryFrom<(u8, &'a [u8])> for ByePacketView<'a> {
+    type Error = error::Error;
+
+    fn try_from(value: (u8, &'a [u8])) -> Result<Self, Self::Error> {
+        ByePacketView::try_new(value.0, value.1)
+    }
+}
+
+impl<'a> ByePacketView<'a> {
+    pub fn try_new<T, U>(count: u8, bytes: &'a T) -> Result<Self, error::Error>
+    where
+        T: AsRef<U> + ?Sized,
+        U: ?Sized + 'a,
+        &'a U: Into<&'a [u8]>,
+    {
+        let data: &'a [u8] = bytes.as_ref().into();
+        if data.len() < count as usize * SSRC_LEN {
+            Err(error::Error::InvalidPacketLen(data.len()))
+        } else {
+            Ok(Self { data, count })
+        }
+    }
+
+    /// Number of sources leaving the session
+    pub fn source_count(&self) -> u8 {
+        self.count
+    }
+
+    /// Iterate over the SSRC/CSRC identifiers of the sources leaving the session
+    pub fn ssrcs(&self) -> impl Iterator<Item = u32> + 'a {
+        self.data
+            .chunks_exact(SSRC_LEN)
+            .take(self.count as usize)
+            .map(|b| crate::util::read_int!(b, u32, 0))
+    }
+
+    /// Get the reason for leaving. Returns `None` if the packet does not carry a reason and
+    /// an error if the reason exceeds the packet or is not valid utf8
+    pub fn reason(&self) -> Option<Result<&'a str, error::Error>> {
+        let data = &self.data[self.count as usize * SSRC_LEN..];
+        let len = *data.first()? as usize;
+        Some(
+            data.get(1..1 + len)
+                .ok_or(error::Error::EndOfPacketReached)
+                .and_then(|reason| Ok(from_utf8(reason)?)),
+        )
+    }
+}
+
+#[allow(unused)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn read() {
+        let data = [
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x03, b'b', b'y', b'e',
+        ];
+        let bye = ByePacketView::try_new(2, &data).unwrap();
+        assert_eq!(bye.source_count(), 2);
+        assert_eq!(bye.ssrcs().collect::<Vec<_>>(), [0x01020304, 0x05060708]);
+        assert_eq!(bye.reason().unwrap().unwrap(), "bye");
+        let bye = ByePacketView::try_new(3, &data).unwrap();
+        assert!(bye.reason().is_none());
+        let bye = ByePacketView::try_new(1, &data[..8]).unwrap();
+        assert!(bye.reason().unwrap().is_err());
+        assert!(ByePacketView::try_new(4, &data).is_err());
+    }
+
+    #[test]
+    fn write_and_read() {
+        let mut buf = [0xffu8; 16];
+        assert_eq!(body_len(2, Some("eof")), 12);
+        assert_eq!(body_len(2, Some("done")), 16);
+        assert_eq!(body_len(2, None), 8);
+        write(&[1, 2], Some("done"), &mut buf).unwrap();
+        assert_eq!(&buf[8..], &[4, b'd', b'o', b'n', b'e', 0, 0, 0]);
+        let bye = ByePacketView::try_new(2, &buf).unwrap();
+        assert_eq!(bye.ssrcs().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(bye.reason().unwrap().unwrap(), "done");
+    }
+}
@@ -1,13 +1,19 @@
 #![allow(unused)]
 pub mod app;
+mod builder;
+pub mod bye;
+pub mod nack;
 pub mod rr;
 pub mod rx_report;
 pub mod sdes;
 pub mod sr;
+pub mod xr;
+
+pub use builder::RTCPCompoundBuilder;
 
 pub mod error {
 
-    #[derive(Debug)]
+    #[derive(Debug, Clone, Copy)]
     pub enum ErrorKind {
         NotEnoughData {
             need: usize,
@@ -16,17 +22,52 @@ pub mod error {
         },
         InvalidPadding,
         UnknownReportType(u8),
+        NotEnoughSpace {
+            need: usize,
+            got: usize,
+        },
+        TooManyItems(usize),
+        UnalignedLength(usize),
+        /// The packet exceeds the maximum length the 16 bit length field can express
+        PacketTooLong(usize),
         SDES(super::sdes::error::Error),
         RR(super::rr::error::Error),
         SR(super::sr::error::Error),
         APP(super::app::error::Error),
+        NACK(super::nack::error::Error),
+        BYE(super::bye::error::Error),
+        XR(super::xr::error::Error),
+        InvalidCompound {
+            rule: CompoundRule,
+            offset: usize,
+        },
+    }
+
+    /// Validity checks for compound RTCP packets (RFC3550 A.2)
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum CompoundRule {
+        /// Every packet must have version 2
+        Version,
+        /// The first packet must be a sender or receiver report
+        FirstPacketType,
+        /// Only the last packet may be padded and the padding must fit into the packet
+        Padding,
+        /// The lengths of all packets must add up to the length of the compound packet
+        Length,
     }
 
-    #[derive(Debug)]
+    #[derive(Debug, Clone, Copy)]
     pub struct Error {
         kind: ErrorKind,
     }
 
+    impl Error {
+        /// Get the kind of error
+        pub fn kind(&self) -> &ErrorKind {
+            &self.kind
+        }
+    }
+
     impl From<super::sdes::error::Error> for Error {
         fn from(e: super::sdes::error::Error) -> Self {
             Error {
@@ -59,6 +100,30 @@ pub mod error {
         }
     }
 
+    impl From<super::nack::error::Error> for Error {
+        fn from(e: super::nack::error::Error) -> Self {
+            Error {
+                kind: ErrorKind::NACK(e),
+            }
+        }
+    }
+
+    impl From<super::bye::error::Error> for Error {
+        fn from(e: super::bye::error::Error) -> Self {
+            Error {
+                kind: ErrorKind::BYE(e),
+            }
+        }
+    }
+
+    impl From<super::xr::error::Error> for Error {
+        fn from(e: super::xr::error::Error) -> Self {
+            Error {
+                kind: ErrorKind::XR(e),
+            }
+        }
+    }
+
     pub fn not_enough_data(need: usize, got: usize, field: &'static &'static str) -> Error {
         Error {
             kind: ErrorKind::NotEnoughData { need, got, field },
@@ -76,6 +141,36 @@ pub mod error {
             kind: ErrorKind::UnknownReportType(t),
         }
     }
+
+    pub fn not_enough_space(need: usize, got: usize) -> Error {
+        Error {
+            kind: ErrorKind::NotEnoughSpace { need, got },
+        }
+    }
+
+    pub fn too_many_items(count: usize) -> Error {
+        Error {
+            kind: ErrorKind::TooManyItems(count),
+        }
+    }
+
+    pub fn invalid_compound(rule: CompoundRule, offset: usize) -> Error {
+        Error {
+            kind: ErrorKind::InvalidCompound { rule, offset },
+        }
+    }
+
+    pub fn unaligned_length(len: usize) -> Error {
+        Error {
+            kind: ErrorKind::UnalignedLength(len),
+        }
+    }
+
+    pub fn packet_too_long(len: usize) -> Error {
+        Error {
+            kind: ErrorKind::PacketTooLong(len),
+        }
+    }
 }
 
 #[allow(clippy::upper_case_acronyms)]
@@ -85,15 +180,18 @@ pub enum RTCPReportView<'a> {
     RR(rr::ReceiverReportMessageView<'a>),
     SDES(sdes::SourceDescriptionMessageIterator<'a>),
     APP(app::ApplicationSpecificMessageView<'a>),
-    NACK(),
+    NACK(nack::NACKPacketView<'a>),
+    BYE(bye::ByePacketView<'a>),
+    XR(xr::ExtendedReportView<'a>),
 }
 
 const RTCP_PT_SR: u8 = 200;
 const RTCP_PT_RR: u8 = 201;
 const RTCP_PT_SDES: u8 = 202;
-const RTCP_PT_BYTE: u8 = 203;
+const RTCP_PT_BYE: u8 = 203;
 const RTCP_PT_APP: u8 = 204;
 const RTCP_PT_NACK: u8 = 205;
+const RTCP_PT_XR: u8 = 207;
 
 impl<'a> RTCPReportView<'a> {
     fn try_new<T, U>(packet_type: u8, aux: u8, bytes: &'a T) -> Result<Self, error::Error>
@@ -113,9 +211,16 @@ impl<'a> RTCPReportView<'a> {
             202 => Ok(RTCPReportView::SDES(
                 sdes::SourceDescriptionMessageIterator::try_new(bytes)?,
             )),
+            203 => Ok(RTCPReportView::BYE(bye::ByePacketView::try_new(
+                aux, bytes,
+            )?)),
             204 => Ok(RTCPReportView::APP(
                 app::ApplicationSpecificMessageView::try_new(aux, bytes)?,
             )),
+            205 => Ok(RTCPReportView::NACK(nack::NACKPacketView::try_new(
+                aux, bytes,
+            )?)),
+            207 => Ok(RTCPReportView::XR(xr::ExtendedReportView::try_new(bytes)?)),
             _ => Err(error::unknown_report_type(packet_type)),
         }
     }
@@ -207,9 +312,14 @@ impl<'a> RTCPPacketView<'a> {
         (self.pt() == RTCP_PT_APP).then(|| self.aux())
     }
 
-    /// number items in the packet (reception reports, sdes items...)
+    /// feedback message type of transport layer feedback packets
+    pub fn feedback_format(&self) -> Option<u8> {
+        (self.pt() == RTCP_PT_NACK).then(|| self.aux())
+    }
+
+    /// number items in the packet (reception reports, sdes items, sources leaving...)
     pub fn item_count(&self) -> Option<u8> {
-        [RTCP_PT_RR, RTCP_PT_SR, RTCP_PT_SDES]
+        [RTCP_PT_RR, RTCP_PT_SR, RTCP_PT_SDES, RTCP_PT_BYE]
             .contains(&self.pt())
             .then(|| self.aux())
     }
@@ -227,9 +337,58 @@ impl<'a> RTCPPacketView<'a> {
     }
 }
 
+/// Check that `bytes` is a valid compound RTCP packet according to RFC3550 A.2: every packet
+/// has version 2, the first packet is an unpadded sender or receiver report, only the last packet
+/// is padded and the packet lengths add up to the length of `bytes`. Padding on the first packet
+/// is invalid even if it is the only packet. Returns an iterator over
+/// the packets or an error with the violated rule and the offset of the offending packet.
+pub fn validate_compound<'a, T, U>(bytes: &'a T) -> Result<RTCPPacketViewIterator<'a>, error::Error>
+where
+    T: AsRef<U> + ?Sized,
+    U: ?Sized + 'a,
+    &'a U: Into<&'a [u8]>,
+{
+    use error::CompoundRule;
+    const HEADER_LEN: usize = 4;
+    let data: &'a [u8] = bytes.as_ref().into();
+    if data.is_empty() {
+        return Err(error::invalid_compound(CompoundRule::Length, 0));
+    }
+    let mut offset = 0;
+    while offset < data.len() {
+        let packet = &data[offset..];
+        if packet.len() < HEADER_LEN {
+            return Err(error::invalid_compound(CompoundRule::Length, offset));
+        }
+        let len = (crate::util::read_int!(packet, u16, 2) as usize + 1) * 4;
+        if packet[0] >> 6 != 2 {
+            return Err(error::invalid_compound(CompoundRule::Version, offset));
+        }
+        if offset == 0 && packet[1] != RTCP_PT_SR && packet[1] != RTCP_PT_RR {
+            return Err(error::invalid_compound(CompoundRule::FirstPacketType, 0));
+        }
+        if offset == 0 && packet[0] & 0x20 != 0 {
+            return Err(error::invalid_compound(CompoundRule::Padding, 0));
+        }
+        if len > packet.len() {
+            return Err(error::invalid_compound(CompoundRule::Length, offset));
+        }
+        if packet[0] & 0x20 != 0
+            && (len != packet.len()
+                || packet[len - 1] == 0
+                || packet[len - 1] as usize > len - HEADER_LEN)
+        {
+            return Err(error::invalid_compound(CompoundRule::Padding, offset));
+        }
+        offset += len;
+    }
+    Ok(RTCPPacketViewIterator::from(data))
+}
+
 /// Can wrap any number of concatenated RTCP packets and provides
 /// an implementation of [Iterator] that yields RTCP packets that are
 /// parsed on-the-fly.
+#[derive(Debug, Clone)]
 pub struct RTCPPacketViewIterator<'a> {
     data: &'a [u8],
     pos: usize,
@@ -295,8 +454,18 @@ impl<'a> Iterator for RTCPPacketViewIterator<'a> {
 
 #[allow(unused)]
 mod test {
-    use super::RTCPPacketView;
+    use super::app::rist::range_nack::PacketRangeRequest;
+    use super::app::rist::RistApplicationSpecificMessage;
+    use super::app::MessageView;
+    use super::error::{CompoundRule, ErrorKind};
+    use super::rx_report::ReceptionReport;
+    use super::sdes::{SourceDescriptionItem, SourceDescriptionItemPayload};
+    use super::sr::SenderInfo;
+    use super::xr::{DlrrSubBlock, ReportBlock, ReportBlockView};
+    use super::{RTCPCompoundBuilder, RTCPPacketView, RTCPReportView};
     use crate::rtcp::RTCPPacketViewIterator;
+    use core::time::Duration;
+    use rist_rs_types::time::ntp::Timestamp;
 
     const RR_WITH_SDES: [u8; 416] = [
         // packet 0
@@ -368,4 +537,429 @@ mod test {
             }
         }
     }
+
+    const CNAME_0: &str = "jonasohland-macbook-pro.local";
+    const CNAME_1: &str = "jonasohland-macbook-pro.locall";
+    const CNAME_2: &str = "jonasohland-macbook-pro.loca";
+
+    const RX_REPORT: ReceptionReport = ReceptionReport {
+        ssrc: 0x58f33dea,
+        fraction_lost: 0,
+        cumulative_lost: 0,
+        extended_highest_sequence_number: 0x2cd8,
+        jitter: 0x760,
+        last_sr: 0x86d9f581,
+        delay_since_last_sr: 1,
+    };
+
+    const RTT_ECHO: [u8; 12] = [
+        0x83, 0xb1, 0xe7, 0x69, 0x80, 0x27, 0xfa, 0x1a, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    fn cname(ssrc: u32, name: &str) -> SourceDescriptionItem<'_> {
+        SourceDescriptionItem {
+            ssrc,
+            payload: SourceDescriptionItemPayload::CNAME(name),
+        }
+    }
+
+    #[test]
+    fn build_rr_with_sdes() {
+        let sdes = [
+            cname(0x1d56bc2e, CNAME_0),
+            cname(0x1d56bc2e, CNAME_1),
+            cname(0x1d56bc2e, CNAME_2),
+        ];
+        let mut buf = [0u8; 512];
+        let mut builder = RTCPCompoundBuilder::new(&mut buf);
+        builder
+            .source_description(&sdes)
+            .unwrap()
+            .padding(4)
+            .unwrap();
+        builder
+            .source_description(&sdes)
+            .unwrap()
+            .padding(4)
+            .unwrap();
+        builder
+            .sender_report(
+                &SenderInfo {
+                    ssrc: 0x1d56bc2e,
+                    ntp_timestamp: Timestamp::new(0xe665a542, 0x31318761),
+                    rtp_timestamp: 0x88ba9a5c,
+                    packet_count: 0,
+                    octet_count: 0,
+                },
+                &[],
+            )
+            .unwrap()
+            .receiver_report(0xd2bd4e3e, &[RX_REPORT])
+            .unwrap()
+            .source_description(&[cname(0x58f33dea, "ACLTP ChannelHandle 30")])
+            .unwrap()
+            .source_description(&[cname(0xd2bd4e3e, "unknown@200.57.7.204")])
+            .unwrap()
+            .application_specific(3, 0x5ffdb03c, *b"RIST", &RTT_ECHO)
+            .unwrap()
+            .application_specific(2, 0x5ffdb03c, *b"RIST", &RTT_ECHO)
+            .unwrap();
+        // the captured sender counts sources instead of chunks in the SDES packets
+        let mut expected = RR_WITH_SDES;
+        expected[0] = 0xa3;
+        expected[120] = 0xa3;
+        assert_eq!(builder.len(), expected.len());
+        assert_eq!(builder.finish(), expected);
+    }
+
+    #[test]
+    fn build_sdes_chunk_count() {
+        let sdes = [
+            cname(0x01020304, "abc"),
+            SourceDescriptionItem {
+                ssrc: 0x01020304,
+                payload: SourceDescriptionItemPayload::NAME("def"),
+            },
+            cname(0x05060708, "ghi"),
+        ];
+        let mut buf = [0u8; 64];
+        let mut builder = RTCPCompoundBuilder::new(&mut buf);
+        builder.source_description(&sdes).unwrap();
+        let data = builder.finish();
+        assert_eq!(data[0] & 0x1f, 3);
+        let packet = RTCPPacketView::try_new(data).unwrap();
+        assert_eq!(packet.item_count(), Some(3));
+        match packet.report().unwrap() {
+            RTCPReportView::SDES(sdes) => {
+                let items = sdes.collect::<Result<Vec<_>, _>>().unwrap();
+                assert_eq!(items.len(), 3);
+                assert_eq!(items[1].ssrc, 0x01020304);
+                assert_eq!(items[1].payload.value(), "def");
+            }
+            _ => panic!("expected source description"),
+        }
+    }
+
+    #[test]
+    fn build_and_parse() {
+        let mut buf = [0u8; 128];
+        let mut builder = RTCPCompoundBuilder::new(&mut buf);
+        builder
+            .sender_report(
+                &SenderInfo {
+                    ssrc: 0x01020304,
+                    ntp_timestamp: Timestamp::new(1, 2),
+                    rtp_timestamp: 3,
+                    packet_count: 4,
+                    octet_count: 5,
+                },
+                &[RX_REPORT, RX_REPORT],
+            )
+            .unwrap()
+            .source_description(&[cname(0x01020304, "abc")])
+            .unwrap()
+            .padding(8)
+            .unwrap();
+        let packets = RTCPPacketViewIterator::new(builder.finish())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(packets.len(), 2);
+        match packets[0].report().unwrap() {
+            RTCPReportView::SR(sr) => {
+                assert_eq!(sr.sender_ssrc(), 0x01020304);
+                assert_eq!(sr.ntp_timestamp().seconds(), 1);
+                assert_eq!(sr.ntp_timestamp().frac(), 2);
+                assert_eq!(sr.rtp_timestamp(), 3);
+                assert_eq!(sr.packet_count(), 4);
+                assert_eq!(sr.octet_count(), 5);
+                assert_eq!(sr.reception_reports().count(), 2);
+                assert!(sr
+                    .reception_reports()
+                    .all(|report| ReceptionReport::from(report) == RX_REPORT));
+            }
+            _ => panic!("expected sender report"),
+        }
+        assert_eq!(packets[1].padding_len().unwrap().unwrap(), 8);
+        match packets[1].report().unwrap() {
+            RTCPReportView::SDES(sdes) => {
+                let items = sdes.collect::<Result<Vec<_>, _>>().unwrap();
+                assert_eq!(items.len(), 1);
+                assert_eq!(items[0].payload.value(), "abc");
+            }
+            _ => panic!("expected source description"),
+        }
+    }
+
+    #[test]
+    fn build_invalid() {
+        let mut buf = [0u8; 16];
+        let mut builder = RTCPCompoundBuilder::new(&mut buf);
+        // no packet to pad
+        assert!(builder.padding(4).is_err());
+        // unaligned application data
+        assert!(builder
+            .application_specific(0, 0, *b"RIST", &[0, 0])
+            .is_err());
+        // subtype does not fit into 5 bits
+        assert!(builder.application_specific(32, 0, *b"RIST", &[]).is_err());
+        // not enough space for the sender info
+        let info = SenderInfo {
+            ssrc: 0,
+            ntp_timestamp: Timestamp::new(0, 0),
+            rtp_timestamp: 0,
+            packet_count: 0,
+            octet_count: 0,
+        };
+        assert!(builder.sender_report(&info, &[]).is_err());
+        assert!(builder.is_empty());
+        builder.receiver_report(0, &[]).unwrap().padding(4).unwrap();
+        // already padded
+        assert!(builder.padding(4).is_err());
+        // length does not fit into the length field
+        let data = vec![0u8; 4 * (u16::MAX as usize - 1)];
+        assert!(matches!(
+            builder
+                .application_specific(0, 0, *b"RIST", &data)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::PacketTooLong(262_148)
+        ));
+    }
+
+    #[test]
+    fn build_and_parse_generic_nack() {
+        let lost = [100, 101, 116, 117, 200];
+        let mut buf = [0u8; 64];
+        let mut builder = RTCPCompoundBuilder::new(&mut buf);
+        builder
+            .receiver_report(0x01020304, &[])
+            .unwrap()
+            .generic_nack(0x01020304, 0x0a0b0c0d, &lost)
+            .unwrap();
+        let packets = RTCPPacketViewIterator::new(builder.finish())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(packets[1].feedback_format(), Some(1));
+        match packets[1].report().unwrap() {
+            RTCPReportView::NACK(nack) => {
+                assert_eq!(nack.sender_ssrc(), 0x01020304);
+                assert_eq!(nack.media_ssrc(), 0x0a0b0c0d);
+                assert_eq!(nack.entries().count(), 3);
+                assert_eq!(nack.lost_sequence_numbers().collect::<Vec<_>>(), lost);
+            }
+            _ => panic!("expected generic nack"),
+        }
+        let mut buf = [0u8; 64];
+        let mut builder = RTCPCompoundBuilder::new(&mut buf);
+        // not in ascending order
+        assert!(builder
+            .generic_nack(0x01020304, 0x0a0b0c0d, &[200, 100])
+            .is_err());
+        assert!(builder.is_empty());
+    }
+
+    #[test]
+    fn build_rist_rtt_echo() {
+        let mut buf = [0u8; 64];
+        let mut builder = RTCPCompoundBuilder::new(&mut buf);
+        builder
+            .rist_rtt_echo_response(
+                0x5ffdb03c,
+                Timestamp::new(0x83b1e769, 0x8027fa1a),
+                Duration::ZERO,
+            )
+            .unwrap()
+            .rist_rtt_echo_request(0x5ffdb03c, Timestamp::new(0x83b1e769, 0x8027fa1a))
+            .unwrap();
+        assert_eq!(builder.finish(), &RR_WITH_SDES[RR_WITH_SDES.len() - 48..]);
+
+        let mut buf = [0u8; 64];
+        let mut builder = RTCPCompoundBuilder::new(&mut buf);
+        builder
+            .rist_rtt_echo_response(
+                0x01020304,
+                Timestamp::new(1234, 5678),
+                Duration::from_micros(2500),
+            )
+            .unwrap();
+        let packet = RTCPPacketView::try_new(builder.finish()).unwrap();
+        assert_eq!(packet.subtype(), Some(3));
+        match packet.report().unwrap() {
+            RTCPReportView::APP(app) => match app.message().unwrap() {
+                MessageView::Rist(RistApplicationSpecificMessage::RTTEchoResponse(echo)) => {
+                    assert_eq!(echo.timestamp().seconds(), 1234);
+                    assert_eq!(echo.timestamp().frac(), 5678);
+                    assert_eq!(echo.processing_delay(), Duration::from_micros(2500));
+                }
+                _ => panic!("expected rtt echo response"),
+            },
+            _ => panic!("expected application-defined packet"),
+        }
+    }
+
+    #[test]
+    fn build_rist_range_nack() {
+        let requests = [
+            PacketRangeRequest {
+                seq_start: 100,
+                count: 3,
+            },
+            PacketRangeRequest {
+                seq_start: 0xfffe,
+                count: 0,
+            },
+        ];
+        let mut buf = [0u8; 64];
+        let mut builder = RTCPCompoundBuilder::new(&mut buf);
+        builder.rist_range_nack(0x01020304, &requests).unwrap();
+        let packet = RTCPPacketView::try_new(builder.finish()).unwrap();
+        assert_eq!(packet.subtype(), Some(0));
+        match packet.report().unwrap() {
+            RTCPReportView::APP(app) => {
+                assert_eq!(app.ssrc(), 0x01020304);
+                assert_eq!(app.name().unwrap(), "RIST");
+                match app.message().unwrap() {
+                    MessageView::Rist(RistApplicationSpecificMessage::RangeNack(nack)) => {
+                        assert_eq!(nack.requests().collect::<Vec<_>>(), requests);
+                    }
+                    _ => panic!("expected range nack"),
+                }
+            }
+            _ => panic!("expected application-defined packet"),
+        }
+    }
+
+    #[test]
+    fn build_and_parse_bye_and_xr() {
+        let dlrr = [DlrrSubBlock {
+            ssrc: 0x0a0b0c0d,
+            last_rr: 0x12345678,
+            delay_since_last_rr: 0x8000,
+        }];
+        let mut buf = [0u8; 128];
+        let mut builder = RTCPCompoundBuilder::new(&mut buf);
+        builder
+            .receiver_report(0x01020304, &[])
+            .unwrap()
+            .extended_report(
+                0x01020304,
+                &[
+                    ReportBlock::ReceiverReferenceTime(Timestamp::new(1, 2)),
+                    ReportBlock::Dlrr(&dlrr),
+                ],
+            )
+            .unwrap()
+            .bye(&[0x01020304], Some("shutdown"))
+            .unwrap();
+        let packets = RTCPPacketViewIterator::new(builder.finish())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(packets.len(), 3);
+        match packets[1].report().unwrap() {
+            RTCPReportView::XR(xr) => {
+                assert_eq!(xr.sender_ssrc(), 0x01020304);
+                let blocks = xr.blocks().collect::<Result<Vec<_>, _>>().unwrap();
+                assert!(
+                    matches!(blocks[0], ReportBlockView::ReceiverReferenceTime(ts) if ts.seconds() == 1 && ts.frac() == 2)
+                );
+                assert!(matches!(blocks[1], ReportBlockView::Dlrr(d) if d.sub_blocks().eq(dlrr)));
+            }
+            _ => panic!("expected extended report"),
+        }
+        assert_eq!(packets[2].item_count(), Some(1));
+        match packets[2].report().unwrap() {
+            RTCPReportView::BYE(bye) => {
+                assert_eq!(bye.ssrcs().collect::<Vec<_>>(), [0x01020304]);
+                assert_eq!(bye.reason().unwrap().unwrap(), "shutdown");
+            }
+            _ => panic!("expected goodbye"),
+        }
+
+        // the block does not fit into the length field
+        let dlrr = vec![dlrr[0]; 21846];
+        let mut buf = [0u8; 64];
+        let mut builder = RTCPCompoundBuilder::new(&mut buf);
+        assert!(builder
+            .extended_report(0x01020304, &[ReportBlock::Dlrr(&dlrr)])
+            .is_err());
+        assert!(builder.is_empty());
+    }
+
+    #[test]
+    fn parse_xr_with_unknown_block() {
+        // XR with a VoIP metrics block (type 7) followed by a RRTR block
+        let data = [
+            0x80, 0xcf, 0x00, 0x0d, 0x01, 0x02, 0x03, 0x04, // header, sender ssrc
+            0x07, 0x00, 0x00, 0x08, 0x0a, 0x0b, 0x0c, 0x0d, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // voip metrics
+            0x04, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x06, // rrtr
+        ];
+        let packet = RTCPPacketView::try_new(&data).unwrap();
+        match packet.report().unwrap() {
+            RTCPReportView::XR(xr) => {
+                let blocks = xr.blocks().collect::<Result<Vec<_>, _>>().unwrap();
+                assert_eq!(blocks.len(), 1);
+                assert!(
+                    matches!(blocks[0], ReportBlockView::ReceiverReferenceTime(ts) if ts.seconds() == 5)
+                );
+            }
+            _ => panic!("expected extended report"),
+        }
+    }
+
+    fn assert_invalid(data: &[u8], rule: CompoundRule, offset: usize) {
+        match super::validate_compound(data).unwrap_err().kind() {
+            ErrorKind::InvalidCompound { rule: r, offset: o } => {
+                assert_eq!((*r, *o), (rule, offset))
+            }
+            e => panic!("unexpected error {e:?}"),
+        }
+    }
+
+    #[test]
+    fn validate_compound() {
+        let mut buf = [0u8; 128];
+        let mut builder = RTCPCompoundBuilder::new(&mut buf);
+        builder
+            .receiver_report(0x01020304, &[RX_REPORT])
+            .unwrap()
+            .source_description(&[cname(0x01020304, "abc")])
+            .unwrap()
+            .padding(4)
+            .unwrap();
+        let len = builder.len();
+        assert_eq!(super::validate_compound(&buf[..len]).unwrap().count(), 2);
+        let sdes_offset = 8 + 24;
+        // trailing data
+        assert_invalid(&buf[..sdes_offset + 2], CompoundRule::Length, sdes_offset);
+        // the padded packet is no longer the last one
+        assert_invalid(&buf[..len + 4], CompoundRule::Padding, sdes_offset);
+        // truncated
+        assert_invalid(&buf[..len - 4], CompoundRule::Length, sdes_offset);
+        assert_invalid(&[], CompoundRule::Length, 0);
+        // sdes first
+        assert_invalid(&buf[sdes_offset..len], CompoundRule::FirstPacketType, 0);
+        assert_invalid(&RR_WITH_SDES, CompoundRule::FirstPacketType, 0);
+
+        let mut data = buf;
+        data[sdes_offset] = 0x41;
+        assert_invalid(&data[..len], CompoundRule::Version, sdes_offset);
+        // padding on the first packet
+        let mut data = buf;
+        data[0] |= 0x20;
+        assert_invalid(&data[..len], CompoundRule::Padding, 0);
+        // invalid padding length
+        let mut data = buf;
+        data[len - 1] = 0;
+        assert_invalid(&data[..len], CompoundRule::Padding, sdes_offset);
+        data[len - 1] = 17;
+        assert_invalid(&data[..len], CompoundRule::Padding, sdes_offset);
+        // padding on a single report
+        let mut buf = [0u8; 16];
+        let mut builder = RTCPCompoundBuilder::new(&mut buf);
+        builder.receiver_report(0, &[]).unwrap().padding(4).unwrap();
+        let len = builder.len();
+        assert_invalid(&buf[..len], CompoundRule::Padding, 0);
+    }
 }
@@ -1,3 +1,269 @@
+use crate::util;
+
+pub mod error {
+
+    #[derive(Debug, Clone, Copy)]
+    pub enum Error {
+        InvalidPacketLen(usize),
+        UnknownFormat(u8),
+        /// The lost sequence numbers are not in ascending (wrapping) order, carries the first
+        /// sequence number that is out of order
+        NotAscending(u16),
+    }
+}
+
+/// Feedback message type of the generic NACK (RFC4585 6.2.1)
+pub const FMT_GENERIC_NACK: u8 = 1;
+
+/// Length of the SSRC fields in front of the feedback control information
+const SSRC_FIELDS_LEN: usize = 8;
+
+/// Length of a single PID/BLP pair
+const ENTRY_LEN: usize = 4;
+
+/// A single generic NACK entry. Requests retransmission of the packet with the sequence
+/// number `pid` and of the 16 following packets indicated by the bitmask `blp`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenericNack {
+    /// Packet ID of the first lost packet
+    pub pid: u16,
+
+    /// Bitmask of following lost packets. If bit `i` is set, packet `pid + i + 1` is lost as well
+    pub blp: u16,
+}
+
+impl From<[u8; 4]> for GenericNack {
+    fn from(data: [u8; 4]) -> Self {
+        Self {
+            pid: util::read_int!(data, u16, 0),
+            blp: util::read_int!(data, u16, 2),
+        }
+    }
+}
+
+impl From<GenericNack> for [u8; 4] {
+    fn from(nack: GenericNack) -> Self {
+        let [p0, p1] = nack.pid.to_be_bytes();
+        let [b0, b1] = nack.blp.to_be_bytes();
+        [p0, p1, b0, b1]
+    }
+}
+
+impl GenericNack {
+    /// Iterate over all sequence numbers requested by this entry
+    pub fn sequence_numbers(&self) -> impl Iterator<Item = u16> {
+        let GenericNack { pid, blp } = *self;
+        core::iter::once(pid).chain(
+            (0..16u16)
+                .filter(move |bit| blp & (1 << bit) != 0)
+                .map(move |bit| pid.wrapping_add(bit + 1)),
+        )
+    }
+}
+
+/// Compresses a sequence of lost sequence numbers into a list of [GenericNack] entries.
+/// Yields the minimal number of entries if the sequence numbers are given in ascending
+/// (wrapping) order, any other order still yields entries that cover all sequence numbers.
+#[derive(Debug, Clone)]
+pub struct GenericNackCompressor<I> {
+    iter: I,
+    current: Option<GenericNack>,
+}
+
+/// Check that lost sequence numbers are in ascending (wrapping) order, as required to compress
+/// them into the minimal number of entries. Duplicates are allowed
+pub fn check_ascending(lost: &[u16]) -> Result<(), error::Error> {
+    match lost
+        .windows(2)
+        .find(|pair| (pair[1].wrapping_sub(pair[0]) as i16) < 0)
+    {
+        Some(pair) => Err(error::Error::NotAscending(pair[1])),
+        None => Ok(()),
+    }
+}
+
+/// Create a [GenericNackCompressor] from something that yields lost sequence numbers
+pub fn compress<I>(seq: I) -> GenericNackCompressor<I::IntoIter>
+where
+    I: IntoIterator<Item = u16>,
+{
+    GenericNackCompressor {
+        iter: seq.into_iter(),
+        current: None,
+    }
+}
+
+impl<I> Iterator for GenericNackCompressor<I>
+where
+    I: Iterator<Item = u16>,
+{
+    type Item = GenericNack;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match (self.iter.next(), self.current.as_mut()) {
+                (Some(seq), None) => self.current = Some(GenericNack { pid: seq, blp: 0 }),
+                (Some(seq), Some(nack)) => match seq.wrapping_sub(nack.pid) {
+                    0 => {}
+                    d @ 1..=16 => nack.blp |= 1 << (d - 1),
+                    _ => return self.current.replace(GenericNack { pid: seq, blp: 0 }),
+                },
+                (None, _) => return self.current.take(),
+            }
+        }
+    }
+}
+
+/// View over the body of a generic NACK transport layer feedback message (PT 205, FMT 1)
+#[derive(Debug, Clone, Copy)]
 pub struct NACKPacketView<'a> {
     data: &'a [u8],
 }
+
+impl<'a> NACKPacketView<'a> {
+    pub fn try_new<T, U>(fmt: u8, bytes: &'a T) -> Result<Self, error::Error>
+    where
+        T: AsRef<U> + ?Sized,
+        U: ?Sized + 'a,
+        &'a U: Into<&'a [u8]>,
+    {
+        let data: &'a [u8] = bytes.as_ref().into();
+        if fmt != FMT_GENERIC_NACK {
+            Err(error::Error::UnknownFormat(fmt))
+        } else if data.len() < SSRC_FIELDS_LEN || !data.len().is_multiple_of(ENTRY_LEN) {
+            Err(error::Error::InvalidPacketLen(data.len()))
+        } else {
+            Ok(Self { data })
+        }
+    }
+
+    /// SSRC of the sender of this feedback message
+    pub fn sender_ssrc(&self) -> u32 {
+        util::read_int!(self.data, u32, 0)
+    }
+
+    /// SSRC of the media source the lost packets belong to
+    pub fn media_ssrc(&self) -> u32 {
+        util::read_int!(self.data, u32, 4)
+    }
+
+    /// Iterate over the PID/BLP entries
+    pub fn entries(&self) -> impl Iterator<Item = GenericNack> + 'a {
+        self.data[SSRC_FIELDS_LEN..]
+            .chunks_exact(ENTRY_LEN)
+            .map(|slice| {
+                let data: [u8; 4] = slice
+                    .try_into()
+                    .expect(rist_rs_types::internal::INTERNAL_ERR_PRE_VALIDATED);
+                GenericNack::from(data)
+            })
+    }
+
+    /// Iterate over all lost sequence numbers expanded from the PID/BLP entries
+    pub fn lost_sequence_numbers(&self) -> impl Iterator<Item = u16> + 'a {
+        self.entries().flat_map(|nack| nack.sequence_numbers())
+    }
+}
+
+#[allow(unused)]
+mod test {
+    use super::*;
+
+    const NACK: [u8; 16] = [
+        0x01, 0x02, 0x03, 0x04, 0x0a, 0x0b, 0x0c, 0x0d, 0x00, 0x10, 0x80, 0x01, 0xff, 0xff, 0x00,
+        0x02,
+    ];
+
+    #[test]
+    fn parse() {
+        let nack = NACKPacketView::try_new(FMT_GENERIC_NACK, NACK.as_slice()).unwrap();
+        assert_eq!(nack.sender_ssrc(), 0x01020304);
+        assert_eq!(nack.media_ssrc(), 0x0a0b0c0d);
+        assert_eq!(
+            nack.entries().collect::<Vec<_>>(),
+            [
+                GenericNack {
+                    pid: 0x10,
+                    blp: 0x8001
+                },
+                GenericNack {
+                    pid: 0xffff,
+                    blp: 0x2
+                }
+            ]
+        );
+        assert_eq!(
+            nack.lost_sequence_numbers().collect::<Vec<_>>(),
+            [0x10, 0x11, 0x20, 0xffff, 0x1]
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(NACKPacketView::try_new(2, NACK.as_slice()).is_err());
+        assert!(NACKPacketView::try_new(FMT_GENERIC_NACK, &NACK[..7]).is_err());
+        assert!(NACKPacketView::try_new(FMT_GENERIC_NACK, &NACK[..10]).is_err());
+    }
+
+    #[test]
+    fn compress_sequence_numbers() {
+        let lost = [0x10, 0x11, 0x20, 0x21, 0x22, 0x40];
+        let entries = compress(lost).collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [
+                GenericNack {
+                    pid: 0x10,
+                    blp: 0x8001
+                },
+                GenericNack {
+                    pid: 0x21,
+                    blp: 0x1
+                },
+                GenericNack { pid: 0x40, blp: 0 }
+            ]
+        );
+        let expanded = entries
+            .iter()
+            .flat_map(|e| e.sequence_numbers())
+            .collect::<Vec<_>>();
+        assert_eq!(expanded, lost);
+    }
+
+    #[test]
+    fn compress_wrapping() {
+        let lost = [0xfffe, 0xffff, 0x0, 0xe];
+        let entries = compress(lost).collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [GenericNack {
+                pid: 0xfffe,
+                blp: 0x8003
+            }]
+        );
+        assert_eq!(entries[0].sequence_numbers().collect::<Vec<_>>(), lost);
+    }
+
+    #[test]
+    fn ascending() {
+        assert!(check_ascending(&[]).is_ok());
+        assert!(check_ascending(&[0xfffe, 0xffff, 0xffff, 0x0, 0x7ffe]).is_ok());
+        assert!(matches!(
+            check_ascending(&[0x10, 0x20, 0x11]),
+            Err(error::Error::NotAscending(0x11))
+        ));
+        assert!(matches!(
+            check_ascending(&[0x0, 0x8000]),
+            Err(error::Error::NotAscending(0x8000))
+        ));
+    }
+
+    #[test]
+    fn compress_duplicates_and_empty() {
+        assert_eq!(compress([]).count(), 0);
+        assert_eq!(
+            compress([5, 5, 6, 6]).collect::<Vec<_>>(),
+            [GenericNack { pid: 5, blp: 0x1 }]
+        );
+    }
+}
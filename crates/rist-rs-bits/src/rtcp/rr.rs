@@ -34,7 +34,7 @@ impl<'a> ReceiverReportMessageView<'a> {
         let data: &'a [u8] = bytes.as_ref().into();
         if data.len() < 4 {
             Err(error::Error::EndOfPacketReached)
-        } else if (data.len() - 4) % 24 != 0 {
+        } else if !(data.len() - 4).is_multiple_of(24) {
             Err(error::Error::InvalidPacketLen(data.len()))
         } else {
             Ok(ReceiverReportMessageView { data })
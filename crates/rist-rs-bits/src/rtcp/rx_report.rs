@@ -1,14 +1,187 @@
+/// Length of a reception report block in bytes
+pub const RX_REPORT_LEN: usize = 24;
+
+/// Largest value of the signed 24-bit cumulative number of packets lost
+const CUMULATIVE_LOST_MAX: i32 = 0x7fffff;
+
+/// Smallest value of the signed 24-bit cumulative number of packets lost
+const CUMULATIVE_LOST_MIN: i32 = -0x800000;
+
 #[derive(Debug, Clone, Copy)]
 pub struct ReceptionReportView<'a> {
-    data: &'a [u8; 24],
+    data: &'a [u8; RX_REPORT_LEN],
 }
 
-impl<'a> From<&'a [u8; 24]> for ReceptionReportView<'a> {
-    fn from(data: &'a [u8; 24]) -> Self {
+impl<'a> From<&'a [u8; RX_REPORT_LEN]> for ReceptionReportView<'a> {
+    fn from(data: &'a [u8; RX_REPORT_LEN]) -> Self {
         Self { data }
     }
 }
 
 impl<'a> ReceptionReportView<'a> {
-    // TODO: Implement accessors!
+    const SSRC_OFFSET: usize = 0;
+    const FRACTION_LOST_OFFSET: usize = Self::SSRC_OFFSET + 4;
+    const CUMULATIVE_LOST_OFFSET: usize = Self::FRACTION_LOST_OFFSET + 1;
+    const EXT_HIGHEST_SEQ_OFFSET: usize = Self::CUMULATIVE_LOST_OFFSET + 3;
+    const JITTER_OFFSET: usize = Self::EXT_HIGHEST_SEQ_OFFSET + 4;
+    const LSR_OFFSET: usize = Self::JITTER_OFFSET + 4;
+    const DLSR_OFFSET: usize = Self::LSR_OFFSET + 4;
+
+    /// SSRC of the source this report is about
+    pub fn ssrc(&self) -> u32 {
+        crate::util::read_int!(self.data, u32, Self::SSRC_OFFSET)
+    }
+
+    /// Fraction of packets lost since the previous report as a fixed point number
+    /// with the binary point at the left edge of the field
+    pub fn fraction_lost(&self) -> u8 {
+        self.data[Self::FRACTION_LOST_OFFSET]
+    }
+
+    /// Total number of packets lost since the beginning of reception. May be negative
+    /// if duplicate packets were received
+    pub fn cumulative_lost(&self) -> i32 {
+        // place the 24 bit value in the upper bytes and shift back to sign-extend it
+        i32::from_be_bytes([
+            self.data[Self::CUMULATIVE_LOST_OFFSET],
+            self.data[Self::CUMULATIVE_LOST_OFFSET + 1],
+            self.data[Self::CUMULATIVE_LOST_OFFSET + 2],
+            0,
+        ]) >> 8
+    }
+
+    /// Highest sequence number received, extended with the count of sequence number cycles
+    pub fn extended_highest_sequence_number(&self) -> u32 {
+        crate::util::read_int!(self.data, u32, Self::EXT_HIGHEST_SEQ_OFFSET)
+    }
+
+    /// Interarrival jitter in RTP timestamp units
+    pub fn jitter(&self) -> u32 {
+        crate::util::read_int!(self.data, u32, Self::JITTER_OFFSET)
+    }
+
+    /// Middle 32 bits of the NTP timestamp of the last sender report received from the source
+    pub fn last_sr(&self) -> u32 {
+        crate::util::read_int!(self.data, u32, Self::LSR_OFFSET)
+    }
+
+    /// Delay since the last sender report was received in units of 1/65536 seconds
+    pub fn delay_since_last_sr(&self) -> u32 {
+        crate::util::read_int!(self.data, u32, Self::DLSR_OFFSET)
+    }
+}
+
+/// Owned reception report block that can be written into sender and receiver reports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReceptionReport {
+    /// SSRC of the source this report is about
+    pub ssrc: u32,
+
+    /// Fraction of packets lost since the previous report (fixed point, 8 fractional bits)
+    pub fraction_lost: u8,
+
+    /// Total number of packets lost. Saturates at the bounds of a signed 24-bit integer when written
+    pub cumulative_lost: i32,
+
+    /// Extended highest sequence number received
+    pub extended_highest_sequence_number: u32,
+
+    /// Interarrival jitter in RTP timestamp units
+    pub jitter: u32,
+
+    /// Middle 32 bits of the NTP timestamp of the last sender report received
+    pub last_sr: u32,
+
+    /// Delay since the last sender report was received in units of 1/65536 seconds
+    pub delay_since_last_sr: u32,
+}
+
+impl ReceptionReport {
+    /// Write the report block into `buf`
+    pub fn write(&self, buf: &mut [u8; RX_REPORT_LEN]) {
+        let lost = self
+            .cumulative_lost
+            .clamp(CUMULATIVE_LOST_MIN, CUMULATIVE_LOST_MAX)
+            .to_be_bytes();
+        buf[0..4].copy_from_slice(&self.ssrc.to_be_bytes());
+        buf[4] = self.fraction_lost;
+        buf[5..8].copy_from_slice(&lost[1..]);
+        buf[8..12].copy_from_slice(&self.extended_highest_sequence_number.to_be_bytes());
+        buf[12..16].copy_from_slice(&self.jitter.to_be_bytes());
+        buf[16..20].copy_from_slice(&self.last_sr.to_be_bytes());
+        buf[20..24].copy_from_slice(&self.delay_since_last_sr.to_be_bytes());
+    }
+}
+
+impl<'a> From<ReceptionReportView<'a>> for ReceptionReport {
+    fn from(view: ReceptionReportView<'a>) -> Self {
+        Self {
+            ssrc: view.ssrc(),
+            fraction_lost: view.fraction_lost(),
+            cumulative_lost: view.cumulative_lost(),
+            extended_highest_sequence_number: view.extended_highest_sequence_number(),
+            jitter: view.jitter(),
+            last_sr: view.last_sr(),
+            delay_since_last_sr: view.delay_since_last_sr(),
+        }
+    }
+}
+
+#[allow(unused)]
+mod test {
+    use super::*;
+
+    const RX_REPORT: [u8; RX_REPORT_LEN] = [
+        0x58, 0xf3, 0x3d, 0xea, 0x19, 0x00, 0x01, 0x02, 0x00, 0x01, 0x2c, 0xd8, 0x00, 0x00, 0x07,
+        0x60, 0x86, 0xd9, 0xf5, 0x81, 0x00, 0x00, 0x00, 0x01,
+    ];
+
+    #[test]
+    fn read() {
+        let report = ReceptionReportView::from(&RX_REPORT);
+        assert_eq!(report.ssrc(), 0x58f33dea);
+        assert_eq!(report.fraction_lost(), 0x19);
+        assert_eq!(report.cumulative_lost(), 0x102);
+        assert_eq!(report.extended_highest_sequence_number(), 0x12cd8);
+        assert_eq!(report.jitter(), 0x760);
+        assert_eq!(report.last_sr(), 0x86d9f581);
+        assert_eq!(report.delay_since_last_sr(), 1);
+    }
+
+    #[test]
+    fn negative_cumulative_lost() {
+        let mut data = RX_REPORT;
+        data[5..8].copy_from_slice(&[0xff, 0xff, 0xfe]);
+        assert_eq!(ReceptionReportView::from(&data).cumulative_lost(), -2);
+        data[5..8].copy_from_slice(&[0x80, 0x00, 0x00]);
+        assert_eq!(
+            ReceptionReportView::from(&data).cumulative_lost(),
+            -0x800000
+        );
+    }
+
+    #[test]
+    fn write() {
+        let report = ReceptionReport::from(ReceptionReportView::from(&RX_REPORT));
+        let mut buf = [0u8; RX_REPORT_LEN];
+        report.write(&mut buf);
+        assert_eq!(buf, RX_REPORT);
+    }
+
+    #[test]
+    fn write_saturating() {
+        let mut buf = [0u8; RX_REPORT_LEN];
+        for (lost, expected) in [
+            (-1, -1),
+            (i32::MIN, CUMULATIVE_LOST_MIN),
+            (i32::MAX, CUMULATIVE_LOST_MAX),
+        ] {
+            ReceptionReport {
+                cumulative_lost: lost,
+                ..Default::default()
+            }
+            .write(&mut buf);
+            assert_eq!(ReceptionReportView::from(&buf).cumulative_lost(), expected);
+        }
+    }
 }
@@ -14,6 +14,8 @@ pub mod error {
         InvalidPadding,
         /// Unknown item type
         UnknownType,
+        /// Item text is longer than 255 bytes and can not be written
+        ItemTooLong(usize),
     }
 
     /// Implemented to short-circuit convert UTF8 errors to the parser error type
@@ -49,6 +51,36 @@ pub enum SourceDescriptionItemPayload<'a> {
     PRIV(&'a str),
 }
 
+impl<'a> SourceDescriptionItemPayload<'a> {
+    /// Value of the item type field for this payload
+    pub fn item_type(&self) -> u8 {
+        match self {
+            SourceDescriptionItemPayload::CNAME(_) => 1,
+            SourceDescriptionItemPayload::NAME(_) => 2,
+            SourceDescriptionItemPayload::EMAIL(_) => 3,
+            SourceDescriptionItemPayload::PHONE(_) => 4,
+            SourceDescriptionItemPayload::LOC(_) => 5,
+            SourceDescriptionItemPayload::TOOL(_) => 6,
+            SourceDescriptionItemPayload::NOTE(_) => 7,
+            SourceDescriptionItemPayload::PRIV(_) => 8,
+        }
+    }
+
+    /// The text carried by the item
+    pub fn value(&self) -> &'a str {
+        match self {
+            SourceDescriptionItemPayload::CNAME(s)
+            | SourceDescriptionItemPayload::NAME(s)
+            | SourceDescriptionItemPayload::EMAIL(s)
+            | SourceDescriptionItemPayload::PHONE(s)
+            | SourceDescriptionItemPayload::LOC(s)
+            | SourceDescriptionItemPayload::TOOL(s)
+            | SourceDescriptionItemPayload::NOTE(s)
+            | SourceDescriptionItemPayload::PRIV(s) => s,
+        }
+    }
+}
+
 impl<'a> SourceDescriptionItem<'a> {
     pub fn try_new<T, U>(ssrc: u32, desc_type: u8, bytes: &'a T) -> Result<Self, error::Error>
     where
@@ -70,6 +102,29 @@ impl<'a> SourceDescriptionItem<'a> {
         }
         .map(|payload| SourceDescriptionItem { ssrc, payload })
     }
+
+    /// Number of bytes this item occupies in an SDES packet including the terminating
+    /// null octets and padding to the next 32-bit boundary
+    pub fn chunk_len(&self) -> usize {
+        let item_len = self.payload.value().len();
+        SSRC_LEN + ITEM_HEADER_LEN + item_len + chunk_padding(item_len)
+    }
+
+    /// Write the item as a chunk into `buf` which must be exactly [SourceDescriptionItem::chunk_len]
+    /// bytes long. Returns an error if the item text is too long to be represented in the length field
+    pub fn write(&self, buf: &mut [u8]) -> Result<(), error::Error> {
+        let value = self.payload.value().as_bytes();
+        if value.len() > u8::MAX as usize {
+            return Err(error::Error::ItemTooLong(value.len()));
+        }
+        debug_assert_eq!(buf.len(), self.chunk_len());
+        buf[..SSRC_LEN].copy_from_slice(&self.ssrc.to_be_bytes());
+        buf[ITEM_TYPE_OFFSET] = self.payload.item_type();
+        buf[ITEM_LEN_OFFSET] = value.len() as u8;
+        buf[ITEM_OFFSET..ITEM_OFFSET + value.len()].copy_from_slice(value);
+        buf[ITEM_OFFSET + value.len()..].fill(0);
+        Ok(())
+    }
 }
 
 impl<'a> TryFrom<(u32, u8, &'a [u8])> for SourceDescriptionItem<'a> {
@@ -98,6 +153,15 @@ const ITEM_TYPE_OFFSET: usize = SSRC_LEN;
 /// offset of the item payload
 const ITEM_OFFSET: usize = SSRC_LEN + ITEM_HEADER_LEN;
 
+/// Number of null octets that terminate an item of the given length and pad the chunk
+/// to the next 32-bit boundary. At least one null octet is always required.
+fn chunk_padding(item_len: usize) -> usize {
+    match (item_len + ITEM_HEADER_LEN) % 4 {
+        0 => 4,
+        p => 4 - p,
+    }
+}
+
 /// Iterate over a list of SDES item chunks in an rtcp packet.
 /// Parses the Items on-the-fly and stops emitting elements as soon as the end of data
 /// is reached or an error occurs
@@ -160,10 +224,7 @@ impl<'a> SourceDescriptionMessageIterator<'a> {
             let item_len = self.data[self.pos + ITEM_LEN_OFFSET] as usize;
 
             // expect to be padded to 32bit boundary
-            let padding = match (item_len + ITEM_HEADER_LEN) % 4 {
-                0 => 4,
-                p => 4 - p,
-            };
+            let padding = chunk_padding(item_len);
 
             // index of first and last item bytes
             let start_of_item = self.pos + ITEM_OFFSET;
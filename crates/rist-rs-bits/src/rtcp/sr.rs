@@ -1,3 +1,5 @@
+use crate::util;
+
 pub mod error {
 
     #[derive(Debug, Clone, Copy)]
@@ -13,6 +15,40 @@ pub struct SenderReportMessageView<'a> {
 
 const MIN_PACKET_LEN: usize = 24;
 
+/// Sender information block at the start of every sender report
+#[derive(Debug, Clone, Copy)]
+pub struct SenderInfo {
+    /// SSRC of the sender
+    pub ssrc: u32,
+
+    /// Wallclock time at which this report was sent
+    pub ntp_timestamp: rist_rs_types::time::ntp::Timestamp,
+
+    /// RTP timestamp corresponding to the NTP timestamp
+    pub rtp_timestamp: u32,
+
+    /// Number of RTP packets sent
+    pub packet_count: u32,
+
+    /// Number of payload octets sent
+    pub octet_count: u32,
+}
+
+impl SenderInfo {
+    /// Length of the sender information block in bytes
+    pub const LEN: usize = MIN_PACKET_LEN;
+
+    /// Write the sender information block into `buf`
+    pub fn write(&self, buf: &mut [u8; Self::LEN]) {
+        buf[0..4].copy_from_slice(&self.ssrc.to_be_bytes());
+        buf[4..8].copy_from_slice(&self.ntp_timestamp.seconds().to_be_bytes());
+        buf[8..12].copy_from_slice(&self.ntp_timestamp.frac().to_be_bytes());
+        buf[12..16].copy_from_slice(&self.rtp_timestamp.to_be_bytes());
+        buf[16..20].copy_from_slice(&self.packet_count.to_be_bytes());
+        buf[20..24].copy_from_slice(&self.octet_count.to_be_bytes());
+    }
+}
+
 impl<'a> TryFrom<&'a [u8]> for SenderReportMessageView<'a> {
     type Error = error::Error;
 
@@ -36,13 +72,17 @@ impl<'a> SenderReportMessageView<'a> {
         &'a U: Into<&'a [u8]>,
     {
         let data: &'a [u8] = bytes.as_ref().into();
-        if data.len() < MIN_PACKET_LEN || (data.len() % 24) != 0 {
+        if data.len() < MIN_PACKET_LEN || !data.len().is_multiple_of(24) {
             Err(error::Error::InvalidPacketLen(data.len()))
         } else {
             Ok(SenderReportMessageView { data })
         }
     }
 
+    pub fn sender_ssrc(&self) -> u32 {
+        util::read_int!(self.data, u32, 0)
+    }
+
     pub fn ntp_timestamp(&self) -> rist_rs_types::time::ntp::Timestamp {
         rist_rs_types::time::ntp::Timestamp::new(
             u32::from_be_bytes([
@@ -0,0 +1,510 @@
+use rist_rs_types::time::ntp::Timestamp;
+
+use crate::util;
+
+pub mod error {
+
+    #[derive(Debug, Clone, Copy)]
+    pub enum Error {
+        /// The packet is too short to hold the SSRC of the sender or not aligned to 32 bits
+        InvalidPacketLen(usize),
+        /// A report block exceeds the packet
+        EndOfPacketReached,
+        /// The length of a report block is invalid for its block type
+        InvalidBlockLen { block_type: u8, len: usize },
+    }
+}
+
+/// Block type of receiver reference time report blocks (RFC3611 4.4)
+pub const BT_RECEIVER_REFERENCE_TIME: u8 = 4;
+
+/// Block type of DLRR report blocks (RFC3611 4.5)
+pub const BT_DLRR: u8 = 5;
+
+/// Block type of statistics summary report blocks (RFC3611 4.6)
+pub const BT_STATISTICS_SUMMARY: u8 = 6;
+
+/// Length of the SSRC of the sender in front of the report blocks
+const SSRC_LEN: usize = 4;
+
+/// Length of the header of every report block
+const BLOCK_HEADER_LEN: usize = 4;
+
+/// Length of the contents of a receiver reference time report block
+const RRTR_LEN: usize = 8;
+
+/// Length of a DLRR sub-block
+const DLRR_SUB_BLOCK_LEN: usize = 12;
+
+/// Length of the contents of a statistics summary report block
+const STATISTICS_SUMMARY_LEN: usize = 36;
+
+/// A single DLRR sub-block, answering the receiver reference time report of one receiver
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DlrrSubBlock {
+    /// SSRC of the receiver that sent the receiver reference time report
+    pub ssrc: u32,
+
+    /// Middle 32 bits of the NTP timestamp of the last receiver reference time report
+    pub last_rr: u32,
+
+    /// Delay since the last receiver reference time report was received in units of
+    /// 1/65536 seconds
+    pub delay_since_last_rr: u32,
+}
+
+impl From<[u8; DLRR_SUB_BLOCK_LEN]> for DlrrSubBlock {
+    fn from(data: [u8; DLRR_SUB_BLOCK_LEN]) -> Self {
+        Self {
+            ssrc: util::read_int!(data, u32, 0),
+            last_rr: util::read_int!(data, u32, 4),
+            delay_since_last_rr: util::read_int!(data, u32, 8),
+        }
+    }
+}
+
+impl From<DlrrSubBlock> for [u8; DLRR_SUB_BLOCK_LEN] {
+    fn from(block: DlrrSubBlock) -> Self {
+        let mut data = [0u8; DLRR_SUB_BLOCK_LEN];
+        data[0..4].copy_from_slice(&block.ssrc.to_be_bytes());
+        data[4..8].copy_from_slice(&block.last_rr.to_be_bytes());
+        data[8..12].copy_from_slice(&block.delay_since_last_rr.to_be_bytes());
+        data
+    }
+}
+
+/// Kind of values reported in the TTL or hop limit fields of a statistics summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TtlKind {
+    /// IPv4 TTL values
+    Ttl,
+    /// IPv6 hop limit values
+    HopLimit,
+}
+
+/// Minimum, maximum, mean and standard deviation of a reported value
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Summary<T> {
+    pub min: T,
+    pub max: T,
+    pub mean: T,
+    pub dev: T,
+}
+
+/// Statistics about the packets received from a source in a range of sequence numbers.
+/// Fields that are `None` are flagged as not reported
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatisticsSummary {
+    /// SSRC of the source the statistics are about
+    pub ssrc: u32,
+
+    /// First sequence number of the reported range
+    pub begin_seq: u16,
+
+    /// Last sequence number of the reported range plus one
+    pub end_seq: u16,
+
+    /// Number of packets lost in the range
+    pub lost_packets: Option<u32>,
+
+    /// Number of duplicate packets in the range
+    pub dup_packets: Option<u32>,
+
+    /// Interarrival jitter in RTP timestamp units
+    pub jitter: Option<Summary<u32>>,
+
+    /// TTL or hop limit values of the received packets
+    pub ttl: Option<(TtlKind, Summary<u8>)>,
+}
+
+impl StatisticsSummary {
+    const FLAG_LOSS: u8 = 0x80;
+    const FLAG_DUP: u8 = 0x40;
+    const FLAG_JITTER: u8 = 0x20;
+    const TOH_TTL: u8 = 0;
+    const TOH_HOP_LIMIT: u8 = 1;
+    const TOH_NONE: u8 = 2;
+
+    fn read(flags: u8, data: &[u8]) -> Self {
+        let jitter = Summary {
+            min: util::read_int!(data, u32, 16),
+            max: util::read_int!(data, u32, 20),
+            mean: util::read_int!(data, u32, 24),
+            dev: util::read_int!(data, u32, 28),
+        };
+        let ttl = Summary {
+            min: data[32],
+            max: data[33],
+            mean: data[34],
+            dev: data[35],
+        };
+        Self {
+            ssrc: util::read_int!(data, u32, 0),
+            begin_seq: util::read_int!(data, u16, 4),
+            end_seq: util::read_int!(data, u16, 6),
+            lost_packets: (flags & Self::FLAG_LOSS != 0).then(|| util::read_int!(data, u32, 8)),
+            dup_packets: (flags & Self::FLAG_DUP != 0).then(|| util::read_int!(data, u32, 12)),
+            jitter: (flags & Self::FLAG_JITTER != 0).then_some(jitter),
+            ttl: match (flags >> 3) & 0x3 {
+                Self::TOH_TTL => Some((TtlKind::Ttl, ttl)),
+                Self::TOH_HOP_LIMIT => Some((TtlKind::HopLimit, ttl)),
+                _ => None,
+            },
+        }
+    }
+
+    /// Value of the type-specific field of the block header
+    fn flags(&self) -> u8 {
+        let toh = match self.ttl {
+            Some((TtlKind::Ttl, _)) => Self::TOH_TTL,
+            Some((TtlKind::HopLimit, _)) => Self::TOH_HOP_LIMIT,
+            None => Self::TOH_NONE,
+        };
+        [
+            (self.lost_packets.is_some(), Self::FLAG_LOSS),
+            (self.dup_packets.is_some(), Self::FLAG_DUP),
+            (self.jitter.is_some(), Self::FLAG_JITTER),
+        ]
+        .into_iter()
+        .filter(|(set, _)| *set)
+        .fold(toh << 3, |flags, (_, flag)| flags | flag)
+    }
+
+    fn write(&self, buf: &mut [u8]) {
+        let jitter = self.jitter.unwrap_or_default();
+        let (_, ttl) = self.ttl.unwrap_or((TtlKind::Ttl, Summary::default()));
+        buf[0..4].copy_from_slice(&self.ssrc.to_be_bytes());
+        buf[4..6].copy_from_slice(&self.begin_seq.to_be_bytes());
+        buf[6..8].copy_from_slice(&self.end_seq.to_be_bytes());
+        buf[8..12].copy_from_slice(&self.lost_packets.unwrap_or(0).to_be_bytes());
+        buf[12..16].copy_from_slice(&self.dup_packets.unwrap_or(0).to_be_bytes());
+        for (chunk, value) in
+            buf[16..32]
+                .chunks_exact_mut(4)
+                .zip([jitter.min, jitter.max, jitter.mean, jitter.dev])
+        {
+            chunk.copy_from_slice(&value.to_be_bytes());
+        }
+        buf[32..36].copy_from_slice(&[ttl.min, ttl.max, ttl.mean, ttl.dev]);
+    }
+}
+
+/// View over the sub-blocks of a DLRR report block
+#[derive(Debug, Clone, Copy)]
+pub struct DlrrBlockView<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> DlrrBlockView<'a> {
+    /// Iterate over the sub-blocks
+    pub fn sub_blocks(&self) -> impl Iterator<Item = DlrrSubBlock> + 'a {
+        self.data.chunks_exact(DLRR_SUB_BLOCK_LEN).map(|slice| {
+            DlrrSubBlock::from(
+                <[u8; DLRR_SUB_BLOCK_LEN]>::try_from(slice)
+                    .expect(rist_rs_types::internal::INTERNAL_ERR_PRE_VALIDATED),
+            )
+        })
+    }
+}
+
+/// A known report block of an extended report
+#[derive(Debug, Clone, Copy)]
+pub enum ReportBlockView<'a> {
+    /// Receiver reference time, allows non-senders to take part in RTT measurement
+    ReceiverReferenceTime(Timestamp),
+    /// Delay since the last receiver reference time reports
+    Dlrr(DlrrBlockView<'a>),
+    /// Statistics summary
+    StatisticsSummary(StatisticsSummary),
+}
+
+/// A report block to be written into an extended report
+#[derive(Debug, Clone, Copy)]
+pub enum ReportBlock<'a> {
+    /// Receiver reference time, the NTP time at which the report is sent
+    ReceiverReferenceTime(Timestamp),
+    /// Answers to receiver reference time reports
+    Dlrr(&'a [DlrrSubBlock]),
+    /// Statistics summary
+    StatisticsSummary(StatisticsSummary),
+}
+
+impl<'a> ReportBlock<'a> {
+    /// Number of bytes this block occupies including the block header
+    pub fn len(&self) -> usize {
+        BLOCK_HEADER_LEN
+            + match self {
+                ReportBlock::ReceiverReferenceTime(_) => RRTR_LEN,
+                ReportBlock::Dlrr(blocks) => blocks.len() * DLRR_SUB_BLOCK_LEN,
+                ReportBlock::StatisticsSummary(_) => STATISTICS_SUMMARY_LEN,
+            }
+    }
+
+    /// Never true, every block has at least a header
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Write the block into `buf` which must be exactly [ReportBlock::len] bytes long.
+    /// Returns an error if the block is too long to be represented in the length field
+    pub fn write(&self, buf: &mut [u8]) -> Result<(), error::Error> {
+        let (block_type, flags) = match self {
+            ReportBlock::ReceiverReferenceTime(_) => (BT_RECEIVER_REFERENCE_TIME, 0),
+            ReportBlock::Dlrr(_) => (BT_DLRR, 0),
+            ReportBlock::StatisticsSummary(summary) => (BT_STATISTICS_SUMMARY, summary.flags()),
+        };
+        let words = self.len() / 4 - 1;
+        if words > u16::MAX as usize {
+            return Err(error::Error::InvalidBlockLen {
+                block_type,
+                len: self.len(),
+            });
+        }
+        debug_assert_eq!(buf.len(), self.len());
+        buf[0] = block_type;
+        buf[1] = flags;
+        buf[2..4].copy_from_slice(&(words as u16).to_be_bytes());
+        let body = &mut buf[BLOCK_HEADER_LEN..];
+        match self {
+            ReportBlock::ReceiverReferenceTime(ts) => {
+                body[0..4].copy_from_slice(&ts.seconds().to_be_bytes());
+                body[4..8].copy_from_slice(&ts.frac().to_be_bytes());
+            }
+            ReportBlock::Dlrr(blocks) => {
+                for (chunk, block) in body.chunks_exact_mut(DLRR_SUB_BLOCK_LEN).zip(*blocks) {
+                    chunk.copy_from_slice(&<[u8; DLRR_SUB_BLOCK_LEN]>::from(*block));
+                }
+            }
+            ReportBlock::StatisticsSummary(summary) => summary.write(body),
+        }
+        Ok(())
+    }
+}
+
+/// View over the body of an extended report (XR) packet (RFC3611)
+#[derive(Debug, Clone, Copy)]
+pub struct ExtendedReportView<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for ExtendedReportView<'a> {
+    type Error = error::Error;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        ExtendedReportView::try_new(data)
+    }
+}
+
+impl<'a> ExtendedReportView<'a> {
+    pub fn try_new<T, U>(bytes: &'a T) -> Result<Self, error::Error>
+    where
+        T: AsRef<U> + ?Sized,
+        U: ?Sized + 'a,
+        &'a U: Into<&'a [u8]>,
+    {
+        let data: &'a [u8] = bytes.as_ref().into();
+        if data.len() < SSRC_LEN || !data.len().is_multiple_of(4) {
+            Err(error::Error::InvalidPacketLen(data.len()))
+        } else {
+            Ok(Self { data })
+        }
+    }
+
+    /// SSRC of the sender of this report
+    pub fn sender_ssrc(&self) -> u32 {
+        util::read_int!(self.data, u32, 0)
+    }
+
+    /// Iterate over the report blocks. Blocks of unknown type are skipped, the iterator
+    /// stops after the first error
+    pub fn blocks(&self) -> ReportBlockIterator<'a> {
+        ReportBlockIterator {
+            data: &self.data[SSRC_LEN..],
+        }
+    }
+}
+
+/// Iterator over the known report blocks of an extended report
+#[derive(Debug, Clone)]
+pub struct ReportBlockIterator<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ReportBlockIterator<'a> {
+    /// Parse the next block with its header. Returns `None` for blocks of unknown type
+    fn parse(
+        block_type: u8,
+        flags: u8,
+        body: &'a [u8],
+    ) -> Option<Result<ReportBlockView<'a>, error::Error>> {
+        let invalid = || error::Error::InvalidBlockLen {
+            block_type,
+            len: body.len(),
+        };
+        match block_type {
+            BT_RECEIVER_REFERENCE_TIME => Some(if body.len() == RRTR_LEN {
+                Ok(ReportBlockView::ReceiverReferenceTime(Timestamp::new(
+                    util::read_int!(body, u32, 0),
+                    util::read_int!(body, u32, 4),
+                )))
+            } else {
+                Err(invalid())
+            }),
+            BT_DLRR => Some(if body.len().is_multiple_of(DLRR_SUB_BLOCK_LEN) {
+                Ok(ReportBlockView::Dlrr(DlrrBlockView { data: body }))
+            } else {
+                Err(invalid())
+            }),
+            BT_STATISTICS_SUMMARY => Some(if body.len() == STATISTICS_SUMMARY_LEN {
+                Ok(ReportBlockView::StatisticsSummary(StatisticsSummary::read(
+                    flags, body,
+                )))
+            } else {
+                Err(invalid())
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Iterator for ReportBlockIterator<'a> {
+    type Item = Result<ReportBlockView<'a>, error::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.data.is_empty() {
+            if self.data.len() < BLOCK_HEADER_LEN {
+                self.data = &[];
+                return Some(Err(error::Error::EndOfPacketReached));
+            }
+            let len = (util::read_int!(self.data, u16, 2) as usize + 1) * 4;
+            if self.data.len() < len {
+                self.data = &[];
+                return Some(Err(error::Error::EndOfPacketReached));
+            }
+            let (block, rest) = self.data.split_at(len);
+            self.data = rest;
+            if let Some(result) = Self::parse(block[0], block[1], &block[BLOCK_HEADER_LEN..]) {
+                if result.is_err() {
+                    self.data = &[];
+                }
+                return Some(result);
+            }
+        }
+        None
+    }
+}
+
+#[allow(unused)]
+mod test {
+    use super::*;
+
+    /// XR with a RRTR block, an unknown block (type 42) and a DLRR block with one sub-block
+    const XR: [u8; 40] = [
+        0x01, 0x02, 0x03, 0x04, // sender ssrc
+        0x04, 0x00, 0x00, 0x02, 0x83, 0xb1, 0xe7, 0x69, 0x80, 0x27, 0xfa, 0x1a, // rrtr
+        0x2a, 0x00, 0x00, 0x01, 0xff, 0xff, 0xff, 0xff, // unknown
+        0x05, 0x00, 0x00, 0x03, 0x0a, 0x0b, 0x0c, 0x0d, 0xe7, 0x69, 0x80, 0x27, 0x00, 0x01, 0x00,
+        0x00, // dlrr
+    ];
+
+    #[test]
+    fn read() {
+        let xr = ExtendedReportView::try_new(&XR).unwrap();
+        assert_eq!(xr.sender_ssrc(), 0x01020304);
+        let blocks = xr.blocks().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(blocks.len(), 2);
+        match blocks[0] {
+            ReportBlockView::ReceiverReferenceTime(ts) => {
+                assert_eq!(ts.seconds(), 0x83b1e769);
+                assert_eq!(ts.frac(), 0x8027fa1a);
+            }
+            _ => panic!("expected receiver reference time"),
+        }
+        match blocks[1] {
+            ReportBlockView::Dlrr(dlrr) => assert_eq!(
+                dlrr.sub_blocks().collect::<Vec<_>>(),
+                [DlrrSubBlock {
+                    ssrc: 0x0a0b0c0d,
+                    last_rr: 0xe7698027,
+                    delay_since_last_rr: 0x10000,
+                }]
+            ),
+            _ => panic!("expected dlrr"),
+        }
+    }
+
+    #[test]
+    fn read_invalid() {
+        assert!(ExtendedReportView::try_new(&XR[..3]).is_err());
+        assert!(ExtendedReportView::try_new(&XR[..6]).is_err());
+        // truncated dlrr block
+        let xr = ExtendedReportView::try_new(&XR[..36]).unwrap();
+        let mut blocks = xr.blocks();
+        assert!(blocks.next().unwrap().is_ok());
+        assert!(blocks.next().unwrap().is_err());
+        assert!(blocks.next().is_none());
+        // rrtr block with wrong length
+        let mut data = XR;
+        data[7] = 1;
+        data[12..16].copy_from_slice(&[0x2a, 0x00, 0x00, 0x00]);
+        let xr = ExtendedReportView::try_new(&data).unwrap();
+        assert!(xr.blocks().next().unwrap().is_err());
+    }
+
+    #[test]
+    fn write_and_read_statistics_summary() {
+        let summary = StatisticsSummary {
+            ssrc: 0x0a0b0c0d,
+            begin_seq: 100,
+            end_seq: 200,
+            lost_packets: Some(3),
+            dup_packets: None,
+            jitter: Some(Summary {
+                min: 1,
+                max: 10,
+                mean: 4,
+                dev: 2,
+            }),
+            ttl: Some((
+                TtlKind::HopLimit,
+                Summary {
+                    min: 60,
+                    max: 64,
+                    mean: 63,
+                    dev: 1,
+                },
+            )),
+        };
+        let block = ReportBlock::StatisticsSummary(summary);
+        let mut buf = [0u8; 4 + 40];
+        buf[..4].copy_from_slice(&0x01020304u32.to_be_bytes());
+        block.write(&mut buf[4..]).unwrap();
+        assert_eq!(&buf[4..8], &[6, 0xa8, 0, 9]);
+        let xr = ExtendedReportView::try_new(&buf).unwrap();
+        match xr.blocks().next().unwrap().unwrap() {
+            ReportBlockView::StatisticsSummary(read) => assert_eq!(read, summary),
+            _ => panic!("expected statistics summary"),
+        }
+        let summary = StatisticsSummary {
+            ttl: None,
+            ..Default::default()
+        };
+        ReportBlock::StatisticsSummary(summary)
+            .write(&mut buf[4..])
+            .unwrap();
+        assert_eq!(buf[5], 0x10);
+        match xr_block(&buf) {
+            ReportBlockView::StatisticsSummary(read) => assert_eq!(read, summary),
+            _ => panic!("expected statistics summary"),
+        }
+    }
+
+    fn xr_block(data: &[u8]) -> ReportBlockView<'_> {
+        ExtendedReportView::try_new(data)
+            .unwrap()
+            .blocks()
+            .next()
+            .unwrap()
+            .unwrap()
+    }
+}
@@ -0,0 +1,169 @@
+use super::{error, RTPView};
+
+/// Builder for RTP packets. Collects the header fields and writes them together with
+/// a payload into a caller-supplied buffer.
+#[derive(Debug, Clone)]
+pub struct RTPBuilder<'a> {
+    marker: bool,
+    payload_type: u8,
+    sequence_number: u16,
+    timestamp: u32,
+    ssrc: u32,
+    csrc: &'a [u32],
+    extension: Option<([u8; 2], &'a [u8])>,
+    padding: u8,
+}
+
+impl<'a> Default for RTPBuilder<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> RTPBuilder<'a> {
+    /// RTP protocol version written to every packet
+    const VERSION: u8 = 2;
+
+    /// Maximum number of csrcs that can be indicated by the CC field
+    const CSRC_COUNT_MAX: usize = 0xf;
+
+    /// Create a new builder with all header fields set to zero
+    pub fn new() -> Self {
+        Self {
+            marker: false,
+            payload_type: 0,
+            sequence_number: 0,
+            timestamp: 0,
+            ssrc: 0,
+            csrc: &[],
+            extension: None,
+            padding: 0,
+        }
+    }
+
+    /// Set the marker bit
+    pub fn with_marker(mut self, marker: bool) -> Self {
+        self.marker = marker;
+        self
+    }
+
+    /// Set the payload type. Must fit into 7 bits
+    pub fn with_payload_type(mut self, payload_type: u8) -> Self {
+        self.payload_type = payload_type;
+        self
+    }
+
+    /// Set the (not extended) sequence number
+    pub fn with_sequence_number(mut self, sequence_number: u16) -> Self {
+        self.sequence_number = sequence_number;
+        self
+    }
+
+    /// Set the RTP timestamp
+    pub fn with_timestamp(mut self, timestamp: u32) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Set the SSRC of the stream this packet belongs to
+    pub fn with_ssrc(mut self, ssrc: u32) -> Self {
+        self.ssrc = ssrc;
+        self
+    }
+
+    /// Set the list of csrcs. At most 15 csrcs can be written
+    pub fn with_csrc(mut self, csrc: &'a [u32]) -> Self {
+        self.csrc = csrc;
+        self
+    }
+
+    /// Add a header extension as defined in RFC3550 5.3.1. The length of `data` must be a
+    /// multiple of 4 bytes, the extension information header is added by the builder
+    pub fn with_extension(mut self, profile: [u8; 2], data: &'a [u8]) -> Self {
+        self.extension = Some((profile, data));
+        self
+    }
+
+    /// Add `len` bytes of padding after the payload. The padding length includes the
+    /// trailing padding-length byte, a value of 0 disables padding
+    pub fn with_padding(mut self, len: u8) -> Self {
+        self.padding = len;
+        self
+    }
+
+    /// Length of the header including csrcs and the header extension
+    pub fn header_len(&self) -> usize {
+        RTPView::HEADER_LEN_MIN
+            + core::mem::size_of_val(self.csrc)
+            + self.extension.map(|(_, data)| 4 + data.len()).unwrap_or(0)
+    }
+
+    /// Total length of a packet written by this builder with a payload of `payload_len` bytes
+    pub fn packet_len(&self, payload_len: usize) -> usize {
+        self.header_len() + payload_len + self.padding as usize
+    }
+
+    /// Check that all configured values can be represented in the header
+    fn validate(&self) -> Result<(), error::Error> {
+        if self.payload_type > 0x7f {
+            Err(error::other("payload type must fit into 7 bits"))
+        } else if self.csrc.len() > Self::CSRC_COUNT_MAX {
+            Err(error::other("too many csrcs, at most 15 are allowed"))
+        } else {
+            match self.extension {
+                Some((_, data)) if !data.len().is_multiple_of(4) => Err(error::other(
+                    "extension length must be a multiple of 4 bytes",
+                )),
+                Some((_, data)) if data.len() / 4 > u16::MAX as usize => {
+                    Err(error::other("extension too long"))
+                }
+                _ => Ok(()),
+            }
+        }
+    }
+
+    /// Write the packet with the given payload into `buf`. Returns the number of bytes
+    /// written or an error if the buffer is too small or the header can not be represented.
+    pub fn write(&self, payload: &[u8], buf: &mut [u8]) -> Result<usize, error::Error> {
+        self.validate()?;
+        let len = self.packet_len(payload.len());
+        if buf.len() < len {
+            return Err(error::not_enough_space(len, buf.len()));
+        }
+        let buf = &mut buf[..len];
+        buf[0] = (Self::VERSION << 6)
+            | if self.padding != 0 { 0x20 } else { 0 }
+            | if self.extension.is_some() { 0x10 } else { 0 }
+            | self.csrc.len() as u8;
+        buf[1] = if self.marker { 0x80 } else { 0 } | self.payload_type;
+        buf[2..4].copy_from_slice(&self.sequence_number.to_be_bytes());
+        buf[4..8].copy_from_slice(&self.timestamp.to_be_bytes());
+        buf[8..12].copy_from_slice(&self.ssrc.to_be_bytes());
+        let mut offset = RTPView::HEADER_LEN_MIN;
+        for csrc in self.csrc {
+            buf[offset..offset + 4].copy_from_slice(&csrc.to_be_bytes());
+            offset += 4;
+        }
+        if let Some((profile, data)) = self.extension {
+            buf[offset..offset + 2].copy_from_slice(&profile);
+            buf[offset + 2..offset + 4].copy_from_slice(&((data.len() / 4) as u16).to_be_bytes());
+            buf[offset + 4..offset + 4 + data.len()].copy_from_slice(data);
+            offset += 4 + data.len();
+        }
+        buf[offset..offset + payload.len()].copy_from_slice(payload);
+        offset += payload.len();
+        if self.padding != 0 {
+            buf[offset..len - 1].fill(0);
+            buf[len - 1] = self.padding;
+        }
+        Ok(len)
+    }
+
+    /// Write the packet with the given payload into a newly allocated vector
+    #[cfg(feature = "alloc")]
+    pub fn to_vec(&self, payload: &[u8]) -> Result<alloc::vec::Vec<u8>, error::Error> {
+        let mut buf = alloc::vec![0u8; self.packet_len(payload.len())];
+        self.write(payload, &mut buf)?;
+        Ok(buf)
+    }
+}
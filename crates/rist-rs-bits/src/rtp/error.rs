@@ -1,13 +1,27 @@
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum Kind {
     Other(&'static str),
+    NotEnoughSpace { need: usize, got: usize },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct Error {
     k: Kind,
 }
 
+impl Error {
+    /// Get the kind of error
+    pub fn kind(&self) -> Kind {
+        self.k
+    }
+}
+
 pub fn other(s: &'static str) -> Error {
     Error { k: Kind::Other(s) }
 }
+
+pub fn not_enough_space(need: usize, got: usize) -> Error {
+    Error {
+        k: Kind::NotEnoughSpace { need, got },
+    }
+}
@@ -4,4 +4,15 @@ pub mod rist;
 
 /// A RTP Header Extension
 pub trait ReadExt<'a>: TryFrom<&'a [u8]> {}
-pub trait WriteExt {}
+
+/// A RTP Header Extension that can be written with [super::RTPBuilder::with_extension]
+pub trait WriteExt {
+    /// Value of the profile-defined field of the extension information header
+    const PROFILE: [u8; 2];
+
+    /// Extension data without the extension information header
+    type Data: AsRef<[u8]>;
+
+    /// Encode the extension data
+    fn to_data(&self) -> Self::Data;
+}
@@ -1,37 +1,266 @@
 use core::convert::TryFrom;
 
 use super::super::RTPView;
+use crate::util;
 
-#[derive(Debug)]
+/// Value of the profile-defined field of the RIST header extension ("RI")
+pub const PROFILE: [u8; 2] = [0x52, 0x49];
+
+/// Length of the extension data following the extension information header
+pub const DATA_LEN: usize = 4;
+
+/// Length of the extension information header
+const INFO_LEN: usize = 4;
+
+/// Maximum number of TS packets that can be described by the extension
+pub const TS_PACKET_COUNT_MAX: u8 = 7;
+
+const FLAG_NPD: u8 = 0x80;
+pub(crate) const FLAG_SEQ_EXT: u8 = 0x40;
+const FLAG_TS_204: u8 = 0x04;
+
+#[derive(Debug, Clone, Copy)]
 pub enum ErrorKind {
     InvalidLength,
+    InvalidProfile([u8; 2]),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct Error {
     kind: ErrorKind,
 }
 
+impl Error {
+    fn new(kind: ErrorKind) -> Self {
+        Self { kind }
+    }
+
+    /// Get the kind of error
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+/// View over the RIST RTP header extension (VSF TR-06-1). Carries the upper 16 bits of
+/// the extended sequence number and the null packet deletion (NPD) information
+#[derive(Debug, Clone, Copy)]
 pub struct Extension<'a> {
     data: &'a [u8],
 }
 
+/// Create the view from the extension field of a RTP packet, starting with the extension
+/// information header
 impl<'a> TryFrom<&'a [u8]> for Extension<'a> {
     type Error = Error;
 
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
-        Ok(Self { data })
+        if data.len() < INFO_LEN + DATA_LEN {
+            Err(Error::new(ErrorKind::InvalidLength))
+        } else if data[0..2] != PROFILE {
+            Err(Error::new(ErrorKind::InvalidProfile([data[0], data[1]])))
+        } else if util::read_int!(data, u16, 2) as usize * 4 != DATA_LEN {
+            Err(Error::new(ErrorKind::InvalidLength))
+        } else {
+            Ok(Self {
+                data: &data[INFO_LEN..INFO_LEN + DATA_LEN],
+            })
+        }
     }
 }
 
 impl<'a> super::ReadExt<'a> for Extension<'a> {}
 
 impl<'a> Extension<'a> {
-    fn has_extended_sequence_number(&self) -> bool {
-        todo!()
+    /// Check if null packet deletion is active for this packet (N flag)
+    pub fn has_npd(&self) -> bool {
+        (self.data[0] & FLAG_NPD) != 0
+    }
+
+    /// Check if the sequence number extension is valid (E flag)
+    pub fn has_extended_sequence_number(&self) -> bool {
+        (self.data[0] & FLAG_SEQ_EXT) != 0
+    }
+
+    /// Number of TS packets carried by the packet before null packets were deleted
+    pub fn ts_packet_count(&self) -> u8 {
+        (self.data[0] >> 3) & 0x7
+    }
+
+    /// Size of the carried TS packets in bytes, either 188 or 204
+    pub fn ts_packet_size(&self) -> usize {
+        if (self.data[0] & FLAG_TS_204) != 0 {
+            204
+        } else {
+            188
+        }
+    }
+
+    /// Null packet deletion bitmap. The most significant of the 7 bits corresponds to the first
+    /// TS packet, a set bit indicates that a null packet was deleted at this position
+    pub fn npd_bits(&self) -> u8 {
+        ((self.data[0] & 0x3) << 5) | (self.data[1] >> 3)
+    }
+
+    /// Check if a null packet was deleted at TS packet position `index`
+    pub fn is_null_packet_deleted(&self, index: usize) -> bool {
+        index < TS_PACKET_COUNT_MAX as usize && (self.npd_bits() & (0x40 >> index)) != 0
+    }
+
+    /// Upper 16 bits of the extended sequence number
+    pub fn sequence_number_extension(&self) -> u16 {
+        util::read_int!(self.data, u16, 2)
+    }
+
+    /// Get the 32-bit extended sequence number formed with the sequence number of `packet`.
+    /// Returns `None` if the sequence number extension is not valid
+    pub fn extended_sequence_number(&self, packet: &RTPView) -> Option<u32> {
+        self.has_extended_sequence_number().then(|| {
+            ((self.sequence_number_extension() as u32) << 16) | packet.sequence_number() as u32
+        })
+    }
+}
+
+/// Builds the data of a RIST header extension to be written with
+/// [RTPBuilder::with_extension](super::super::RTPBuilder::with_extension)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExtensionBuilder {
+    npd: bool,
+    ts_packet_count: u8,
+    ts_packet_size_204: bool,
+    npd_bits: u8,
+    sequence_number_extension: Option<u16>,
+}
+
+impl ExtensionBuilder {
+    /// Create a new builder with all fields and flags cleared
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the upper 16 bits of the extended sequence number and the E flag
+    pub fn with_sequence_number_extension(mut self, ext: u16) -> Self {
+        self.sequence_number_extension = Some(ext);
+        self
+    }
+
+    /// Set the upper 16 bits from a 32-bit extended sequence number. The lower 16 bits are
+    /// expected to be written to the RTP sequence number field
+    pub fn with_extended_sequence_number(self, seq: u32) -> Self {
+        self.with_sequence_number_extension((seq >> 16) as u16)
+    }
+
+    /// Set the number of TS packets carried (including deleted null packets). Values above 7 are clamped
+    pub fn with_ts_packet_count(mut self, count: u8) -> Self {
+        self.ts_packet_count = count.min(TS_PACKET_COUNT_MAX);
+        self
+    }
+
+    /// Set the size of the carried TS packets to 204 instead of 188 bytes
+    pub fn with_ts_packet_size_204(mut self, ts_204: bool) -> Self {
+        self.ts_packet_size_204 = ts_204;
+        self
+    }
+
+    /// Enable null packet deletion and set the 7-bit deletion bitmap. The most significant
+    /// bit corresponds to the first TS packet
+    pub fn with_npd_bits(mut self, bits: u8) -> Self {
+        self.npd = true;
+        self.npd_bits = bits & 0x7f;
+        self
+    }
+
+    /// Get the extension data
+    pub fn build(&self) -> [u8; DATA_LEN] {
+        let [ext_hi, ext_lo] = self.sequence_number_extension.unwrap_or(0).to_be_bytes();
+        let mut b0 = (self.ts_packet_count << 3) | (self.npd_bits >> 5);
+        if self.npd {
+            b0 |= FLAG_NPD;
+        }
+        if self.sequence_number_extension.is_some() {
+            b0 |= FLAG_SEQ_EXT;
+        }
+        if self.ts_packet_size_204 {
+            b0 |= FLAG_TS_204;
+        }
+        [b0, (self.npd_bits & 0x1f) << 3, ext_hi, ext_lo]
+    }
+}
+
+impl super::WriteExt for ExtensionBuilder {
+    const PROFILE: [u8; 2] = PROFILE;
+    type Data = [u8; DATA_LEN];
+
+    fn to_data(&self) -> Self::Data {
+        self.build()
+    }
+}
+
+#[allow(unused)]
+mod test {
+    use super::super::WriteExt;
+    use super::*;
+    use crate::rtp::RTPBuilder;
+
+    #[test]
+    fn read() {
+        let data = [0x52, 0x49, 0x00, 0x01, 0xfb, 0x28, 0x12, 0x34];
+        let ext = Extension::try_from(data.as_slice()).unwrap();
+        assert!(ext.has_npd());
+        assert!(ext.has_extended_sequence_number());
+        assert_eq!(ext.ts_packet_count(), 7);
+        assert_eq!(ext.ts_packet_size(), 188);
+        assert_eq!(ext.npd_bits(), 0b1100101);
+        assert!(ext.is_null_packet_deleted(0));
+        assert!(ext.is_null_packet_deleted(1));
+        assert!(!ext.is_null_packet_deleted(2));
+        assert!(ext.is_null_packet_deleted(6));
+        assert!(!ext.is_null_packet_deleted(7));
+        assert_eq!(ext.sequence_number_extension(), 0x1234);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Extension::try_from([0x52, 0x49, 0x00, 0x01].as_slice()).is_err());
+        assert!(Extension::try_from([0x52, 0x48, 0x00, 0x01, 0, 0, 0, 0].as_slice()).is_err());
+        assert!(Extension::try_from([0x52, 0x49, 0x00, 0x02, 0, 0, 0, 0].as_slice()).is_err());
+    }
+
+    #[test]
+    fn build() {
+        let data = ExtensionBuilder::new()
+            .with_ts_packet_count(7)
+            .with_npd_bits(0b1100101)
+            .with_sequence_number_extension(0x1234)
+            .build();
+        assert_eq!(data, [0xfb, 0x28, 0x12, 0x34]);
+        assert_eq!(
+            ExtensionBuilder::new()
+                .with_ts_packet_count(1)
+                .with_ts_packet_size_204(true)
+                .build(),
+            [0x0c, 0x00, 0x00, 0x00]
+        );
     }
 
-    fn extended_sequence_number(&self, packet: &RTPView) -> Option<u32> {
-        todo!()
+    #[test]
+    fn round_trip() {
+        let ext = ExtensionBuilder::new()
+            .with_ts_packet_count(7)
+            .with_npd_bits(0b0000010)
+            .with_extended_sequence_number(0x0003_fffe);
+        let data = ext.to_data();
+        let packet = RTPBuilder::new()
+            .with_sequence_number(0xfffe)
+            .with_extension(ExtensionBuilder::PROFILE, &data)
+            .to_vec(&[0x47; 188 * 6])
+            .unwrap();
+        let rtp = RTPView::try_new(&packet).unwrap();
+        let read = rtp.rist_extension().unwrap().unwrap();
+        assert!(read.has_npd());
+        assert!(read.is_null_packet_deleted(5));
+        assert_eq!(read.ts_packet_count(), 7);
+        assert_eq!(read.extended_sequence_number(&rtp), Some(0x0003_fffe));
+        assert_eq!(rtp.extended_sequence_number(), Some(0x0003_fffe));
+        assert_eq!(rtp.payload().unwrap().len(), 188 * 6);
     }
 }
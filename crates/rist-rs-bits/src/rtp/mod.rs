@@ -1,6 +1,11 @@
 #![allow(unused)]
+mod builder;
 pub mod error;
-mod ext;
+pub mod ext;
+mod view_mut;
+
+pub use builder::RTPBuilder;
+pub use view_mut::RTPViewMut;
 
 use super::util;
 use core::convert::TryFrom;
@@ -40,6 +45,16 @@ impl<'a> RTPView<'a> {
         (self.data[0] & 0xc0) >> 6
     }
 
+    /// Check if the marker bit is set
+    pub fn marker(&self) -> bool {
+        (self.data[1] & 0x80) != 0
+    }
+
+    /// Get the payload type
+    pub fn payload_type(&self) -> u8 {
+        self.data[1] & 0x7f
+    }
+
     /// Check if the header extension bit is set
     pub fn has_extension(&self) -> bool {
         (self.data[0] & 0x10) != 0
@@ -90,22 +105,27 @@ impl<'a> RTPView<'a> {
         self.has_padding()
             .then(|| match self.data[self.data.len() - 1] {
                 0 => Err(error::other("invalid padding length, cannot be 0")),
-                l if l <= self.data.len() as u8 - Self::HEADER_LEN_MIN as u8 => Ok(l),
+                l if l as usize <= self.data.len() - Self::HEADER_LEN_MIN => Ok(l),
                 _s => Err(error::other("invalid padding")),
             })
     }
 
-    pub fn payload(&self) -> Result<&'a [u8], error::Error> {
+    /// Range of the payload within the packet
+    fn payload_range(&self) -> Result<core::ops::Range<usize>, error::Error> {
         let offset =
-            Self::HEADER_LEN_MIN + self.extension_len().unwrap_or(Ok(0))? + self.crscs_len();
+            Self::HEADER_LEN_MIN + self.extension_size().unwrap_or(Ok(0))? + self.crscs_len();
         let padding = self.padding_len().unwrap_or(Ok(0))? as usize;
         if offset + padding > self.data.len() {
             Err(error::other("length error"))
         } else {
-            Ok(&self.data[offset..self.data.len() - padding])
+            Ok(offset..self.data.len() - padding)
         }
     }
 
+    pub fn payload(&self) -> Result<&'a [u8], error::Error> {
+        Ok(&self.data[self.payload_range()?])
+    }
+
     /// Get the extension information header defined in RFC3550 5.3.1
     /// Returns `None` if the extension bit is not set
     pub fn extension_info(&self) -> Option<Result<([u8; 2], u16), error::Error>> {
@@ -129,17 +149,48 @@ impl<'a> RTPView<'a> {
             .map(|r| r.map(|(_, len)| len as usize))
     }
 
+    /// Size of the extension in bytes, including the 4 bytes of the extension information
+    /// header. `None` if the extension bit is not set
+    pub fn extension_size(&self) -> Option<Result<usize, error::Error>> {
+        self.extension_len().map(|r| r.map(|len| 4 + len * 4))
+    }
+
     /// Get the extension header. Returns `None` if the extension bit is not set
     /// and an error if conversion to the extension type fails.
-    fn extension<'b, Ext: ext::ReadExt<'a>>(&self) -> Option<Result<Ext, Ext::Error>> {
+    /// The data passed to the extension starts with the extension information header.
+    pub fn extension<Ext: ext::ReadExt<'a>>(&self) -> Option<Result<Ext, Ext::Error>> {
         self.has_extension().then(|| {
-            Ext::try_from(&self.data[Self::HEADER_LEN_MIN + (4 * self.csrc_count() as usize)..])
+            Ext::try_from(
+                self.data
+                    .get(Self::HEADER_LEN_MIN + self.crscs_len()..)
+                    .unwrap_or(&[]),
+            )
         })
     }
-}
 
-trait RTPWriter {
-    fn sequence_number() -> u16;
+    /// Get the RIST header extension (VSF TR-06-1). Returns `None` if the extension bit is not set
+    pub fn rist_extension(&self) -> Option<Result<ext::rist::Extension<'a>, ext::rist::Error>> {
+        self.extension()
+    }
+
+    /// Get the 32-bit extended sequence number from the RIST header extension. Returns `None`
+    /// if the packet carries no valid RIST header extension or the extension is not flagged as valid
+    pub fn extended_sequence_number(&self) -> Option<u32> {
+        self.rist_extension()?.ok()?.extended_sequence_number(self)
+    }
+
+    /// Iterate over the MPEG-2 transport stream packets in the payload. Uses the packet size
+    /// signalled by the RIST header extension if present, 188 bytes otherwise
+    pub fn ts_packets(&self) -> Result<crate::ts::TsPacketIter<'a>, error::Error> {
+        let packet_len = match self.rist_extension() {
+            Some(Ok(ext)) => ext.ts_packet_size(),
+            _ => crate::ts::PACKET_LEN,
+        };
+        Ok(crate::ts::TsPacketIter::with_packet_len(
+            self.payload()?,
+            packet_len,
+        ))
+    }
 }
 
 mod test {
@@ -199,7 +250,7 @@ mod test {
         0xA0, 0x21, 0x23, 0x6c, 0x5b, 0x68, 0x20, 0x88, 0xb3, 0x59, 0xbe, 0xe2, 0x00,
     ];
 
-    pub fn packet(data: &[u8]) -> RTPView {
+    pub fn packet(data: &[u8]) -> RTPView<'_> {
         RTPView::try_from(data).unwrap()
     }
 
@@ -280,14 +331,190 @@ mod test {
     #[test]
     fn invalid() {
         // conversion from invalid length should fail
-        assert!(matches!(
-            RTPView::try_from(RTP_INVALID_LEN.as_slice()),
-            Err(_)
-        ));
+        assert!(RTPView::try_from(RTP_INVALID_LEN.as_slice()).is_err());
 
         let broken_padding = packet(&RTP_BROKEN_PADDING);
         assert!(broken_padding.has_padding());
-        assert!(matches!(broken_padding.payload(), Err(_)));
-        assert!(matches!(broken_padding.padding_len().unwrap(), Err(_)))
+        assert!(broken_padding.payload().is_err());
+        assert!(broken_padding.padding_len().unwrap().is_err())
+    }
+
+    const PAYLOAD_16: [u8; 16] = [
+        0xff, 0xfa, 0xff, 0xfa, 0xff, 0xfa, 0xff, 0xfa, 0xff, 0xfa, 0xff, 0xfa, 0xff, 0xfa, 0xff,
+        0xfa,
+    ];
+
+    const CSRCS: [u32; 4] = [0x1000000, 0x10000, 0x100, 0x1];
+
+    fn builder() -> RTPBuilder<'static> {
+        RTPBuilder::new()
+            .with_payload_type(0x21)
+            .with_sequence_number(9068)
+            .with_timestamp(1533550728)
+            .with_ssrc(3009003234)
+    }
+
+    #[test]
+    fn build_basics() {
+        let packet = builder().to_vec(&[0x47, 0x40]).unwrap();
+        assert_eq!(packet, SOME_PACKET);
+        let rtp = RTPView::try_new(&packet).unwrap();
+        assert_eq!(rtp.version(), 2);
+        assert_eq!(rtp.payload_type(), 0x21);
+        assert!(!rtp.marker());
+        assert_eq!(rtp.payload().unwrap(), &[0x47u8, 0x40u8]);
+    }
+
+    #[test]
+    fn build_empty() {
+        let packet = builder().to_vec(&[]).unwrap();
+        assert_eq!(packet, RTP_WITH_0_LEN);
+    }
+
+    #[test]
+    fn build_empty_with_padding() {
+        let padding_l1 = builder().with_padding(1).to_vec(&[]).unwrap();
+        let padding_l2 = builder().with_padding(2).to_vec(&[]).unwrap();
+        assert_eq!(padding_l1, RTP_0_LEN_PADDED_1);
+        assert_eq!(padding_l2, RTP_0_LEN_PADDED_2);
+    }
+
+    #[test]
+    fn build_empty_and_csrcs() {
+        let packet = builder().with_csrc(&CSRCS).to_vec(&[]).unwrap();
+        assert_eq!(packet, RTP_0_LEN_4_CSRC);
+    }
+
+    #[test]
+    fn build_payload_and_padding_and_csrcs() {
+        let packet = builder()
+            .with_csrc(&CSRCS)
+            .with_padding(4)
+            .to_vec(&PAYLOAD_16)
+            .unwrap();
+        let rtp = RTPView::try_new(&packet).unwrap();
+        assert_eq!(rtp.csrc().collect::<Vec<_>>(), CSRCS);
+        assert!(rtp.has_padding());
+        assert_eq!(rtp.padding_len().unwrap().unwrap(), 4);
+        assert_eq!(rtp.payload().unwrap(), PAYLOAD_16);
+    }
+
+    #[test]
+    fn build_extension_and_marker() {
+        let ext = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+        let packet = builder()
+            .with_marker(true)
+            .with_csrc(&CSRCS[..1])
+            .with_extension([0x52, 0x49], &ext)
+            .with_padding(3)
+            .to_vec(&PAYLOAD_16)
+            .unwrap();
+        let rtp = RTPView::try_new(&packet).unwrap();
+        assert!(rtp.marker());
+        assert!(rtp.has_extension());
+        assert_eq!(rtp.extension_info().unwrap().unwrap(), ([0x52, 0x49], 2));
+        assert_eq!(rtp.extension_len().unwrap().unwrap(), 2);
+        assert_eq!(rtp.extension_size().unwrap().unwrap(), 12);
+        assert_eq!(rtp.payload().unwrap(), PAYLOAD_16);
+    }
+
+    #[test]
+    fn ts_packets() {
+        let mut payload = [0u8; 2 * crate::ts::PACKET_LEN_204];
+        for (i, ts) in payload.chunks_mut(crate::ts::PACKET_LEN_204).enumerate() {
+            ts[..4].copy_from_slice(&[crate::ts::SYNC_BYTE, 0x01, 0x00, 0x10 | i as u8]);
+        }
+        let ext = ext::rist::ExtensionBuilder::new()
+            .with_ts_packet_count(2)
+            .with_ts_packet_size_204(true)
+            .build();
+        let packet = builder()
+            .with_extension(ext::rist::PROFILE, &ext)
+            .to_vec(&payload)
+            .unwrap();
+        let rtp = RTPView::try_new(&packet).unwrap();
+        let counters = rtp
+            .ts_packets()
+            .unwrap()
+            .map(|ts| ts.unwrap().continuity_counter())
+            .collect::<Vec<_>>();
+        assert_eq!(counters, [0, 1]);
+        // without the extension the payload is split into 188 byte packets
+        let packet = builder().to_vec(&payload).unwrap();
+        let rtp = RTPView::try_new(&packet).unwrap();
+        assert!(rtp.ts_packets().unwrap().any(|ts| ts.is_err()));
+    }
+
+    #[test]
+    fn modify_in_place() {
+        let ext = ext::rist::ExtensionBuilder::new().with_ts_packet_count(1);
+        let mut packet = builder()
+            .with_csrc(&CSRCS[..2])
+            .with_extension(ext::rist::PROFILE, &ext.build())
+            .with_padding(4)
+            .to_vec(&PAYLOAD_16)
+            .unwrap();
+        let mut rtp = RTPViewMut::try_from(packet.as_mut_slice()).unwrap();
+        rtp.set_marker(true);
+        assert!(rtp.set_payload_type(0x80).is_err());
+        rtp.set_payload_type(0x60).unwrap();
+        rtp.set_timestamp(0x01020304);
+        rtp.set_ssrc(0xdeadbeef);
+        rtp.set_csrc(1, 0x42).unwrap();
+        assert!(rtp.set_csrc(2, 0x42).is_err());
+        rtp.set_extended_sequence_number(0x0005_0006).unwrap();
+        rtp.payload_mut().unwrap().fill(0x47);
+        let rtp = RTPView::from(rtp);
+        assert!(rtp.marker());
+        assert_eq!(rtp.payload_type(), 0x60);
+        assert_eq!(rtp.timestamp(), 0x01020304);
+        assert_eq!(rtp.ssrc(), 0xdeadbeef);
+        assert_eq!(rtp.csrc().collect::<Vec<_>>(), [CSRCS[0], 0x42]);
+        assert_eq!(rtp.sequence_number(), 6);
+        assert_eq!(rtp.extended_sequence_number(), Some(0x0005_0006));
+        assert_eq!(rtp.rist_extension().unwrap().unwrap().ts_packet_count(), 1);
+        assert_eq!(rtp.payload().unwrap(), [0x47; 16]);
+        assert_eq!(rtp.padding_len().unwrap().unwrap(), 4);
+
+        let mut rtp = RTPViewMut::try_from(packet.as_mut_slice()).unwrap();
+        rtp.set_extension(&ext.with_npd_bits(0x40)).unwrap();
+        assert!(rtp.as_view().rist_extension().unwrap().unwrap().has_npd());
+        rtp.set_extension_profile([0, 0]).unwrap();
+        assert!(rtp.as_view().rist_extension().unwrap().is_err());
+        assert!(rtp.set_extended_sequence_number(0).is_err());
+        rtp.extension_data_mut().unwrap().unwrap().fill(0);
+
+        let mut packet = builder().to_vec(&[]).unwrap();
+        let mut rtp = RTPViewMut::try_from(packet.as_mut_slice()).unwrap();
+        assert!(rtp.extension_data_mut().is_none());
+        assert!(rtp.set_extension(&ext).is_err());
+        assert!(RTPViewMut::try_from(&mut packet[..11]).is_err());
+    }
+
+    #[test]
+    fn build_large_payload_with_padding() {
+        let payload = [0x47u8; 7 * 188];
+        let packet = builder().with_padding(4).to_vec(&payload).unwrap();
+        let rtp = RTPView::try_new(&packet).unwrap();
+        assert_eq!(rtp.payload().unwrap(), payload.as_slice());
+    }
+
+    #[test]
+    fn build_write_into_slice() {
+        let mut buf = [0u8; 13];
+        assert!(builder().write(&[0x47, 0x40], &mut buf).is_err());
+        let mut buf = [0u8; 32];
+        let len = builder().write(&[0x47, 0x40], &mut buf).unwrap();
+        assert_eq!(&buf[..len], SOME_PACKET);
+    }
+
+    #[test]
+    fn build_invalid() {
+        assert!(builder().with_payload_type(0x80).to_vec(&[]).is_err());
+        assert!(builder().with_csrc(&[0u32; 16]).to_vec(&[]).is_err());
+        assert!(builder()
+            .with_extension([0, 0], &[0u8; 3])
+            .to_vec(&[])
+            .is_err());
     }
 }
@@ -0,0 +1,154 @@
+use core::ops::Range;
+
+use super::{error, ext, RTPView};
+
+/// Length of the extension information header
+const EXT_INFO_LEN: usize = 4;
+
+/// View over a mutable slice of data that can be interpreted as a RTP packet. Allows
+/// rewriting the header fields of a packet in place, e.g. when relaying a stream.
+#[derive(Debug)]
+pub struct RTPViewMut<'a> {
+    data: &'a mut [u8],
+}
+
+impl<'a> TryFrom<&'a mut [u8]> for RTPViewMut<'a> {
+    type Error = error::Error;
+
+    fn try_from(data: &'a mut [u8]) -> Result<Self, Self::Error> {
+        RTPView::try_from(&*data)?;
+        Ok(Self { data })
+    }
+}
+
+impl<'a> From<RTPViewMut<'a>> for RTPView<'a> {
+    fn from(view: RTPViewMut<'a>) -> Self {
+        RTPView { data: view.data }
+    }
+}
+
+impl<'a> RTPViewMut<'a> {
+    /// Get a read-only view over the packet
+    pub fn as_view(&self) -> RTPView<'_> {
+        RTPView { data: self.data }
+    }
+
+    /// Set the marker bit
+    pub fn set_marker(&mut self, marker: bool) {
+        self.data[1] = (self.data[1] & 0x7f) | if marker { 0x80 } else { 0 };
+    }
+
+    /// Set the payload type. Returns an error if the value does not fit into 7 bits
+    pub fn set_payload_type(&mut self, pt: u8) -> Result<(), error::Error> {
+        if pt > 0x7f {
+            return Err(error::other("payload type must fit into 7 bits"));
+        }
+        self.data[1] = (self.data[1] & 0x80) | pt;
+        Ok(())
+    }
+
+    /// Set the (not extended) sequence number
+    pub fn set_sequence_number(&mut self, seq: u16) {
+        self.data[2..4].copy_from_slice(&seq.to_be_bytes());
+    }
+
+    /// Set the RTP timestamp
+    pub fn set_timestamp(&mut self, ts: u32) {
+        self.data[4..8].copy_from_slice(&ts.to_be_bytes());
+    }
+
+    /// Set the SSRC value
+    pub fn set_ssrc(&mut self, ssrc: u32) {
+        self.data[8..12].copy_from_slice(&ssrc.to_be_bytes());
+    }
+
+    /// Replace the csrc at position `index`. Returns an error if the packet carries less csrcs
+    /// or the csrc list exceeds the packet
+    pub fn set_csrc(&mut self, index: usize, csrc: u32) -> Result<(), error::Error> {
+        if index >= self.as_view().csrc_count() as usize {
+            return Err(error::other("csrc index out of range"));
+        }
+        let offset = RTPView::HEADER_LEN_MIN + index * 4;
+        self.data
+            .get_mut(offset..offset + 4)
+            .ok_or(error::other("length error"))?
+            .copy_from_slice(&csrc.to_be_bytes());
+        Ok(())
+    }
+
+    /// Range of the extension field including the extension information header.
+    /// Returns `None` if the extension bit is not set
+    fn extension_range(&self) -> Option<Result<Range<usize>, error::Error>> {
+        let view = self.as_view();
+        let len = view.extension_size()?;
+        let offset = RTPView::HEADER_LEN_MIN + view.crscs_len();
+        Some(len.and_then(|len| {
+            if offset + len > self.data.len() {
+                Err(error::other("length error"))
+            } else {
+                Ok(offset..offset + len)
+            }
+        }))
+    }
+
+    /// Set the profile-defined field of the extension information header. Returns an error if
+    /// the extension bit is not set
+    pub fn set_extension_profile(&mut self, profile: [u8; 2]) -> Result<(), error::Error> {
+        let range = self
+            .extension_range()
+            .ok_or(error::other("packet has no extension"))??;
+        self.data[range.start..range.start + 2].copy_from_slice(&profile);
+        Ok(())
+    }
+
+    /// Get the extension data without the extension information header for modification.
+    /// Returns `None` if the extension bit is not set
+    pub fn extension_data_mut(&mut self) -> Option<Result<&mut [u8], error::Error>> {
+        Some(
+            self.extension_range()?
+                .map(|range| &mut self.data[range.start + EXT_INFO_LEN..range.end]),
+        )
+    }
+
+    /// Replace the header extension with `extension`. The length of the extension data can
+    /// not change, an error is returned if it differs from the data in the packet or the
+    /// extension bit is not set
+    pub fn set_extension<Ext: ext::WriteExt>(
+        &mut self,
+        extension: &Ext,
+    ) -> Result<(), error::Error> {
+        let data = extension.to_data();
+        let buf = self
+            .extension_data_mut()
+            .ok_or(error::other("packet has no extension"))??;
+        if buf.len() != data.as_ref().len() {
+            return Err(error::other("extension length mismatch"));
+        }
+        buf.copy_from_slice(data.as_ref());
+        self.set_extension_profile(Ext::PROFILE)
+    }
+
+    /// Set the 32-bit extended sequence number. The lower 16 bits are written to the sequence
+    /// number field, the upper 16 bits to the RIST header extension, which is flagged as
+    /// carrying a valid sequence number extension. Returns an error if the packet carries no
+    /// valid RIST header extension
+    pub fn set_extended_sequence_number(&mut self, seq: u32) -> Result<(), error::Error> {
+        self.as_view()
+            .rist_extension()
+            .ok_or(error::other("packet has no extension"))?
+            .map_err(|_| error::other("invalid RIST extension"))?;
+        let data = self
+            .extension_data_mut()
+            .expect(rist_rs_types::internal::INTERNAL_ERR_PRE_VALIDATED)?;
+        data[0] |= ext::rist::FLAG_SEQ_EXT;
+        data[2..4].copy_from_slice(&((seq >> 16) as u16).to_be_bytes());
+        self.set_sequence_number(seq as u16);
+        Ok(())
+    }
+
+    /// Get the payload for modification
+    pub fn payload_mut(&mut self) -> Result<&mut [u8], error::Error> {
+        let range = self.as_view().payload_range()?;
+        Ok(&mut self.data[range])
+    }
+}
@@ -0,0 +1,490 @@
+use core::convert::TryFrom;
+
+use crate::util;
+
+/// Length of an MPEG-2 transport stream packet
+pub const PACKET_LEN: usize = 188;
+
+/// Length of a transport stream packet followed by 16 bytes of Reed-Solomon parity
+pub const PACKET_LEN_204: usize = 204;
+
+/// Value of the first byte of every transport stream packet
+pub const SYNC_BYTE: u8 = 0x47;
+
+/// PID of null packets
+pub const PID_NULL: u16 = 0x1fff;
+
+/// Length of the fixed transport stream packet header
+const HEADER_LEN: usize = 4;
+
+pub mod error {
+    use core::fmt::Display;
+
+    #[derive(Debug, Clone, Copy)]
+    pub enum ErrorKind {
+        /// Not enough data was supplied to read a full transport stream packet
+        NotEnoughData { need: usize, got: usize },
+
+        /// The first byte of the packet is not the sync byte
+        InvalidSyncByte(u8),
+
+        /// The adaptation field length does not fit into the packet
+        InvalidAdaptationFieldLength(usize),
+
+        /// The data can not be split into packets of the given size, holds the number of
+        /// trailing bytes
+        TrailingData(usize),
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct Error {
+        kind: ErrorKind,
+    }
+
+    impl Display for Error {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match &self.kind {
+                ErrorKind::NotEnoughData { need, got } => write!(
+                    f,
+                    "Not enough data to read transport stream packet, need {need} bytes, got {got} bytes"
+                ),
+                ErrorKind::InvalidSyncByte(b) => write!(f, "Invalid sync byte: {b:#04x}"),
+                ErrorKind::InvalidAdaptationFieldLength(len) => {
+                    write!(f, "Invalid adaptation field length: {len}")
+                }
+                ErrorKind::TrailingData(len) => {
+                    write!(f, "{len} bytes of trailing data after the last packet")
+                }
+            }
+        }
+    }
+
+    impl Error {
+        pub(super) fn new(kind: ErrorKind) -> Self {
+            Self { kind }
+        }
+
+        /// Extract the error kind
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+}
+
+use error::{Error, ErrorKind};
+
+/// Scrambling mode of the payload as signalled by the transport scrambling control field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScramblingControl {
+    NotScrambled,
+    Reserved,
+    EvenKey,
+    OddKey,
+}
+
+/// Program clock reference, a 33 bit base in units of 90 kHz and a 9 bit extension in units of
+/// 27 MHz
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pcr {
+    pub base: u64,
+    pub extension: u16,
+}
+
+impl Pcr {
+    /// Frequency of the full clock reference
+    pub const CLOCK_RATE: u64 = 27_000_000;
+
+    fn read(data: &[u8]) -> Self {
+        let base = (u64::from(data[0]) << 25)
+            | (u64::from(data[1]) << 17)
+            | (u64::from(data[2]) << 9)
+            | (u64::from(data[3]) << 1)
+            | (u64::from(data[4]) >> 7);
+        let extension = (u16::from(data[4] & 0x01) << 8) | u16::from(data[5]);
+        Self { base, extension }
+    }
+
+    /// Get the clock reference in units of 27 MHz
+    pub fn ticks(&self) -> u64 {
+        self.base * 300 + u64::from(self.extension)
+    }
+}
+
+/// View over the adaptation field of a transport stream packet, starting after the adaptation
+/// field length byte
+#[derive(Debug, Clone, Copy)]
+pub struct AdaptationField<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> AdaptationField<'a> {
+    /// Length of the adaptation field, not including the length byte
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns true if the adaptation field is a single stuffing byte without flags
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn flags(&self) -> u8 {
+        self.data.first().copied().unwrap_or(0)
+    }
+
+    /// Check if the discontinuity indicator is set
+    pub fn discontinuity_indicator(&self) -> bool {
+        util::check_bit!(self.flags(), 0)
+    }
+
+    /// Check if the random access indicator is set
+    pub fn random_access_indicator(&self) -> bool {
+        util::check_bit!(self.flags(), 1)
+    }
+
+    /// Check if the elementary stream priority indicator is set
+    pub fn es_priority_indicator(&self) -> bool {
+        util::check_bit!(self.flags(), 2)
+    }
+
+    /// Check if the PCR flag is set
+    pub fn has_pcr(&self) -> bool {
+        util::check_bit!(self.flags(), 3)
+    }
+
+    /// Check if the OPCR flag is set
+    pub fn has_opcr(&self) -> bool {
+        util::check_bit!(self.flags(), 4)
+    }
+
+    /// Check if the splicing point flag is set
+    pub fn has_splice_countdown(&self) -> bool {
+        util::check_bit!(self.flags(), 5)
+    }
+
+    /// Read the clock reference at `offset`. Returns `None` if the field is truncated
+    fn clock_reference(&self, offset: usize) -> Option<Pcr> {
+        self.data.get(offset..offset + 6).map(Pcr::read)
+    }
+
+    /// Get the program clock reference. Returns `None` if the PCR flag is not set or the field
+    /// is truncated
+    pub fn pcr(&self) -> Option<Pcr> {
+        self.has_pcr().then(|| self.clock_reference(1)).flatten()
+    }
+
+    /// Get the original program clock reference. Returns `None` if the OPCR flag is not set or
+    /// the field is truncated
+    pub fn opcr(&self) -> Option<Pcr> {
+        self.has_opcr()
+            .then(|| self.clock_reference(if self.has_pcr() { 7 } else { 1 }))
+            .flatten()
+    }
+
+    /// Get the splice countdown. Returns `None` if the splicing point flag is not set or the
+    /// field is truncated
+    pub fn splice_countdown(&self) -> Option<i8> {
+        let offset = 1 + [self.has_pcr(), self.has_opcr()]
+            .into_iter()
+            .filter(|b| *b)
+            .count()
+            * 6;
+        self.has_splice_countdown()
+            .then(|| self.data.get(offset).map(|b| *b as i8))
+            .flatten()
+    }
+}
+
+/// View over an immutable slice of data that can be interpreted as an MPEG-2 transport stream
+/// packet (ISO/IEC 13818-1)
+#[derive(Debug, Clone, Copy)]
+pub struct TsPacketView<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for TsPacketView<'a> {
+    type Error = Error;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::try_new(data)
+    }
+}
+
+impl<'a> TsPacketView<'a> {
+    /// Create a view over the first 188 bytes of `bytes`. Returns an error if there is not
+    /// enough data, the sync byte is missing or the adaptation field length is invalid
+    pub fn try_new<T, U>(bytes: &'a T) -> Result<Self, Error>
+    where
+        T: AsRef<U> + ?Sized,
+        U: ?Sized + 'a,
+        &'a U: Into<&'a [u8]>,
+    {
+        let data: &'a [u8] = bytes.as_ref().into();
+        if data.len() < PACKET_LEN {
+            return Err(Error::new(ErrorKind::NotEnoughData {
+                need: PACKET_LEN,
+                got: data.len(),
+            }));
+        }
+        let view = Self {
+            data: &data[..PACKET_LEN],
+        };
+        if view.sync_byte() != SYNC_BYTE {
+            Err(Error::new(ErrorKind::InvalidSyncByte(view.sync_byte())))
+        } else if view.payload_offset() > PACKET_LEN {
+            Err(Error::new(ErrorKind::InvalidAdaptationFieldLength(
+                view.data[HEADER_LEN] as usize,
+            )))
+        } else {
+            Ok(view)
+        }
+    }
+
+    /// Get the sync byte, always 0x47 for a valid packet
+    pub fn sync_byte(&self) -> u8 {
+        self.data[0]
+    }
+
+    /// Check if the transport error indicator is set
+    pub fn transport_error_indicator(&self) -> bool {
+        util::check_bit!(self.data[1], 0)
+    }
+
+    /// Check if the payload unit start indicator is set
+    pub fn payload_unit_start_indicator(&self) -> bool {
+        util::check_bit!(self.data[1], 1)
+    }
+
+    /// Check if the transport priority bit is set
+    pub fn transport_priority(&self) -> bool {
+        util::check_bit!(self.data[1], 2)
+    }
+
+    /// Get the 13 bit packet identifier
+    pub fn pid(&self) -> u16 {
+        util::read_int!(self.data, u16, 1) & 0x1fff
+    }
+
+    /// Check if this is a null packet
+    pub fn is_null_packet(&self) -> bool {
+        self.pid() == PID_NULL
+    }
+
+    /// Get the transport scrambling control value
+    pub fn scrambling_control(&self) -> ScramblingControl {
+        match self.data[3] >> 6 {
+            0 => ScramblingControl::NotScrambled,
+            1 => ScramblingControl::Reserved,
+            2 => ScramblingControl::EvenKey,
+            _ => ScramblingControl::OddKey,
+        }
+    }
+
+    /// Check if the packet carries an adaptation field
+    pub fn has_adaptation_field(&self) -> bool {
+        util::check_bit!(self.data[3], 2)
+    }
+
+    /// Check if the packet carries a payload
+    pub fn has_payload(&self) -> bool {
+        util::check_bit!(self.data[3], 3)
+    }
+
+    /// Get the 4 bit continuity counter
+    pub fn continuity_counter(&self) -> u8 {
+        self.data[3] & 0x0f
+    }
+
+    /// Offset of the payload, may exceed the packet length if the adaptation field length is
+    /// invalid
+    fn payload_offset(&self) -> usize {
+        if self.has_adaptation_field() {
+            HEADER_LEN + 1 + self.data[HEADER_LEN] as usize
+        } else {
+            HEADER_LEN
+        }
+    }
+
+    /// Get the adaptation field. Returns `None` if the packet has no adaptation field
+    pub fn adaptation_field(&self) -> Option<AdaptationField<'a>> {
+        self.has_adaptation_field().then(|| AdaptationField {
+            data: &self.data[HEADER_LEN + 1..self.payload_offset()],
+        })
+    }
+
+    /// Get the payload. Empty if the packet carries no payload
+    pub fn payload(&self) -> &'a [u8] {
+        if self.has_payload() {
+            &self.data[self.payload_offset()..]
+        } else {
+            &[]
+        }
+    }
+}
+
+/// Iterator over the transport stream packets in a buffer, typically the payload of an RTP
+/// packet. Yields an error for every packet that fails to parse and for trailing data that is
+/// too short to form a packet
+#[derive(Debug, Clone)]
+pub struct TsPacketIter<'a> {
+    data: &'a [u8],
+    packet_len: usize,
+}
+
+impl<'a> TsPacketIter<'a> {
+    /// Iterate over 188 byte packets
+    pub fn new(data: &'a [u8]) -> Self {
+        Self::with_packet_len(data, PACKET_LEN)
+    }
+
+    /// Iterate over packets of `packet_len` bytes, e.g. [PACKET_LEN_204]. Bytes following the
+    /// first 188 bytes of every packet are skipped
+    pub fn with_packet_len(data: &'a [u8], packet_len: usize) -> Self {
+        Self {
+            data,
+            packet_len: packet_len.max(PACKET_LEN),
+        }
+    }
+}
+
+impl<'a> Iterator for TsPacketIter<'a> {
+    type Item = Result<TsPacketView<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            None
+        } else if self.data.len() < self.packet_len {
+            let len = self.data.len();
+            self.data = &[];
+            Some(Err(Error::new(ErrorKind::TrailingData(len))))
+        } else {
+            let (packet, rest) = self.data.split_at(self.packet_len);
+            self.data = rest;
+            Some(TsPacketView::try_new(packet))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.data.len().div_ceil(self.packet_len);
+        (n, Some(n))
+    }
+}
+
+#[allow(unused)]
+mod test {
+    use super::*;
+
+    /// Packet with PID 0x100, PUSI, continuity counter 5 and a payload of 0xaa
+    fn payload_packet() -> [u8; PACKET_LEN] {
+        let mut data = [0xaa; PACKET_LEN];
+        data[..4].copy_from_slice(&[0x47, 0x41, 0x00, 0x15]);
+        data
+    }
+
+    /// Packet with PID 0x101, an adaptation field with discontinuity, random access, PCR and
+    /// splice countdown, followed by a payload of 0xaa
+    fn adaptation_field_packet() -> [u8; PACKET_LEN] {
+        let mut data = [0xaa; PACKET_LEN];
+        data[..4].copy_from_slice(&[0x47, 0x01, 0x01, 0xb7]);
+        data[4] = 9;
+        data[5] = 0xd4;
+        // base 0x1_0000_0001, extension 0x101
+        data[6..12].copy_from_slice(&[0x80, 0x00, 0x00, 0x00, 0xff, 0x01]);
+        data[12] = 0xfe;
+        data[13] = 0xff;
+        data
+    }
+
+    #[test]
+    fn basics() {
+        let data = payload_packet();
+        let ts = TsPacketView::try_from(data.as_slice()).unwrap();
+        assert_eq!(ts.sync_byte(), SYNC_BYTE);
+        assert!(!ts.transport_error_indicator());
+        assert!(ts.payload_unit_start_indicator());
+        assert!(!ts.transport_priority());
+        assert_eq!(ts.pid(), 0x100);
+        assert_eq!(ts.scrambling_control(), ScramblingControl::NotScrambled);
+        assert!(!ts.has_adaptation_field());
+        assert!(ts.has_payload());
+        assert_eq!(ts.continuity_counter(), 5);
+        assert!(ts.adaptation_field().is_none());
+        assert_eq!(ts.payload(), &[0xaa; PACKET_LEN - 4]);
+    }
+
+    #[test]
+    fn adaptation_field() {
+        let data = adaptation_field_packet();
+        let ts = TsPacketView::try_new(&data).unwrap();
+        assert_eq!(ts.pid(), 0x101);
+        assert_eq!(ts.scrambling_control(), ScramblingControl::EvenKey);
+        assert!(ts.has_adaptation_field());
+        assert_eq!(ts.continuity_counter(), 7);
+        let af = ts.adaptation_field().unwrap();
+        assert_eq!(af.len(), 9);
+        assert!(af.discontinuity_indicator());
+        assert!(af.random_access_indicator());
+        assert!(!af.es_priority_indicator());
+        let pcr = af.pcr().unwrap();
+        assert_eq!(pcr.base, 0x1_0000_0001);
+        assert_eq!(pcr.extension, 0x101);
+        assert_eq!(pcr.ticks(), 0x1_0000_0001 * 300 + 0x101);
+        assert!(af.opcr().is_none());
+        assert_eq!(af.splice_countdown(), Some(-2));
+        assert_eq!(ts.payload().len(), PACKET_LEN - 14);
+        assert_eq!(ts.payload()[0], 0xaa);
+    }
+
+    #[test]
+    fn adaptation_field_only() {
+        let mut data = payload_packet();
+        data[3] = 0x20;
+        data[4] = 183;
+        data[5] = 0;
+        let ts = TsPacketView::try_new(&data).unwrap();
+        assert!(!ts.has_payload());
+        assert!(ts.payload().is_empty());
+        assert_eq!(ts.adaptation_field().unwrap().len(), 183);
+        assert!(ts.adaptation_field().unwrap().pcr().is_none());
+    }
+
+    #[test]
+    fn invalid() {
+        let mut data = payload_packet();
+        assert!(TsPacketView::try_new(&data[..187]).is_err());
+        data[3] = 0x30;
+        data[4] = 184;
+        assert!(TsPacketView::try_new(&data).is_err());
+        data[0] = 0x46;
+        assert!(TsPacketView::try_new(&data).is_err());
+    }
+
+    #[test]
+    fn iterate() {
+        let mut data = [0u8; 3 * PACKET_LEN + 10];
+        for (i, packet) in data.chunks_mut(PACKET_LEN).take(3).enumerate() {
+            packet.copy_from_slice(&payload_packet());
+            packet[3] = 0x10 | i as u8;
+        }
+        let mut iter = TsPacketIter::new(&data);
+        assert_eq!(iter.size_hint(), (4, Some(4)));
+        for i in 0..3 {
+            assert_eq!(iter.next().unwrap().unwrap().continuity_counter(), i);
+        }
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn iterate_204() {
+        let mut data = [0u8; 2 * PACKET_LEN_204];
+        for packet in data.chunks_mut(PACKET_LEN_204) {
+            packet[..PACKET_LEN].copy_from_slice(&adaptation_field_packet());
+        }
+        let iter = TsPacketIter::with_packet_len(&data, PACKET_LEN_204);
+        assert_eq!(
+            iter.filter(|p| p.as_ref().unwrap().pid() == 0x101).count(),
+            2
+        );
+    }
+}
@@ -0,0 +1,324 @@
+use core::convert::TryFrom;
+
+use crate::ip::{v6, IpPacketView};
+use crate::util;
+
+use super::UDPPacket;
+
+/// Length of the UDP header
+pub const HEADER_LEN: usize = 8;
+
+/// Ip protocol number of UDP
+pub const IP_PROTOCOL_UDP: u8 = 17;
+
+pub mod error {
+    use core::fmt::Display;
+
+    #[derive(Debug, Clone, Copy)]
+    pub enum ErrorKind {
+        /// Not enough data was supplied to read the header or the length reported by the header
+        NotEnoughData { need: usize, got: usize },
+
+        /// The length field is smaller than the UDP header
+        InvalidLength(usize),
+
+        /// The Ip packet does not carry UDP, holds the upper-layer protocol number
+        NotUdp(u8),
+
+        /// The Ip packet is a fragment and does not hold the complete datagram
+        Fragmented,
+
+        /// The enclosing Ip packet could not be read
+        Ip(crate::ip::error::Error),
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct Error {
+        kind: ErrorKind,
+    }
+
+    impl Display for Error {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match &self.kind {
+                ErrorKind::NotEnoughData { need, got } => write!(
+                    f,
+                    "Not enough data to read UDP datagram, need at least {need} bytes, got {got} bytes"
+                ),
+                ErrorKind::InvalidLength(len) => write!(f, "Invalid UDP length field: {len}"),
+                ErrorKind::NotUdp(p) => write!(f, "Ip packet carries protocol {p}, not UDP"),
+                ErrorKind::Fragmented => write!(f, "Ip packet is fragmented"),
+                ErrorKind::Ip(e) => write!(f, "Invalid Ip packet: {e:?}"),
+            }
+        }
+    }
+
+    impl Error {
+        pub(super) fn new(kind: ErrorKind) -> Self {
+            Self { kind }
+        }
+
+        /// Extract the error kind
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+
+    impl From<crate::ip::error::Error> for Error {
+        fn from(e: crate::ip::error::Error) -> Self {
+            Self::new(ErrorKind::Ip(e))
+        }
+    }
+
+    impl From<crate::ip::v4::error::Error> for Error {
+        fn from(e: crate::ip::v4::error::Error) -> Self {
+            crate::ip::error::Error::from(e).into()
+        }
+    }
+
+    impl From<crate::ip::v6::error::Error> for Error {
+        fn from(e: crate::ip::v6::error::Error) -> Self {
+            crate::ip::error::Error::from(e).into()
+        }
+    }
+}
+
+use error::{Error, ErrorKind};
+
+/// View over an immutable slice of data that can be interpreted as a UDP datagram (RFC768)
+#[derive(Debug, Clone, Copy)]
+pub struct UdpDatagramView<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for UdpDatagramView<'a> {
+    type Error = Error;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::try_new(data)
+    }
+}
+
+impl<'a> UDPPacket for UdpDatagramView<'a> {
+    fn source_port(&self) -> u16 {
+        util::read_int!(self.data, u16, 0)
+    }
+
+    fn destination_port(&self) -> u16 {
+        util::read_int!(self.data, u16, 2)
+    }
+}
+
+impl<'a> UdpDatagramView<'a> {
+    pub fn try_new<T, U>(bytes: &'a T) -> Result<Self, Error>
+    where
+        T: AsRef<U> + ?Sized,
+        U: ?Sized + 'a,
+        &'a U: Into<&'a [u8]>,
+    {
+        let data: &'a [u8] = bytes.as_ref().into();
+        if data.len() < HEADER_LEN {
+            Err(Error::new(ErrorKind::NotEnoughData {
+                need: HEADER_LEN,
+                got: data.len(),
+            }))
+        } else {
+            Ok(Self { data })
+        }
+    }
+
+    /// Get the UDP datagram carried by an unfragmented Ip packet
+    pub fn try_from_ip(ip: &IpPacketView<'a>) -> Result<Self, Error> {
+        let (protocol, payload) = match ip {
+            IpPacketView::V4(v4) => {
+                if v4.is_fragmented() {
+                    return Err(Error::new(ErrorKind::Fragmented));
+                }
+                (v4.protocol(), v4.payload()?)
+            }
+            IpPacketView::V6(v6) => {
+                for header in v6.extension_headers() {
+                    let header = header?;
+                    // fragment offset and M flag of the fragment header
+                    if header.header_type() == v6::EXT_FRAGMENT
+                        && util::read_int!(header.data(), u16, 2) & 0xfff9 != 0
+                    {
+                        return Err(Error::new(ErrorKind::Fragmented));
+                    }
+                }
+                v6.upper_layer()?
+            }
+        };
+        if protocol != IP_PROTOCOL_UDP {
+            Err(Error::new(ErrorKind::NotUdp(protocol)))
+        } else {
+            Self::try_new(payload)
+        }
+    }
+
+    /// Get the value of the length field, including the header
+    pub fn length(&self) -> usize {
+        util::read_int!(self.data, u16, 4) as usize
+    }
+
+    /// Get the value of the checksum field. A value of 0 indicates that no checksum was calculated
+    pub fn checksum(&self) -> u16 {
+        util::read_int!(self.data, u16, 6)
+    }
+
+    /// Returns a datagram length that is guaranteed to be valid
+    fn valid_length(&self) -> Result<usize, Error> {
+        let len = self.length();
+        if len < HEADER_LEN {
+            Err(Error::new(ErrorKind::InvalidLength(len)))
+        } else if len > self.data.len() {
+            Err(Error::new(ErrorKind::NotEnoughData {
+                need: len,
+                got: self.data.len(),
+            }))
+        } else {
+            Ok(len)
+        }
+    }
+
+    /// Get the payload as limited by the length field. Returns an error if the length field is invalid
+    pub fn payload(&self) -> Result<&'a [u8], Error> {
+        self.valid_length().map(|len| &self.data[HEADER_LEN..len])
+    }
+
+    /// Compute the checksum over the datagram and the pseudo header built from the enclosing
+    /// Ip packet. The checksum field itself is ignored
+    pub fn compute_checksum(&self, ip: &IpPacketView) -> Result<u16, Error> {
+        let len = self.valid_length()?;
+        let [l0, l1, l2, l3] = (len as u32).to_be_bytes();
+        let data = &self.data[..len];
+        let sum = match ip {
+            IpPacketView::V4(v4) => {
+                let mut pseudo = [0u8; 12];
+                pseudo[0..4].copy_from_slice(&v4.source_addr().octets());
+                pseudo[4..8].copy_from_slice(&v4.dest_addr().octets());
+                pseudo[9] = IP_PROTOCOL_UDP;
+                pseudo[10..12].copy_from_slice(&[l2, l3]);
+                util::checksum::u16_concat(&[&pseudo, &data[..6], &data[HEADER_LEN..]])
+            }
+            IpPacketView::V6(v6) => {
+                let mut pseudo = [0u8; 40];
+                pseudo[0..16].copy_from_slice(&v6.source_addr().octets());
+                pseudo[16..32].copy_from_slice(&v6.dest_addr().octets());
+                pseudo[32..36].copy_from_slice(&[l0, l1, l2, l3]);
+                pseudo[39] = IP_PROTOCOL_UDP;
+                util::checksum::u16_concat(&[&pseudo, &data[..6], &data[HEADER_LEN..]])
+            }
+        };
+        // a computed checksum of 0 is transmitted as all ones
+        Ok(if sum == 0 { 0xffff } else { sum })
+    }
+
+    /// Verify the checksum using the pseudo header built from the enclosing Ip packet.
+    /// Datagrams without checksum are accepted over Ipv4 and rejected over Ipv6, where the
+    /// checksum is mandatory
+    pub fn verify_checksum(&self, ip: &IpPacketView) -> Result<bool, Error> {
+        match (self.checksum(), ip) {
+            (0, IpPacketView::V4(_)) => self.valid_length().map(|_| true),
+            (0, IpPacketView::V6(_)) => self.valid_length().map(|_| false),
+            (sum, ip) => self.compute_checksum(ip).map(|computed| computed == sum),
+        }
+    }
+}
+
+#[allow(unused)]
+mod test {
+    use super::*;
+
+    /// SSDP multicast datagram in an Ipv4 packet
+    const IP_UDP: [u8; 153] = [
+        // ip header
+        0x45, 0x00, 0x00, 0x99, 0x00, 0x00, 0x40, 0x00, 0x40, 0x11, 0x00, 0x00, 0xc0, 0xa8, 0x00,
+        0x78, 0xef, 0xff, 0xff, 0xfa, // udp
+        0x9d, 0xa2, 0x07, 0x6c, 0x00, 0x85, 0x7b, 0x31, 0x4d, 0x2d, 0x53, 0x45, 0x41, 0x52, 0x43,
+        0x48, 0x20, 0x2a, 0x20, 0x48, 0x54, 0x54, 0x50, 0x2f, 0x31, 0x2e, 0x31, 0x0d, 0x0a, 0x48,
+        0x4f, 0x53, 0x54, 0x3a, 0x20, 0x32, 0x33, 0x39, 0x2e, 0x32, 0x35, 0x35, 0x2e, 0x32, 0x35,
+        0x35, 0x2e, 0x32, 0x35, 0x30, 0x3a, 0x31, 0x39, 0x30, 0x30, 0x0d, 0x0a, 0x4d, 0x41, 0x4e,
+        0x3a, 0x20, 0x22, 0x73, 0x73, 0x64, 0x70, 0x3a, 0x64, 0x69, 0x73, 0x63, 0x6f, 0x76, 0x65,
+        0x72, 0x22, 0x0d, 0x0a, 0x4d, 0x58, 0x3a, 0x20, 0x31, 0x0d, 0x0a, 0x53, 0x54, 0x3a, 0x20,
+        0x75, 0x72, 0x6e, 0x3a, 0x64, 0x69, 0x61, 0x6c, 0x2d, 0x6d, 0x75, 0x6c, 0x74, 0x69, 0x73,
+        0x63, 0x72, 0x65, 0x65, 0x6e, 0x2d, 0x6f, 0x72, 0x67, 0x3a, 0x73, 0x65, 0x72, 0x76, 0x69,
+        0x63, 0x65, 0x3a, 0x64, 0x69, 0x61, 0x6c, 0x3a, 0x31, 0x0d, 0x0a, 0x0d, 0x0a,
+    ];
+
+    /// Ipv6 UDP packet from fe80::1 to ff02::fb with a hop-by-hop options header
+    const IP6_UDP: [u8; 60] = [
+        0x60, 0x00, 0x00, 0x00, 0x00, 0x14, 0x00, 0xff, 0xfe, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0xff, 0x02, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xfb, // hop-by-hop
+        0x11, 0x00, 0x01, 0x04, 0x00, 0x00, 0x00, 0x00, // udp
+        0x14, 0xe9, 0x14, 0xe9, 0x00, 0x0c, 0x00, 0x00, 0x01, 0x02, 0x03, 0x04,
+    ];
+
+    fn ip(data: &[u8]) -> IpPacketView<'_> {
+        IpPacketView::try_from(data).unwrap()
+    }
+
+    #[test]
+    fn ipv4() {
+        let ip = ip(&IP_UDP);
+        let udp = UdpDatagramView::try_from_ip(&ip).unwrap();
+        assert_eq!(udp.source_port(), 0x9da2);
+        assert_eq!(udp.destination_port(), 1900);
+        assert_eq!(udp.length(), 133);
+        assert_eq!(udp.checksum(), 0x7b31);
+        assert_eq!(udp.payload().unwrap(), &IP_UDP[28..]);
+        assert_eq!(udp.compute_checksum(&ip).unwrap(), 0x7b31);
+        assert!(udp.verify_checksum(&ip).unwrap());
+    }
+
+    #[test]
+    fn ipv4_corrupted() {
+        let mut data = IP_UDP;
+        data[40] ^= 0x01;
+        let ip = ip(&data);
+        let udp = UdpDatagramView::try_from_ip(&ip).unwrap();
+        assert!(!udp.verify_checksum(&ip).unwrap());
+        // without checksum, the datagram is accepted
+        data[26..28].fill(0);
+        let ip = IpPacketView::try_from(data.as_slice()).unwrap();
+        assert!(UdpDatagramView::try_from_ip(&ip)
+            .unwrap()
+            .verify_checksum(&ip)
+            .unwrap());
+    }
+
+    #[test]
+    fn ipv6() {
+        let mut data = IP6_UDP;
+        let udp = UdpDatagramView::try_from_ip(&ip(&data)).unwrap();
+        assert_eq!(udp.payload().unwrap(), [1, 2, 3, 4]);
+        // checksum is mandatory over ipv6
+        assert!(!udp.verify_checksum(&ip(&data)).unwrap());
+        let sum = udp.compute_checksum(&ip(&data)).unwrap();
+        data[54..56].copy_from_slice(&sum.to_be_bytes());
+        let ip = ip(&data);
+        let udp = UdpDatagramView::try_from_ip(&ip).unwrap();
+        assert!(udp.verify_checksum(&ip).unwrap());
+        // the checksum over the pseudo header, datagram and checksum field adds up to zero
+        let mut pseudo = [0u8; 40];
+        pseudo[..32].copy_from_slice(&data[8..40]);
+        pseudo[35] = 12;
+        pseudo[39] = IP_PROTOCOL_UDP;
+        assert_eq!(util::checksum::u16_concat(&[&pseudo, &data[48..]]), 0);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(UdpDatagramView::try_from([0u8; 7].as_slice()).is_err());
+        let udp = UdpDatagramView::try_from([0, 1, 0, 2, 0, 7, 0, 0].as_slice()).unwrap();
+        assert!(udp.payload().is_err());
+        let udp = UdpDatagramView::try_from([0, 1, 0, 2, 0, 9, 0, 0].as_slice()).unwrap();
+        assert!(udp.payload().is_err());
+        let mut data = IP_UDP;
+        data[9] = 6;
+        assert!(UdpDatagramView::try_from_ip(&ip(&data)).is_err());
+        let mut data = IP_UDP;
+        data[6] = 0x20;
+        assert!(UdpDatagramView::try_from_ip(&ip(&data)).is_err());
+    }
+}
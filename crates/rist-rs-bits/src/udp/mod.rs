@@ -1,4 +1,7 @@
-mod reduced;
+pub mod datagram;
+pub mod reduced;
+
+pub use datagram::UdpDatagramView;
 
 pub trait UDPPacket {
     fn source_port(&self) -> u16;
@@ -1,11 +1,107 @@
+use core::convert::TryFrom;
+
+use rist_rs_types::net::Ipv4Addr;
+
+use crate::ip::v4::{Ipv4PacketBuilder, Ipv4PacketView};
 use crate::util;
 
+use super::datagram::{HEADER_LEN as UDP_HEADER_LEN, IP_PROTOCOL_UDP};
 use super::UDPPacket;
 
-struct UDPReducedHeaderPacket<'a> {
+/// Length of the reduced overhead header (source and destination port)
+pub const HEADER_LEN: usize = 4;
+
+pub mod error {
+    use core::fmt::Display;
+
+    #[derive(Debug, Clone, Copy)]
+    pub enum ErrorKind {
+        /// Not enough data was supplied to read a header
+        NotEnoughData { need: usize, got: usize },
+
+        /// The buffer supplied for writing is too small
+        NotEnoughSpace { need: usize, got: usize },
+
+        /// The datagram does not fit into the length field of the UDP or Ipv4 header
+        PayloadTooLong(usize),
+
+        /// The Ipv4 packet does not carry UDP, holds the value of the protocol field
+        NotUdp(u8),
+
+        /// The Ipv4 packet is a fragment and can not be converted on its own
+        Fragmented,
+
+        /// The Ipv4 packet could not be read
+        Ipv4(crate::ip::v4::error::Error),
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct Error {
+        kind: ErrorKind,
+    }
+
+    impl Display for Error {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match &self.kind {
+                ErrorKind::NotEnoughData { need, got } => write!(
+                    f,
+                    "Not enough data to read header, need at least {need} bytes, got {got} bytes"
+                ),
+                ErrorKind::NotEnoughSpace { need, got } => write!(
+                    f,
+                    "Not enough space to write datagram, need {need} bytes, got {got} bytes"
+                ),
+                ErrorKind::PayloadTooLong(len) => {
+                    write!(f, "Payload of {len} bytes is too long for a UDP datagram")
+                }
+                ErrorKind::NotUdp(p) => write!(f, "Ipv4 packet carries protocol {p}, not UDP"),
+                ErrorKind::Fragmented => write!(f, "Ipv4 packet is fragmented"),
+                ErrorKind::Ipv4(e) => e.fmt(f),
+            }
+        }
+    }
+
+    impl Error {
+        pub(super) fn new(kind: ErrorKind) -> Self {
+            Self { kind }
+        }
+
+        /// Extract the error kind
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+
+    impl From<crate::ip::v4::error::Error> for Error {
+        fn from(e: crate::ip::v4::error::Error) -> Self {
+            Self::new(ErrorKind::Ipv4(e))
+        }
+    }
+
+    pub(super) fn not_enough_data(need: usize, got: usize) -> Error {
+        Error::new(ErrorKind::NotEnoughData { need, got })
+    }
+
+    pub(super) fn not_enough_space(need: usize, got: usize) -> Error {
+        Error::new(ErrorKind::NotEnoughSpace { need, got })
+    }
+}
+
+/// View over a packet with the reduced overhead header of the RIST Main Profile (VSF TR-06-2),
+/// which replaces the Ip and UDP headers with the source and destination port
+#[derive(Debug, Clone, Copy)]
+pub struct UDPReducedHeaderPacket<'a> {
     data: &'a [u8],
 }
 
+impl<'a> TryFrom<&'a [u8]> for UDPReducedHeaderPacket<'a> {
+    type Error = error::Error;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::try_new(data)
+    }
+}
+
 impl<'a> UDPPacket for UDPReducedHeaderPacket<'a> {
     fn source_port(&self) -> u16 {
         util::read_int!(self.data, u16, 0)
@@ -15,3 +111,206 @@ impl<'a> UDPPacket for UDPReducedHeaderPacket<'a> {
         util::read_int!(self.data, u16, 2)
     }
 }
+
+impl<'a> UDPReducedHeaderPacket<'a> {
+    pub fn try_new<T, U>(bytes: &'a T) -> Result<Self, error::Error>
+    where
+        T: AsRef<U> + ?Sized,
+        U: ?Sized + 'a,
+        &'a U: Into<&'a [u8]>,
+    {
+        let data: &'a [u8] = bytes.as_ref().into();
+        if data.len() < HEADER_LEN {
+            Err(error::not_enough_data(HEADER_LEN, data.len()))
+        } else {
+            Ok(Self { data })
+        }
+    }
+
+    /// Get the payload following the ports
+    pub fn payload(&self) -> &'a [u8] {
+        &self.data[HEADER_LEN..]
+    }
+
+    /// Length of the full UDP datagram this packet expands to
+    pub fn udp_datagram_len(&self) -> usize {
+        UDP_HEADER_LEN + self.payload().len()
+    }
+
+    /// Write the full UDP datagram carrying the payload into `buf`. The checksum is set to 0,
+    /// which marks it as unused when transported over Ipv4. Returns the number of bytes written
+    pub fn write_udp_datagram(&self, buf: &mut [u8]) -> Result<usize, error::Error> {
+        let len = self.udp_datagram_len();
+        if len > u16::MAX as usize {
+            return Err(error::Error::new(error::ErrorKind::PayloadTooLong(
+                self.payload().len(),
+            )));
+        }
+        if buf.len() < len {
+            return Err(error::not_enough_space(len, buf.len()));
+        }
+        buf[..HEADER_LEN].copy_from_slice(&self.data[..HEADER_LEN]);
+        buf[4..6].copy_from_slice(&(len as u16).to_be_bytes());
+        buf[6..8].fill(0);
+        buf[UDP_HEADER_LEN..len].copy_from_slice(self.payload());
+        Ok(len)
+    }
+
+    /// Write a full Ipv4/UDP datagram carrying the payload into `buf`. The Ipv4 header has
+    /// no options and the DF flag set. Returns the number of bytes written
+    pub fn write_ipv4_datagram(
+        &self,
+        source: Ipv4Addr,
+        destination: Ipv4Addr,
+        ttl: u8,
+        buf: &mut [u8],
+    ) -> Result<usize, error::Error> {
+        let ip = Ipv4PacketBuilder::new()
+            .with_df(true)
+            .with_ttl(ttl)
+            .with_protocol(IP_PROTOCOL_UDP)
+            .with_source_addr(source)
+            .with_dest_addr(destination);
+        let len = ip.packet_len(self.udp_datagram_len());
+        if len > u16::MAX as usize {
+            return Err(error::Error::new(error::ErrorKind::PayloadTooLong(
+                self.payload().len(),
+            )));
+        }
+        if buf.len() < len {
+            return Err(error::not_enough_space(len, buf.len()));
+        }
+        let header_len = ip
+            .write_header(self.udp_datagram_len(), buf)
+            .expect(rist_rs_types::internal::INTERNAL_ERR_PRE_VALIDATED);
+        let udp = &mut buf[header_len..len];
+        self.write_udp_datagram(udp)?;
+        Ok(len)
+    }
+}
+
+/// Write a packet with a reduced overhead header from the given ports and payload into `buf`.
+/// Returns the number of bytes written
+pub fn write(
+    source_port: u16,
+    destination_port: u16,
+    payload: &[u8],
+    buf: &mut [u8],
+) -> Result<usize, error::Error> {
+    let len = HEADER_LEN + payload.len();
+    if buf.len() < len {
+        return Err(error::not_enough_space(len, buf.len()));
+    }
+    buf[0..2].copy_from_slice(&source_port.to_be_bytes());
+    buf[2..4].copy_from_slice(&destination_port.to_be_bytes());
+    buf[HEADER_LEN..len].copy_from_slice(payload);
+    Ok(len)
+}
+
+/// Write a packet with a reduced overhead header from a full UDP datagram into `buf`.
+/// The payload is limited by the length field of the UDP header. Returns the number of bytes written
+pub fn write_from_udp_datagram(udp: &[u8], buf: &mut [u8]) -> Result<usize, error::Error> {
+    if udp.len() < UDP_HEADER_LEN {
+        return Err(error::not_enough_data(UDP_HEADER_LEN, udp.len()));
+    }
+    let udp_len = util::read_int!(udp, u16, 4) as usize;
+    if udp_len < UDP_HEADER_LEN || udp_len > udp.len() {
+        return Err(error::not_enough_data(
+            udp_len.max(UDP_HEADER_LEN),
+            udp.len(),
+        ));
+    }
+    write(
+        util::read_int!(udp, u16, 0),
+        util::read_int!(udp, u16, 2),
+        &udp[UDP_HEADER_LEN..udp_len],
+        buf,
+    )
+}
+
+/// Write a packet with a reduced overhead header from an unfragmented Ipv4 packet carrying UDP
+/// into `buf`. Returns the number of bytes written
+pub fn write_from_ipv4(ip: &Ipv4PacketView, buf: &mut [u8]) -> Result<usize, error::Error> {
+    if ip.protocol() != IP_PROTOCOL_UDP {
+        Err(error::Error::new(error::ErrorKind::NotUdp(ip.protocol())))
+    } else if ip.is_fragmented() {
+        Err(error::Error::new(error::ErrorKind::Fragmented))
+    } else {
+        write_from_udp_datagram(ip.payload()?, buf)
+    }
+}
+
+#[allow(unused)]
+mod test {
+    use super::*;
+    use crate::ip::v4::IPV4_CHECKSUM_FIELD;
+
+    const REDUCED: [u8; 8] = [0x07, 0xd0, 0x07, 0xd1, 0x80, 0x21, 0x00, 0x01];
+
+    #[test]
+    fn read() {
+        let packet = UDPReducedHeaderPacket::try_from(REDUCED.as_slice()).unwrap();
+        assert_eq!(packet.source_port(), 2000);
+        assert_eq!(packet.destination_port(), 2001);
+        assert_eq!(packet.payload(), [0x80, 0x21, 0x00, 0x01]);
+        assert!(UDPReducedHeaderPacket::try_from(&REDUCED[..3]).is_err());
+    }
+
+    #[test]
+    fn write_reduced() {
+        let mut buf = [0u8; 8];
+        assert_eq!(write(2000, 2001, &REDUCED[4..], &mut buf).unwrap(), 8);
+        assert_eq!(buf, REDUCED);
+        assert!(write(2000, 2001, &REDUCED[4..], &mut buf[..7]).is_err());
+    }
+
+    #[test]
+    fn udp_round_trip() {
+        let packet = UDPReducedHeaderPacket::try_from(REDUCED.as_slice()).unwrap();
+        let mut udp = [0u8; 12];
+        assert_eq!(packet.write_udp_datagram(&mut udp).unwrap(), 12);
+        assert_eq!(&udp[..8], [0x07, 0xd0, 0x07, 0xd1, 0x00, 0x0c, 0x00, 0x00]);
+        let mut buf = [0u8; 8];
+        assert_eq!(write_from_udp_datagram(&udp, &mut buf).unwrap(), 8);
+        assert_eq!(buf, REDUCED);
+    }
+
+    #[test]
+    fn ipv4_round_trip() {
+        let packet = UDPReducedHeaderPacket::try_from(REDUCED.as_slice()).unwrap();
+        let mut datagram = [0u8; 32];
+        let len = packet
+            .write_ipv4_datagram(
+                Ipv4Addr::from([192, 168, 0, 1]),
+                Ipv4Addr::from([239, 0, 0, 1]),
+                64,
+                &mut datagram,
+            )
+            .unwrap();
+        assert_eq!(len, 32);
+        let ip = Ipv4PacketView::try_from(datagram.as_slice()).unwrap();
+        assert_eq!(ip.protocol(), IP_PROTOCOL_UDP);
+        assert_eq!(ip.total_len(), 32);
+        assert_eq!(ip.ttl(), 64);
+        assert!(ip.is_multicast());
+        assert_eq!(
+            util::checksum::u16(&datagram[..20], &[IPV4_CHECKSUM_FIELD]),
+            ip.checksum()
+        );
+        let mut buf = [0u8; 8];
+        assert_eq!(write_from_ipv4(&ip, &mut buf).unwrap(), 8);
+        assert_eq!(buf, REDUCED);
+    }
+
+    #[test]
+    fn ipv4_not_udp() {
+        let mut datagram = [0u8; 32];
+        UDPReducedHeaderPacket::try_from(REDUCED.as_slice())
+            .unwrap()
+            .write_ipv4_datagram(Ipv4Addr::from(0), Ipv4Addr::from(0), 1, &mut datagram)
+            .unwrap();
+        datagram[9] = 6;
+        let ip = Ipv4PacketView::try_from(datagram.as_slice()).unwrap();
+        assert!(write_from_ipv4(&ip, &mut [0u8; 8]).is_err());
+    }
+}
@@ -1,35 +1,161 @@
-/// Dirty and slow implementation of 16-bit checksum calculation
-pub fn u16(buf: &[u8], skip: Option<usize>) -> u16 {
-    let mut result = 0u32;
-    for (pos, byte) in buf.iter().enumerate() {
-        if pos % 2 == 0 {
-            if let Some(s) = skip {
-                if s == pos {
-                    continue;
-                }
-            }
-            if pos + 1 < buf.len() {
-                result += u32::from(u16::from_be_bytes(
-                    buf[pos..pos + 2]
-                        .try_into()
-                        .expect("invalid range returned from slice"),
-                ));
-            } else {
-                result += u32::from((*byte as u16) << 8)
-            }
-            if result > 0xffff {
-                result -= 0xffff
+use core::ops::Range;
+
+/// Running one's complement sum over 16-bit big endian words (RFC1071). Data can be added in pieces
+/// of any length, an odd byte at the end of a piece is combined with the first byte of the next.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Checksum {
+    sum: u64,
+    pending: Option<u8>,
+}
+
+impl Checksum {
+    /// Create a new, empty checksum
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `v` to the 64-bit accumulator with end-around carry. As 2^64 is congruent to 1
+    /// modulo 0xffff, this keeps the folded sum correct for input of any length
+    fn add_carry(sum: u64, v: u64) -> u64 {
+        let (sum, carry) = sum.overflowing_add(v);
+        sum + carry as u64
+    }
+
+    /// Sum up the 16-bit words of `data`, which must have an even length. Reads 8 bytes at a
+    /// time and adds both 32-bit halves
+    fn sum_words(data: &[u8]) -> u64 {
+        let mut chunks = data.chunks_exact(8);
+        let mut sum = 0u64;
+        for chunk in chunks.by_ref() {
+            let v = u64::from_be_bytes(
+                chunk
+                    .try_into()
+                    .expect(rist_rs_types::internal::INTERNAL_ERR_PRE_VALIDATED),
+            );
+            sum = Self::add_carry(sum, (v >> 32) + (v & 0xffff_ffff));
+        }
+        for word in chunks.remainder().chunks_exact(2) {
+            sum = Self::add_carry(sum, u16::from_be_bytes([word[0], word[1]]) as u64);
+        }
+        sum
+    }
+
+    /// Add `data` to the sum
+    pub fn add(&mut self, mut data: &[u8]) -> &mut Self {
+        if data.is_empty() {
+            return self;
+        }
+        if let Some(high) = self.pending.take() {
+            self.sum = Self::add_carry(self.sum, u16::from_be_bytes([high, data[0]]) as u64);
+            data = &data[1..];
+        }
+        let even = data.len() & !1;
+        self.sum = Self::add_carry(self.sum, Self::sum_words(&data[..even]));
+        if even < data.len() {
+            self.pending = Some(data[even]);
+        }
+        self
+    }
+
+    /// Add a single 16-bit word to the sum. Must not be called while an odd number of bytes was added
+    pub fn add_u16(&mut self, word: u16) -> &mut Self {
+        debug_assert!(self.pending.is_none());
+        self.sum = Self::add_carry(self.sum, word as u64);
+        self
+    }
+
+    /// Add `len` zero bytes to the sum. Only the alignment of following data is affected
+    pub fn add_zeros(&mut self, len: usize) -> &mut Self {
+        if len == 0 {
+            return self;
+        }
+        let len = match self.pending.take() {
+            Some(high) => {
+                self.sum = Self::add_carry(self.sum, (high as u64) << 8);
+                len - 1
             }
+            None => len,
+        };
+        if len % 2 == 1 {
+            self.pending = Some(0);
         }
+        self
     }
 
-    !result as u16
+    /// Get the folded one's complement sum, without the final complement
+    pub fn sum(&self) -> u16 {
+        let mut sum = Self::add_carry(
+            self.sum,
+            self.pending.map(|high| (high as u64) << 8).unwrap_or(0),
+        );
+        while sum >> 16 != 0 {
+            sum = (sum & 0xffff) + (sum >> 16);
+        }
+        sum as u16
+    }
+
+    /// Get the checksum, the one's complement of the sum
+    pub fn finish(&self) -> u16 {
+        !self.sum()
+    }
+}
+
+/// 16-bit checksum over `buf`. Bytes within the `skip` ranges are treated as zero, which is
+/// used to exclude the checksum field itself. Ranges must be in ascending order and must
+/// not overlap
+pub fn u16(buf: &[u8], skip: &[Range<usize>]) -> u16 {
+    let mut checksum = Checksum::new();
+    let mut pos = 0;
+    for range in skip {
+        let start = range.start.clamp(pos, buf.len());
+        let end = range.end.clamp(start, buf.len());
+        checksum.add(&buf[pos..start]).add_zeros(end - start);
+        pos = end;
+    }
+    checksum.add(&buf[pos..]).finish()
+}
+
+/// 16-bit checksum over the concatenation of `parts`, without copying them into a single buffer
+pub fn u16_concat(parts: &[&[u8]]) -> u16 {
+    parts
+        .iter()
+        .fold(Checksum::new(), |mut checksum, part| {
+            checksum.add(part);
+            checksum
+        })
+        .finish()
+}
+
+/// Incrementally update `checksum` after the 16-bit aligned field `old` was replaced
+/// by `new` (RFC1624, eqn. 3). Both fields must have the same, even length
+pub fn update(checksum: u16, old: &[u8], new: &[u8]) -> u16 {
+    debug_assert_eq!(old.len(), new.len());
+    debug_assert_eq!(old.len() % 2, 0);
+    let mut sum = Checksum::new();
+    sum.add_u16(!checksum);
+    for word in old.chunks_exact(2) {
+        sum.add_u16(!u16::from_be_bytes([word[0], word[1]]));
+    }
+    sum.add(new);
+    sum.finish()
+}
+
+/// Incrementally update `checksum` after a single 16-bit word changed from `old` to `new` (RFC1624)
+pub fn update_u16(checksum: u16, old: u16, new: u16) -> u16 {
+    update(checksum, &old.to_be_bytes(), &new.to_be_bytes())
 }
 
 #[allow(unused)]
 mod test {
 
-    use super::u16;
+    use super::{u16, Checksum};
+    use core::ops::Range;
+
+    /// Position of the checksum field in the Ipv4 header
+    const IPV4_CHECKSUM: Range<usize> = 10..12;
+
+    /// Position of the checksum field in the udp datagram including the pseudo header
+    const UDP_CHECKSUM: Range<usize> = 18..20;
 
     #[test]
     fn test() {
@@ -51,8 +177,104 @@ mod test {
         ];
 
         assert_eq!(
-            super::u16(&bytes, Some(18)),
+            super::u16(&bytes, &[UDP_CHECKSUM]),
             u16::from_be_bytes(bytes[18..20].try_into().unwrap())
         );
     }
+
+    /// Straightforward reference implementation
+    fn reference(buf: &[u8]) -> u16 {
+        let mut sum = 0u32;
+        for word in buf.chunks(2) {
+            sum += u32::from(word[0]) << 8 | u32::from(*word.get(1).unwrap_or(&0));
+            if sum > 0xffff {
+                sum -= 0xffff;
+            }
+        }
+        !sum as u16
+    }
+
+    #[cfg(test)]
+    fn pseudo_random(len: usize, seed: u32) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1103515245).wrapping_add(12345);
+                (state >> 16) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_reference() {
+        for len in [0, 1, 2, 3, 7, 8, 9, 15, 16, 17, 64, 1499, 1500, 9000] {
+            let data = pseudo_random(len, len as u32);
+            assert_eq!(super::u16(&data, &[]), reference(&data), "len {len}");
+        }
+        let ones = [0xff; 4096];
+        assert_eq!(super::u16(&ones, &[]), reference(&ones));
+    }
+
+    #[test]
+    fn accumulator_overflow() {
+        // as if about 16 GiB of 0xff had been added before
+        let mut checksum = Checksum {
+            sum: u64::MAX - 0xfffe,
+            pending: None,
+        };
+        checksum.add(&[0xff; 16]);
+        let mut sum = u64::MAX as u128 - 0xfffe + 8 * 0xffff;
+        while sum >> 16 != 0 {
+            sum = (sum & 0xffff) + (sum >> 16);
+        }
+        assert_eq!(checksum.sum(), sum as u16);
+    }
+
+    #[test]
+    fn skip_ranges() {
+        let data = pseudo_random(101, 7);
+        let mut zeroed = data.clone();
+        zeroed[3..6].fill(0);
+        zeroed[10..12].fill(0);
+        zeroed[100..].fill(0);
+        assert_eq!(
+            super::u16(&data, &[3..6, 10..12, 100..200]),
+            reference(&zeroed)
+        );
+    }
+
+    #[test]
+    fn concat() {
+        let data = pseudo_random(33, 3);
+        assert_eq!(
+            super::u16_concat(&[&data[..3], &data[3..4], &[], &data[4..17], &data[17..]]),
+            reference(&data)
+        );
+    }
+
+    #[test]
+    fn incremental_update() {
+        // Ipv4 header from a real packet, rewrite TTL and destination address
+        let mut header = [
+            0x45, 0x00, 0x00, 0x34, 0x61, 0x5f, 0x40, 0x00, 0x30, 0x06, 0xd2, 0xa3, 0x54, 0xf6,
+            0xfb, 0xc9, 0xc0, 0xa8, 0x05, 0x59,
+        ];
+        assert_eq!(super::u16(&header, &[IPV4_CHECKSUM]), 0xd2a3);
+        let old = u16::from_be_bytes([header[8], header[9]]);
+        header[8] -= 1;
+        let new = u16::from_be_bytes([header[8], header[9]]);
+        let sum = super::update_u16(0xd2a3, old, new);
+        assert_eq!(sum, super::u16(&header, &[IPV4_CHECKSUM]));
+        let old_addr = [header[16], header[17], header[18], header[19]];
+        header[16..20].copy_from_slice(&[10, 0, 0, 1]);
+        let sum = super::update(sum, &old_addr, &header[16..20]);
+        assert_eq!(sum, super::u16(&header, &[IPV4_CHECKSUM]));
+    }
+
+    #[test]
+    fn incremental_update_negative_zero() {
+        // RFC1624 example where eqn. 2 would yield 0x0000 instead of 0xffff
+        let sum = super::update_u16(0xdd2f, 0x5555, 0x3285);
+        assert_eq!(sum, 0x0000);
+    }
 }
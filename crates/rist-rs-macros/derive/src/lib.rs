@@ -0,0 +1,592 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, spanned::Spanned, Attribute, Data, DeriveInput, Error, Expr, Fields, Ident,
+    Lit, LitInt, Result, Token, Type,
+};
+
+/// Derive a read view, a mutable view and a builder from a declarative packet header layout.
+///
+/// Every field of the struct needs a `#[field(offset = N)]` annotation with the byte offset of
+/// the field in the header. Supported field types are `u8`, `u16`, `u32`, `u64`, `bool` and
+/// `[u8; N]`. Integers are read in network byte order. Integer fields can be limited to a range
+/// of bits with `bits = a..b`, counting from the most significant bit of the integer at the
+/// offset. Boolean fields require a single bit, `bits = n`, counting from the most significant
+/// bit of the byte at the offset.
+///
+/// The generated types are named after the struct with a `View`, `ViewMut` and `Builder`
+/// suffix. The names can be changed with `#[packet(view = A, view_mut = B, builder = C)]`.
+///
+/// ```ignore
+/// #[derive(PacketView)]
+/// pub struct Gre {
+///     /// Checksum present
+///     #[field(offset = 0, bits = 0)]
+///     checksum_present: bool,
+///     #[field(offset = 0, bits = 13..16)]
+///     version: u16,
+///     #[field(offset = 2)]
+///     protocol_type: u16,
+/// }
+/// ```
+#[proc_macro_derive(PacketView, attributes(packet, field))]
+pub fn derive_packet_view(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Supported field types
+enum Kind {
+    Bool,
+    Uint { ty: Ident, size: usize },
+    Array(usize),
+}
+
+/// A field of the layout
+struct Field {
+    ident: Ident,
+    ty: Type,
+    kind: Kind,
+    offset: usize,
+    bits: Option<(usize, usize)>,
+    docs: Vec<Attribute>,
+}
+
+impl Field {
+    fn parse(field: &syn::Field) -> Result<Self> {
+        let ident = field
+            .ident
+            .clone()
+            .ok_or_else(|| Error::new(field.span(), "tuple structs are not supported"))?;
+        let kind = Kind::parse(&field.ty)?;
+        let mut offset = None;
+        let mut bits = None;
+        let mut docs = vec![];
+        for attr in &field.attrs {
+            if attr.path().is_ident("doc") {
+                docs.push(attr.clone());
+            } else if attr.path().is_ident("field") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("offset") {
+                        offset = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+                        Ok(())
+                    } else if meta.path.is_ident("bits") {
+                        let input = meta.value()?;
+                        let start = input.parse::<LitInt>()?.base10_parse()?;
+                        let end = if input.peek(Token![..]) {
+                            input.parse::<Token![..]>()?;
+                            input.parse::<LitInt>()?.base10_parse()?
+                        } else {
+                            start + 1
+                        };
+                        bits = Some((start, end));
+                        Ok(())
+                    } else {
+                        Err(meta.error("expected `offset` or `bits`"))
+                    }
+                })?;
+            }
+        }
+        let offset = offset.ok_or_else(|| {
+            Error::new(ident.span(), "missing `#[field(offset = ...)]` annotation")
+        })?;
+        let field = Self {
+            ident,
+            ty: field.ty.clone(),
+            kind,
+            offset,
+            bits,
+            docs,
+        };
+        field.validate()?;
+        Ok(field)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let err = |msg| Err(Error::new(self.ident.span(), msg));
+        match (&self.kind, self.bits) {
+            (Kind::Bool, None) => err("boolean fields need a `bits = n` annotation"),
+            (Kind::Bool, Some((start, end))) if end != start + 1 => {
+                err("boolean fields must occupy a single bit")
+            }
+            (Kind::Uint { size, .. }, Some((start, end))) if start >= end || end > size * 8 => {
+                err("bit range exceeds the field type")
+            }
+            (Kind::Array(_), Some(_)) => err("array fields can not be limited to bits"),
+            _ => Ok(()),
+        }
+    }
+
+    /// Offset of the first byte following the field
+    fn end(&self) -> usize {
+        match (&self.kind, self.bits) {
+            (Kind::Bool, Some((bit, _))) => self.offset + bit / 8 + 1,
+            (Kind::Uint { size, .. }, _) => self.offset + size,
+            (Kind::Array(len), _) => self.offset + len,
+            (Kind::Bool, None) => unreachable!(),
+        }
+    }
+
+    /// Bit range as shift and unshifted mask, `None` if the field covers the whole integer
+    fn shift_and_mask(&self) -> Option<(usize, u64)> {
+        let (start, end) = self.bits?;
+        let size = match self.kind {
+            Kind::Uint { size, .. } => size,
+            _ => return None,
+        };
+        let len = end - start;
+        (len < size * 8).then(|| (size * 8 - end, (1u64 << len) - 1))
+    }
+
+    fn getter(&self) -> TokenStream2 {
+        let ident = &self.ident;
+        let ty = &self.ty;
+        let docs = if self.docs.is_empty() {
+            let doc = format!("Get the `{ident}` field");
+            quote!(#[doc = #doc])
+        } else {
+            let docs = &self.docs;
+            quote!(#(#docs)*)
+        };
+        let body = match &self.kind {
+            Kind::Bool => {
+                let (byte, mask) = self.bool_position();
+                quote!((self.data[#byte] & #mask) != 0)
+            }
+            Kind::Uint { ty, size } => {
+                let read = read_uint(ty, self.offset, *size);
+                match self.shift_and_mask() {
+                    Some((shift, mask)) => {
+                        let mask = uint_literal(ty, mask);
+                        quote!((#read >> #shift) & #mask)
+                    }
+                    None => read,
+                }
+            }
+            Kind::Array(len) => {
+                let (start, end) = (self.offset, self.end());
+                quote! {
+                    let mut b = [0u8; #len];
+                    b.copy_from_slice(&self.data[#start..#end]);
+                    b
+                }
+            }
+        };
+        quote! {
+            #docs
+            pub fn #ident(&self) -> #ty {
+                #body
+            }
+        }
+    }
+
+    /// Whether the setter can fail because the value exceeds the bits of the field
+    fn is_fallible(&self) -> bool {
+        self.shift_and_mask().is_some()
+    }
+
+    fn setter(&self) -> TokenStream2 {
+        let ident = &self.ident;
+        let name = ident.to_string();
+        let setter = format_ident!("set_{}", ident);
+        let ty = &self.ty;
+        let (start, end) = (self.offset, self.end());
+        match &self.kind {
+            Kind::Bool => {
+                let (byte, mask) = self.bool_position();
+                let doc = format!("Set the `{name}` field");
+                quote! {
+                    #[doc = #doc]
+                    pub fn #setter(&mut self, value: bool) {
+                        if value {
+                            self.data[#byte] |= #mask;
+                        } else {
+                            self.data[#byte] &= !#mask;
+                        }
+                    }
+                }
+            }
+            Kind::Uint { ty: uint, size } => match self.shift_and_mask() {
+                Some((shift, mask)) => {
+                    let bits = mask.count_ones();
+                    let doc = format!(
+                        "Set the `{name}` field. Returns an error if the value does not fit into {bits} bits"
+                    );
+                    let read = read_uint(uint, self.offset, *size);
+                    let clear = uint_literal(uint, !(mask << shift));
+                    let check = self.check(quote!(value));
+                    quote! {
+                        #[doc = #doc]
+                        pub fn #setter(
+                            &mut self,
+                            value: #ty,
+                        ) -> Result<(), ::rist_rs_macros::packet::error::Error> {
+                            #check
+                            let word = (#read & #clear) | (value << #shift);
+                            self.data[#start..#end].copy_from_slice(&word.to_be_bytes());
+                            Ok(())
+                        }
+                    }
+                }
+                None => {
+                    let doc = format!("Set the `{name}` field");
+                    quote! {
+                        #[doc = #doc]
+                        pub fn #setter(&mut self, value: #ty) {
+                            self.data[#start..#end].copy_from_slice(&value.to_be_bytes());
+                        }
+                    }
+                }
+            },
+            Kind::Array(_) => {
+                let doc = format!("Set the `{name}` field");
+                quote! {
+                    #[doc = #doc]
+                    pub fn #setter(&mut self, value: #ty) {
+                        self.data[#start..#end].copy_from_slice(&value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Statement returning an error if `value` does not fit into the bits of the field. Empty
+    /// for fields that can hold any value of their type
+    fn check(&self, value: TokenStream2) -> TokenStream2 {
+        let (ty, mask) = match (&self.kind, self.shift_and_mask()) {
+            (Kind::Uint { ty, .. }, Some((_, mask))) => (ty, mask),
+            _ => return quote!(),
+        };
+        let name = self.ident.to_string();
+        let max = uint_literal(ty, mask);
+        quote! {
+            if #value > #max {
+                return Err(::rist_rs_macros::packet::error::Error::new(
+                    ::rist_rs_macros::packet::error::ErrorKind::InvalidValue {
+                        field: #name,
+                        value: u64::from(#value),
+                    },
+                ));
+            }
+        }
+    }
+
+    /// Zero value of the field type
+    fn zero(&self) -> TokenStream2 {
+        match &self.kind {
+            Kind::Bool => quote!(false),
+            Kind::Uint { .. } => quote!(0),
+            Kind::Array(len) => quote!([0u8; #len]),
+        }
+    }
+
+    fn with(&self) -> TokenStream2 {
+        let ident = &self.ident;
+        let ty = &self.ty;
+        let with = format_ident!("with_{}", ident);
+        let doc = format!("Set the `{ident}` field");
+        quote! {
+            #[doc = #doc]
+            pub fn #with(mut self, #ident: #ty) -> Self {
+                self.#ident = #ident;
+                self
+            }
+        }
+    }
+
+    /// Byte index and mask of a boolean field
+    fn bool_position(&self) -> (usize, Literal) {
+        let (bit, _) = self.bits.unwrap_or_default();
+        (
+            self.offset + bit / 8,
+            Literal::u8_suffixed(0x80 >> (bit % 8)),
+        )
+    }
+}
+
+impl Kind {
+    fn parse(ty: &Type) -> Result<Self> {
+        let err = || {
+            Error::new(
+                ty.span(),
+                "expected one of `u8`, `u16`, `u32`, `u64`, `bool` or `[u8; N]`",
+            )
+        };
+        match ty {
+            Type::Path(path) => {
+                let ident = path.path.get_ident().ok_or_else(err)?;
+                let size = match ident.to_string().as_str() {
+                    "bool" => return Ok(Kind::Bool),
+                    "u8" => 1,
+                    "u16" => 2,
+                    "u32" => 4,
+                    "u64" => 8,
+                    _ => return Err(err()),
+                };
+                Ok(Kind::Uint {
+                    ty: ident.clone(),
+                    size,
+                })
+            }
+            Type::Array(array) => match (&*array.elem, &array.len) {
+                (Type::Path(elem), Expr::Lit(len)) if elem.path.is_ident("u8") => match &len.lit {
+                    Lit::Int(len) => Ok(Kind::Array(len.base10_parse()?)),
+                    _ => Err(err()),
+                },
+                _ => Err(err()),
+            },
+            _ => Err(err()),
+        }
+    }
+}
+
+/// Expression reading an integer in network byte order from `self.data`
+fn read_uint(ty: &Ident, offset: usize, size: usize) -> TokenStream2 {
+    let end = offset + size;
+    quote! {{
+        let mut b = [0u8; #size];
+        b.copy_from_slice(&self.data[#offset..#end]);
+        #ty::from_be_bytes(b)
+    }}
+}
+
+/// Integer literal with the suffix of the field type, truncated to its width
+fn uint_literal(ty: &Ident, value: u64) -> Literal {
+    match ty.to_string().as_str() {
+        "u8" => Literal::u8_suffixed(value as u8),
+        "u16" => Literal::u16_suffixed(value as u16),
+        "u32" => Literal::u32_suffixed(value as u32),
+        _ => Literal::u64_suffixed(value),
+    }
+}
+
+/// Names of the generated types from `#[packet(...)]`
+fn names(input: &DeriveInput) -> Result<(Ident, Ident, Ident)> {
+    let ident = &input.ident;
+    let mut view = format_ident!("{}View", ident);
+    let mut view_mut = format_ident!("{}ViewMut", ident);
+    let mut builder = format_ident!("{}Builder", ident);
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("packet")) {
+        attr.parse_nested_meta(|meta| {
+            let name = if meta.path.is_ident("view") {
+                &mut view
+            } else if meta.path.is_ident("view_mut") {
+                &mut view_mut
+            } else if meta.path.is_ident("builder") {
+                &mut builder
+            } else {
+                return Err(meta.error("expected `view`, `view_mut` or `builder`"));
+            };
+            *name = meta.value()?.parse()?;
+            Ok(())
+        })?;
+    }
+    Ok((view, view_mut, builder))
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(fields) => fields
+                .named
+                .iter()
+                .map(Field::parse)
+                .collect::<Result<Vec<_>>>()?,
+            _ => {
+                return Err(Error::new(
+                    input.span(),
+                    "expected a struct with named fields",
+                ))
+            }
+        },
+        _ => return Err(Error::new(input.span(), "expected a struct")),
+    };
+    let (view, view_mut, builder) = names(&input)?;
+    let ident = &input.ident;
+    let vis = &input.vis;
+    let header_len = fields.iter().map(Field::end).max().unwrap_or(0);
+    let idents = fields.iter().map(|f| &f.ident).collect::<Vec<_>>();
+    let types = fields.iter().map(|f| &f.ty).collect::<Vec<_>>();
+    let getters = fields.iter().map(Field::getter);
+    let setters = fields.iter().map(Field::setter);
+    let withs = fields.iter().map(Field::with);
+    let zeros = fields.iter().map(Field::zero);
+    let checks = fields.iter().map(|f| {
+        let ident = &f.ident;
+        f.check(quote!(self.#ident))
+    });
+    let writes = fields.iter().map(|f| {
+        let ident = &f.ident;
+        let setter = format_ident!("set_{}", ident);
+        if f.is_fallible() {
+            quote!(view.#setter(self.#ident)?;)
+        } else {
+            quote!(view.#setter(self.#ident);)
+        }
+    });
+    let view_doc = format!(
+        "View over an immutable slice of data that can be interpreted as a [`{ident}`] header"
+    );
+    let view_mut_doc = format!(
+        "View over a mutable slice of data that can be interpreted as a [`{ident}`] header"
+    );
+    let builder_doc = format!("Builder for packets with a [`{ident}`] header");
+
+    Ok(quote! {
+        #[doc = #view_doc]
+        #[derive(Debug, Clone, Copy)]
+        #vis struct #view<'a> {
+            data: &'a [u8],
+        }
+
+        impl<'a> TryFrom<&'a [u8]> for #view<'a> {
+            type Error = ::rist_rs_macros::packet::error::Error;
+
+            fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+                Self::try_new(data)
+            }
+        }
+
+        impl<'a> #view<'a> {
+            /// Length of the header
+            pub const HEADER_LEN: usize = #header_len;
+
+            pub fn try_new<T, U>(bytes: &'a T) -> Result<Self, ::rist_rs_macros::packet::error::Error>
+            where
+                T: AsRef<U> + ?Sized,
+                U: ?Sized + 'a,
+                &'a U: Into<&'a [u8]>,
+            {
+                let data: &'a [u8] = bytes.as_ref().into();
+                if data.len() < Self::HEADER_LEN {
+                    Err(::rist_rs_macros::packet::error::Error::new(
+                        ::rist_rs_macros::packet::error::ErrorKind::NotEnoughData {
+                            need: Self::HEADER_LEN,
+                            got: data.len(),
+                        },
+                    ))
+                } else {
+                    Ok(Self { data })
+                }
+            }
+
+            #(#getters)*
+
+            /// Get the data following the header
+            pub fn payload(&self) -> &'a [u8] {
+                &self.data[Self::HEADER_LEN..]
+            }
+
+            /// Read all fields of the header
+            pub fn decode(&self) -> #ident {
+                #ident {
+                    #(#idents: self.#idents(),)*
+                }
+            }
+        }
+
+        #[doc = #view_mut_doc]
+        #[derive(Debug)]
+        #vis struct #view_mut<'a> {
+            data: &'a mut [u8],
+        }
+
+        impl<'a> TryFrom<&'a mut [u8]> for #view_mut<'a> {
+            type Error = ::rist_rs_macros::packet::error::Error;
+
+            fn try_from(data: &'a mut [u8]) -> Result<Self, Self::Error> {
+                #view::try_from(&*data)?;
+                Ok(Self { data })
+            }
+        }
+
+        impl<'a> From<#view_mut<'a>> for #view<'a> {
+            fn from(view: #view_mut<'a>) -> Self {
+                #view { data: view.data }
+            }
+        }
+
+        impl<'a> #view_mut<'a> {
+            /// Get a read-only view over the packet
+            pub fn as_view(&self) -> #view<'_> {
+                #view { data: self.data }
+            }
+
+            #(#setters)*
+
+            /// Get the data following the header for modification
+            pub fn payload_mut(&mut self) -> &mut [u8] {
+                &mut self.data[#view::HEADER_LEN..]
+            }
+        }
+
+        #[doc = #builder_doc]
+        #[derive(Debug, Clone, Copy)]
+        #vis struct #builder {
+            #(#idents: #types,)*
+        }
+
+        impl Default for #builder {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl #builder {
+            /// Create a new builder with all fields set to zero
+            pub fn new() -> Self {
+                Self {
+                    #(#idents: #zeros,)*
+                }
+            }
+
+            #(#withs)*
+
+            /// Length of the header
+            pub fn header_len(&self) -> usize {
+                #view::HEADER_LEN
+            }
+
+            /// Length of the packet carrying `payload`
+            pub fn packet_len(&self, payload: &[u8]) -> usize {
+                self.header_len() + payload.len()
+            }
+
+            /// Check that all values fit into their fields
+            fn validate(&self) -> Result<(), ::rist_rs_macros::packet::error::Error> {
+                #(#checks)*
+                Ok(())
+            }
+
+            /// Write the header followed by `payload` into `buf`. Returns the number of bytes
+            /// written or an error if `buf` is too small or a value does not fit into its field.
+            /// `buf` is left untouched on error
+            pub fn write(
+                &self,
+                payload: &[u8],
+                buf: &mut [u8],
+            ) -> Result<usize, ::rist_rs_macros::packet::error::Error> {
+                self.validate()?;
+                let len = self.packet_len(payload);
+                if buf.len() < len {
+                    return Err(::rist_rs_macros::packet::error::Error::new(
+                        ::rist_rs_macros::packet::error::ErrorKind::NotEnoughSpace {
+                            need: len,
+                            got: buf.len(),
+                        },
+                    ));
+                }
+                buf[..#view::HEADER_LEN].fill(0);
+                let mut view = #view_mut {
+                    data: &mut buf[..len],
+                };
+                #(#writes)*
+                buf[#view::HEADER_LEN..len].copy_from_slice(payload);
+                Ok(len)
+            }
+        }
+    })
+}
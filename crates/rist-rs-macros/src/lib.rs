@@ -1 +1,7 @@
+#![no_std]
+extern crate self as rist_rs_macros;
+
 pub mod features;
+pub mod packet;
+
+pub use rist_rs_macros_derive::PacketView;
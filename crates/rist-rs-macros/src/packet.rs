@@ -0,0 +1,238 @@
+//! Support code for views generated with [`PacketView`](crate::PacketView)
+
+pub mod error {
+    use core::fmt::Display;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ErrorKind {
+        /// Not enough data was supplied to read the header
+        NotEnoughData { need: usize, got: usize },
+
+        /// The buffer supplied for writing a packet is too small
+        NotEnoughSpace { need: usize, got: usize },
+
+        /// The value does not fit into the bits of the field
+        InvalidValue { field: &'static str, value: u64 },
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct Error {
+        kind: ErrorKind,
+    }
+
+    impl Display for Error {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            match &self.kind {
+                ErrorKind::NotEnoughData { need, got } => write!(
+                    f,
+                    "Not enough data to read header, need at least {need} bytes, got {got} bytes"
+                ),
+                ErrorKind::NotEnoughSpace { need, got } => write!(
+                    f,
+                    "Not enough space to write packet, need {need} bytes, got {got} bytes"
+                ),
+                ErrorKind::InvalidValue { field, value } => {
+                    write!(f, "Value {value} does not fit into field {field}")
+                }
+            }
+        }
+    }
+
+    impl Error {
+        /// Create a new error. Used by the generated code
+        pub fn new(kind: ErrorKind) -> Self {
+            Self { kind }
+        }
+
+        /// Extract the error kind
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+}
+
+#[allow(unused)]
+mod test {
+    use super::error::ErrorKind;
+    use crate::PacketView;
+
+    /// Fixed RTP header (RFC3550 5.1)
+    #[derive(PacketView, Debug, PartialEq, Eq)]
+    #[packet(view = RtpView, view_mut = RtpViewMut, builder = RtpBuilder)]
+    pub struct Rtp {
+        /// RTP protocol version
+        #[field(offset = 0, bits = 0..2)]
+        version: u8,
+        #[field(offset = 0, bits = 2)]
+        padding: bool,
+        #[field(offset = 0, bits = 3)]
+        extension: bool,
+        #[field(offset = 0, bits = 4..8)]
+        csrc_count: u8,
+        #[field(offset = 1, bits = 0)]
+        marker: bool,
+        #[field(offset = 1, bits = 1..8)]
+        payload_type: u8,
+        #[field(offset = 2)]
+        sequence_number: u16,
+        #[field(offset = 4)]
+        timestamp: u32,
+        #[field(offset = 8)]
+        ssrc: u32,
+    }
+
+    /// Layout with fields spanning byte boundaries
+    #[derive(PacketView)]
+    pub struct Flags {
+        #[field(offset = 0, bits = 9)]
+        flag: bool,
+        #[field(offset = 2, bits = 3..16)]
+        offset: u16,
+        #[field(offset = 4)]
+        mac: [u8; 6],
+    }
+
+    /// Layout with an array longer than 32 bytes
+    #[derive(PacketView)]
+    pub struct Digest {
+        #[field(offset = 0)]
+        kind: u8,
+        #[field(offset = 1)]
+        digest: [u8; 40],
+    }
+
+    const SOME_PACKET: [u8; 14] = [
+        0x80, 0x21, 0x23, 0x6c, 0x5b, 0x68, 0x20, 0x88, 0xb3, 0x59, 0xbe, 0xe2, 0x47, 0x40,
+    ];
+
+    #[test]
+    fn read() {
+        assert_eq!(RtpView::HEADER_LEN, 12);
+        let rtp = RtpView::try_new(&SOME_PACKET).unwrap();
+        assert_eq!(rtp.version(), 2);
+        assert!(!rtp.padding());
+        assert!(!rtp.extension());
+        assert_eq!(rtp.csrc_count(), 0);
+        assert!(!rtp.marker());
+        assert_eq!(rtp.payload_type(), 0x21);
+        assert_eq!(rtp.sequence_number(), 9068);
+        assert_eq!(rtp.timestamp(), 1533550728);
+        assert_eq!(rtp.ssrc(), 3009003234);
+        assert_eq!(rtp.payload(), [0x47, 0x40]);
+        assert_eq!(
+            rtp.decode(),
+            Rtp {
+                version: 2,
+                padding: false,
+                extension: false,
+                csrc_count: 0,
+                marker: false,
+                payload_type: 0x21,
+                sequence_number: 9068,
+                timestamp: 1533550728,
+                ssrc: 3009003234,
+            }
+        );
+        assert_eq!(
+            RtpView::try_new(&SOME_PACKET[..11]).unwrap_err().kind(),
+            ErrorKind::NotEnoughData { need: 12, got: 11 }
+        );
+    }
+
+    #[test]
+    fn modify() {
+        let mut data = SOME_PACKET;
+        let mut rtp = RtpViewMut::try_from(data.as_mut_slice()).unwrap();
+        rtp.set_marker(true);
+        rtp.set_csrc_count(15).unwrap();
+        assert_eq!(
+            rtp.set_payload_type(0x80).unwrap_err().kind(),
+            ErrorKind::InvalidValue {
+                field: "payload_type",
+                value: 0x80
+            }
+        );
+        rtp.set_ssrc(0x01020304);
+        let rtp = RtpView::from(rtp);
+        assert_eq!(rtp.version(), 2);
+        assert_eq!(rtp.csrc_count(), 15);
+        assert!(rtp.marker());
+        assert_eq!(rtp.payload_type(), 0x21);
+        assert_eq!(rtp.ssrc(), 0x01020304);
+        assert_eq!(&data[..4], [0x8f, 0xa1, 0x23, 0x6c]);
+    }
+
+    #[test]
+    fn build() {
+        let mut buf = [0xffu8; 16];
+        let builder = RtpBuilder::new()
+            .with_version(2)
+            .with_payload_type(0x21)
+            .with_sequence_number(9068)
+            .with_timestamp(1533550728)
+            .with_ssrc(3009003234);
+        assert_eq!(builder.packet_len(&[0x47, 0x40]), 14);
+        let len = builder.write(&[0x47, 0x40], &mut buf).unwrap();
+        assert_eq!(&buf[..len], SOME_PACKET);
+        assert_eq!(
+            builder
+                .write(&[0x47, 0x40], &mut buf[..13])
+                .unwrap_err()
+                .kind(),
+            ErrorKind::NotEnoughSpace { need: 14, got: 13 }
+        );
+        assert!(builder.with_version(4).write(&[], &mut buf).is_err());
+    }
+
+    #[test]
+    fn build_invalid_leaves_buffer() {
+        let mut buf = [0xffu8; 12];
+        assert_eq!(
+            RtpBuilder::new()
+                .with_version(2)
+                .with_payload_type(0x80)
+                .write(&[], &mut buf)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::InvalidValue {
+                field: "payload_type",
+                value: 0x80
+            }
+        );
+        assert_eq!(buf, [0xff; 12]);
+    }
+
+    #[test]
+    fn large_array() {
+        let mut buf = [0xffu8; 41];
+        assert_eq!(DigestBuilder::new().write(&[], &mut buf).unwrap(), 41);
+        assert_eq!(buf, [0; 41]);
+        DigestBuilder::default()
+            .with_kind(1)
+            .with_digest([2; 40])
+            .write(&[], &mut buf)
+            .unwrap();
+        assert_eq!(DigestView::try_new(&buf).unwrap().digest(), [2; 40]);
+    }
+
+    #[test]
+    fn spanning_fields() {
+        let mut buf = [0u8; 10];
+        let len = FlagsBuilder::new()
+            .with_flag(true)
+            .with_offset(0x1234)
+            .with_mac([1, 2, 3, 4, 5, 6])
+            .write(&[], &mut buf)
+            .unwrap();
+        assert_eq!(len, 10);
+        assert_eq!(buf, [0, 0x40, 0x12, 0x34, 1, 2, 3, 4, 5, 6]);
+        let mut view = FlagsViewMut::try_from(buf.as_mut_slice()).unwrap();
+        view.set_flag(false);
+        assert_eq!(view.as_view().offset(), 0x1234);
+        assert!(view.set_offset(0x2000).is_err());
+        view.set_offset(0x1fff).unwrap();
+        assert!(!view.as_view().flag());
+        assert_eq!(view.as_view().mac(), [1, 2, 3, 4, 5, 6]);
+        assert_eq!(buf[..4], [0, 0, 0x1f, 0xff]);
+    }
+}
@@ -198,11 +198,11 @@ fn sdp_origin_addr<'a, E: ParseError<&'a str> + FromExternalError<&'a str, Error
     ))(input)
 }
 
-fn line<'a, F: 'a, O, E: ParseError<&'a str> + FromExternalError<&'a str, Error>>(
+fn line<'a, F, O, E: ParseError<&'a str> + FromExternalError<&'a str, Error>>(
     inner: F,
 ) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
 where
-    F: FnMut(&'a str) -> IResult<&'a str, O, E>,
+    F: 'a + FnMut(&'a str) -> IResult<&'a str, O, E>,
 {
     terminated(inner, alt((tag("\r\n"), tag("\n"))))
 }
@@ -123,7 +123,7 @@ mod test {
             NonBlockingUdpSocket::bind(SocketAddr::from_str("0.0.0.0:0").unwrap()).unwrap();
         let mut buf = [];
         // should not block and return no data
-        assert!(matches!(socket.try_recv(&mut buf), None));
+        assert!(socket.try_recv(&mut buf).is_none());
     }
 
     #[test]
@@ -152,6 +152,6 @@ mod test {
             },
             _ => panic!(),
         }
-        assert!(matches!(rx.try_recv(&mut rxbuf), None));
+        assert!(rx.try_recv(&mut rxbuf).is_none());
     }
 }
@@ -34,7 +34,7 @@ impl Ipv6Addr {
         // always safe to transmute [u16; 8] to [u8; 16]
         unsafe {
             Self {
-                data: core::mem::transmute([
+                data: core::mem::transmute::<[u16; 8], [u8; 16]>([
                     a.to_be(),
                     b.to_be(),
                     c.to_be(),
@@ -48,8 +48,9 @@ impl Ipv6Addr {
         }
     }
 
-    fn segments(&self) -> [u16; 8] {
-        let [a, b, c, d, e, f, g, h] = unsafe { core::mem::transmute::<_, [u16; 8]>(self.data) };
+    pub fn segments(&self) -> [u16; 8] {
+        let [a, b, c, d, e, f, g, h] =
+            unsafe { core::mem::transmute::<[u8; 16], [u16; 8]>(self.data) };
         [
             u16::from_be(a),
             u16::from_be(b),
@@ -62,7 +63,7 @@ impl Ipv6Addr {
         ]
     }
 
-    fn octets(&self) -> [u8; 16] {
+    pub const fn octets(&self) -> [u8; 16] {
         self.data
     }
 
@@ -71,6 +72,38 @@ impl Ipv6Addr {
     }
 }
 
+impl Display for Ipv6Addr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let segments = self.segments();
+        // find the longest run of at least two zero segments to compress it (RFC5952)
+        let (mut start, mut len) = (0, 0);
+        let mut i = 0;
+        while i < segments.len() {
+            let run = segments[i..].iter().take_while(|s| **s == 0).count();
+            if run > len && run > 1 {
+                (start, len) = (i, run);
+            }
+            i += run.max(1);
+        }
+        let write_segments = |f: &mut core::fmt::Formatter<'_>, s: &[u16]| {
+            s.iter().enumerate().try_for_each(|(i, s)| {
+                if i == 0 {
+                    write!(f, "{s:x}")
+                } else {
+                    write!(f, ":{s:x}")
+                }
+            })
+        };
+        if len == 0 {
+            write_segments(f, &segments)
+        } else {
+            write_segments(f, &segments[..start])?;
+            write!(f, "::")?;
+            write_segments(f, &segments[start + len..])
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Hash, Ord, Clone, Copy)]
 pub struct Ipv4Addr {
     data: [u8; 4],
@@ -1,3 +1,4 @@
 pub mod framerate;
 pub mod timebase;
+pub mod timeline;
 pub mod timestamp;
@@ -2,7 +2,9 @@ use super::{
     framerate::MediaFramerate,
     timestamp::{MediaTimestamp, MediaTimestampPrimitive},
 };
-use crate::traits::math::numbers::{Rational, RationalExt, RationalPrimitive};
+use crate::traits::math::numbers::{
+    div_round, IntegerRationalPrimitive, Rational, RationalExt, RationalPrimitive, Rounding,
+};
 use num_traits::FromPrimitive;
 
 pub trait MediaTimebase<T>: Rational<T>
@@ -25,10 +27,67 @@ where
         }
     }
 
+    /// Convert a timestamp to the target timebase exactly, using 128-bit intermediates and the
+    /// given rounding. Returns `None` if a timebase has a zero numerator or denominator or the
+    /// converted value does not fit into the timestamp type
+    fn convert_timestamp_rounded<K>(
+        &self,
+        ts: MediaTimestamp<K>,
+        target: impl MediaTimebase<T>,
+        rounding: Rounding,
+    ) -> Option<MediaTimestamp<K>>
+    where
+        T: IntegerRationalPrimitive,
+        K: MediaTimestampPrimitive + FromPrimitive,
+    {
+        if self.numerator() == T::zero()
+            || self.denominator() == T::zero()
+            || target.numerator() == T::zero()
+            || target.denominator() == T::zero()
+        {
+            return None;
+        }
+        let num = ts.value().to_i128()? * self.numerator().widen() * target.denominator().widen();
+        let den = self.denominator().widen() * target.numerator().widen();
+        K::from_i128(div_round(num, den, rounding)?).map(MediaTimestamp::new)
+    }
+
+    /// Convert a timestamp to the target timebase, even if the timebases are not multiples of
+    /// each other, rounding towards zero. Returns `None` if the conversion fails
     fn convert_timestamp_unchecked<K>(
         &self,
         ts: MediaTimestamp<K>,
         target: impl MediaTimebase<T>,
+    ) -> Option<MediaTimestamp<K>>
+    where
+        T: IntegerRationalPrimitive,
+        K: MediaTimestampPrimitive + FromPrimitive,
+    {
+        self.convert_timestamp_rounded(ts, target, Rounding::Truncate)
+    }
+
+    /// Convert a timestamp to the target timebase, rounding towards zero. Returns `None` if the
+    /// timebases are not multiples of each other or the conversion fails
+    fn convert_timestamp<K>(
+        &self,
+        ts: MediaTimestamp<K>,
+        target: impl MediaTimebase<T> + Copy,
+    ) -> Option<MediaTimestamp<K>>
+    where
+        T: IntegerRationalPrimitive,
+        K: MediaTimestampPrimitive + FromPrimitive,
+    {
+        self.can_convert(target)
+            .then(|| self.convert_timestamp_rounded(ts, target, Rounding::Truncate))
+            .flatten()
+    }
+
+    /// Like [MediaTimebase::convert_timestamp_unchecked], but computes through `f64`. Also
+    /// works for floating point timebases, precision is lost for large timestamps
+    fn convert_timestamp_f64_unchecked<K>(
+        &self,
+        ts: MediaTimestamp<K>,
+        target: impl MediaTimebase<T>,
     ) -> MediaTimestamp<K>
     where
         K: MediaTimestampPrimitive + FromPrimitive,
@@ -42,7 +101,9 @@ where
         )
     }
 
-    fn convert_timestamp<K>(
+    /// Like [MediaTimebase::convert_timestamp], but computes through `f64`. Also works for
+    /// floating point timebases, precision is lost for large timestamps
+    fn convert_timestamp_f64<K>(
         &self,
         ts: MediaTimestamp<K>,
         target: impl MediaTimebase<T> + Copy,
@@ -100,4 +161,65 @@ mod test {
     fn test_to_framerate() {
         assert_eq!(Rate::rational(25, 1), Rate::rational(1, 25).to_framerate());
     }
+
+    #[test]
+    fn test_convert_timestamp() {
+        let ts = MediaTimestamp::new(u32::MAX);
+        let ms = Rate::rational(1u32, 1000);
+        let ticks = Rate::rational(1u32, 90000);
+        assert_eq!(
+            ticks.convert_timestamp(ts, ms),
+            Some(MediaTimestamp::new(47721858))
+        );
+        assert_eq!(
+            ticks.convert_timestamp_rounded(ts, ms, Rounding::Ceil),
+            Some(MediaTimestamp::new(47721859))
+        );
+        // the f64 path rounds 4294967295 * 1000 / 90000 = 47721858.83 the same way
+        assert_eq!(
+            ticks.convert_timestamp_f64(ts, ms),
+            Some(MediaTimestamp::new(47721858))
+        );
+        assert_eq!(ms.convert_timestamp(ts, ticks), None);
+        assert_eq!(
+            Rate::rational(0u32, 1).convert_timestamp_rounded(ts, ms, Rounding::Floor),
+            None
+        );
+        assert_eq!(
+            ticks.convert_timestamp_rounded(ts, Rate::rational(1u32, 0), Rounding::Floor),
+            None
+        );
+        assert_eq!(
+            ticks.convert_timestamp_rounded(ts, Rate::rational(0u32, 1), Rounding::Floor),
+            None
+        );
+    }
+
+    #[test]
+    fn test_convert_timestamp_ntsc() {
+        let field = Rate::rational(1001u32, 60000);
+        let frame = Rate::rational(1001u32, 30000);
+        assert!(field.can_convert(frame));
+        assert_eq!(
+            field.convert_timestamp(MediaTimestamp::new(-3i32), frame),
+            Some(MediaTimestamp::new(-1))
+        );
+        assert_eq!(
+            field.convert_timestamp_rounded(MediaTimestamp::new(-3i32), frame, Rounding::Floor),
+            Some(MediaTimestamp::new(-2))
+        );
+        assert_eq!(
+            frame.convert_timestamp_unchecked(MediaTimestamp::new(1_000_000_007u32), field),
+            Some(MediaTimestamp::new(2_000_000_014))
+        );
+        assert_eq!(
+            frame.convert_timestamp_unchecked(MediaTimestamp::new(u32::MAX), field),
+            None
+        );
+        let ticks = Rate::rational(1u32, 90000);
+        assert_eq!(
+            frame.convert_timestamp_rounded(MediaTimestamp::new(1u32), ticks, Rounding::Nearest),
+            Some(MediaTimestamp::new(3003))
+        );
+    }
 }
@@ -0,0 +1,229 @@
+use crate::time::{ntp::Timestamp, rate::Rate};
+use crate::traits::math::numbers::Rational;
+
+/// Maximum relative deviation of the clock rate estimated from sender reports from the nominal
+/// clock rate. Reports implying a larger deviation are treated as a discontinuity of the sender
+/// clock and restart the estimation
+pub const MAX_RATE_DEVIATION: f64 = 0.01;
+
+/// Number of NTP fraction units per second
+const NTP_FRAC_PER_SEC: f64 = 4294967296.0;
+
+/// A (NTP, RTP) timestamp pair taken from a sender report
+#[derive(Debug, Clone, Copy)]
+struct SenderReport {
+    ntp: Timestamp,
+    rtp: i64,
+}
+
+/// Timeline of a RTP media stream. Unwraps 32-bit RTP timestamps into a monotonic 64-bit
+/// timeline and maps the timeline to wall-clock time using the (NTP, RTP) timestamp pairs of
+/// successive RTCP sender reports.
+///
+/// The clock rate of the sender is estimated from the first and the latest sender report, which
+/// compensates for drift between the sender's media clock and its wall-clock.
+#[derive(Debug, Clone)]
+pub struct MediaTimeline {
+    clock_rate: u32,
+    highest: Option<i64>,
+    reference: Option<SenderReport>,
+    latest: Option<SenderReport>,
+    rate: f64,
+}
+
+impl MediaTimeline {
+    /// Make a new timeline for a media clock running at `clock_rate` Hz. Returns `None` if
+    /// `clock_rate` is 0
+    pub fn new(clock_rate: u32) -> Option<Self> {
+        if clock_rate == 0 {
+            return None;
+        }
+        Some(Self {
+            clock_rate,
+            highest: None,
+            reference: None,
+            latest: None,
+            rate: clock_rate as f64,
+        })
+    }
+
+    /// Nominal clock rate in Hz
+    pub fn clock_rate(&self) -> u32 {
+        self.clock_rate
+    }
+
+    /// Timebase of the timeline, the reciprocal of the clock rate
+    pub fn timebase(&self) -> Rate<u32> {
+        Rate::rational(1, self.clock_rate)
+    }
+
+    /// Clock rate estimated from the sender reports, the nominal clock rate until two
+    /// consistent sender reports have been received
+    pub fn estimated_rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Highest timestamp on the timeline seen so far
+    pub fn highest(&self) -> Option<i64> {
+        self.highest
+    }
+
+    /// Unwrap a 32-bit RTP timestamp onto the timeline. The timestamp is placed within half the
+    /// timestamp range of the highest timestamp seen so far, so reordered timestamps from before
+    /// a wrap-around map to values below the highest timestamp. The first timestamp is placed at
+    /// its own value, timestamps reordered before it may map to negative values
+    pub fn unwrap(&mut self, ts: u32) -> i64 {
+        let extended = match self.highest {
+            Some(highest) => highest + ts.wrapping_sub(highest as u32) as i32 as i64,
+            None => ts as i64,
+        };
+        if self.highest.is_none_or(|highest| extended > highest) {
+            self.highest = Some(extended);
+        }
+        extended
+    }
+
+    /// Add the NTP and RTP timestamps of a sender report. The RTP timestamp is unwrapped onto
+    /// the timeline
+    pub fn push_sender_report(&mut self, ntp: Timestamp, rtp: u32) {
+        let report = SenderReport {
+            ntp,
+            rtp: self.unwrap(rtp),
+        };
+        match self.reference {
+            Some(reference) => {
+                let elapsed = ntp.wrapping_sub(reference.ntp).as_secs_f64();
+                if elapsed > 0. {
+                    let rate = (report.rtp - reference.rtp) as f64 / elapsed;
+                    if (rate / self.clock_rate as f64 - 1.).abs() <= MAX_RATE_DEVIATION {
+                        self.rate = rate;
+                    } else {
+                        self.reference = Some(report);
+                        self.rate = self.clock_rate as f64;
+                    }
+                }
+            }
+            None => self.reference = Some(report),
+        }
+        self.latest = Some(report);
+    }
+
+    /// Map a timestamp on the timeline to wall-clock time. Returns `None` if no sender report
+    /// has been received yet
+    pub fn to_ntp(&self, ts: i64) -> Option<Timestamp> {
+        let latest = self.latest?;
+        let delta = ((ts - latest.rtp) as f64 / self.rate * NTP_FRAC_PER_SEC) as i64;
+        Some(Timestamp::from(
+            u64::from(latest.ntp).wrapping_add(delta as u64),
+        ))
+    }
+
+    /// Map wall-clock time to a timestamp on the timeline. Returns `None` if no sender report
+    /// has been received yet
+    pub fn from_ntp(&self, ntp: Timestamp) -> Option<i64> {
+        let latest = self.latest?;
+        let delta = u64::from(ntp).wrapping_sub(u64::from(latest.ntp)) as i64;
+        Some(latest.rtp + (delta as f64 / NTP_FRAC_PER_SEC * self.rate).round() as i64)
+    }
+
+    /// Convert a timestamp on the timeline to a different timebase, rounding towards negative
+    /// infinity. Returns `None` if the numerator or denominator of `timebase` is 0 or the
+    /// result does not fit into an `i64`
+    pub fn to_timebase(&self, ts: i64, timebase: impl Rational<u32>) -> Option<i64> {
+        if timebase.numerator() == 0 || timebase.denominator() == 0 {
+            return None;
+        }
+        let num = ts as i128 * timebase.denominator() as i128;
+        let den = self.clock_rate as i128 * timebase.numerator() as i128;
+        i64::try_from(num.div_euclid(den)).ok()
+    }
+}
+
+#[allow(unused)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn unwrap() {
+        let mut timeline = MediaTimeline::new(90000).unwrap();
+        assert_eq!(timeline.unwrap(u32::MAX - 10), u32::MAX as i64 - 10);
+        assert_eq!(timeline.unwrap(5), u32::MAX as i64 + 6);
+        // reordered timestamp from before the wrap
+        assert_eq!(timeline.unwrap(u32::MAX - 1), u32::MAX as i64 - 1);
+        assert_eq!(timeline.highest(), Some(u32::MAX as i64 + 6));
+        assert_eq!(timeline.unwrap(10), u32::MAX as i64 + 11);
+
+        let mut timeline = MediaTimeline::new(90000).unwrap();
+        assert_eq!(timeline.unwrap(3), 3);
+        assert_eq!(timeline.unwrap(u32::MAX), -1);
+        assert_eq!(timeline.highest(), Some(3));
+    }
+
+    #[test]
+    fn sender_reports() {
+        let mut timeline = MediaTimeline::new(90000).unwrap();
+        assert!(timeline.to_ntp(0).is_none());
+        let ntp = Timestamp::new(3_900_000_000, 0);
+        timeline.push_sender_report(ntp, 1000);
+        assert_eq!(timeline.to_ntp(1000), Some(ntp));
+        assert_eq!(
+            timeline.to_ntp(91000),
+            Some(Timestamp::new(3_900_000_001, 0))
+        );
+        assert_eq!(
+            timeline.from_ntp(Timestamp::new(3_899_999_999, 0)),
+            Some(-89000)
+        );
+
+        // the sender clock runs 100ppm fast
+        timeline.push_sender_report(Timestamp::new(3_900_000_010, 0), 1000 + 900_090);
+        assert!((timeline.estimated_rate() - 90009.).abs() < 1e-6);
+        let latest = 901_090;
+        assert_eq!(
+            timeline.to_ntp(latest + 90009),
+            Some(Timestamp::new(3_900_000_011, 0))
+        );
+        assert_eq!(
+            timeline.from_ntp(Timestamp::new(3_900_000_012, 0)),
+            Some(latest + 2 * 90009)
+        );
+
+        // a jump of the media clock restarts the estimation
+        timeline.push_sender_report(Timestamp::new(3_900_000_020, 0), 5_000_000);
+        assert_eq!(timeline.estimated_rate(), 90000.);
+        assert_eq!(
+            timeline.to_ntp(5_045_000),
+            Some(Timestamp::new(3_900_000_020, 1 << 31))
+        );
+    }
+
+    #[test]
+    fn sender_reports_across_ntp_era() {
+        let mut timeline = MediaTimeline::new(48000).unwrap();
+        timeline.push_sender_report(Timestamp::new(u32::MAX, 0), u32::MAX - 23999);
+        timeline.push_sender_report(Timestamp::new(1, 0), 72000);
+        assert!((timeline.estimated_rate() - 48000.).abs() < 1e-6);
+        let ts = timeline.unwrap(96000);
+        assert_eq!(ts, (1 << 32) + 96000);
+        assert_eq!(timeline.to_ntp(ts), Some(Timestamp::new(1, 1 << 31)));
+    }
+
+    #[test]
+    fn timebase() {
+        let timeline = MediaTimeline::new(90000).unwrap();
+        assert_eq!(timeline.timebase(), Rate::rational(1, 90000));
+        assert_eq!(
+            timeline.to_timebase(90000, Rate::rational(1, 1000)),
+            Some(1000)
+        );
+        assert_eq!(timeline.to_timebase(-1, Rate::rational(1, 1000)), Some(-1));
+        assert_eq!(
+            timeline.to_timebase(3003, Rate::rational(1001, 30000)),
+            Some(1)
+        );
+        assert_eq!(timeline.to_timebase(90000, Rate::rational(0, 1000)), None);
+        assert_eq!(timeline.to_timebase(90000, Rate::rational(1, 0)), None);
+        assert_eq!(timeline.to_timebase(0, Rate::rational(1, 0)), None);
+        assert!(MediaTimeline::new(0).is_none());
+    }
+}
@@ -4,8 +4,10 @@ use num_traits::FromPrimitive;
 use num_traits::PrimInt;
 
 use crate::time::rate::Rate;
+use crate::traits::math::numbers::IntegerRationalPrimitive;
 use crate::traits::math::numbers::Rational;
 use crate::traits::math::numbers::RationalPrimitive;
+use crate::traits::math::numbers::Rounding;
 
 use super::timebase::MediaTimebase;
 
@@ -75,23 +77,82 @@ where
         }
     }
 
-    #[allow(unused)]
-    fn to_timebase_unchecked(self, timebase: impl MediaTimebase<B> + Copy) -> Self {
+    /// Get the timestamp value in units of the timebase
+    pub fn timestamp(&self) -> MediaTimestamp<T> {
+        self.ts
+    }
+
+    /// Get the timebase of the timestamp
+    pub fn timebase(&self) -> Rate<B> {
+        self.timebase
+    }
+
+    /// Get the framerate of the media
+    pub fn framerate(&self) -> Rate<B> {
+        self.framerate
+    }
+
+    /// Convert the timestamp to another timebase through `f64`, even if the timebases are not
+    /// multiples of each other and precision is lost
+    pub fn to_timebase_f64_unchecked(self, timebase: impl MediaTimebase<B> + Copy) -> Self {
+        Self {
+            ts: self
+                .timebase
+                .convert_timestamp_f64_unchecked(self.ts, timebase),
+            timebase: Rate::new(timebase),
+            framerate: self.framerate,
+        }
+    }
+
+    /// Convert the timestamp to another timebase through `f64`. Returns `None` if the timebases
+    /// are not multiples of each other or the converted value does not fit into the timestamp
+    /// type
+    pub fn to_timebase_f64(self, timebase: impl MediaTimebase<B> + Copy) -> Option<Self> {
+        self.timebase
+            .convert_timestamp_f64(self.ts, timebase)
+            .map(|ts| self.with_timebase(ts, timebase))
+    }
+
+    fn with_timebase(self, ts: MediaTimestamp<T>, timebase: impl Rational<B>) -> Self {
         Self {
-            ts: self.timebase.convert_timestamp_unchecked(self.ts, timebase),
+            ts,
             timebase: Rate::new(timebase),
             framerate: self.framerate,
         }
     }
+}
+
+impl<T, B> ConvertibleMediaTimestamp<T, B>
+where
+    T: MediaTimestampPrimitive + FromPrimitive,
+    B: IntegerRationalPrimitive,
+{
+    /// Convert the timestamp to another timebase exactly with the given rounding. Returns
+    /// `None` if the converted value does not fit into the timestamp type
+    pub fn to_timebase_rounded(
+        self,
+        timebase: impl MediaTimebase<B> + Copy,
+        rounding: Rounding,
+    ) -> Option<Self> {
+        self.timebase
+            .convert_timestamp_rounded(self.ts, timebase, rounding)
+            .map(|ts| self.with_timebase(ts, timebase))
+    }
+
+    /// Convert the timestamp to another timebase, even if the timebases are not multiples of
+    /// each other and precision is lost. Rounds towards zero. Returns `None` if the converted
+    /// value does not fit into the timestamp type
+    pub fn to_timebase_unchecked(self, timebase: impl MediaTimebase<B> + Copy) -> Option<Self> {
+        self.timebase
+            .convert_timestamp_unchecked(self.ts, timebase)
+            .map(|ts| self.with_timebase(ts, timebase))
+    }
 
-    #[allow(unused)]
-    fn to_timebase(self, timebase: impl MediaTimebase<B> + Copy) -> Option<Self> {
+    /// Convert the timestamp to another timebase. Returns `None` if the timebases are not
+    /// multiples of each other or the converted value does not fit into the timestamp type
+    pub fn to_timebase(self, timebase: impl MediaTimebase<B> + Copy) -> Option<Self> {
         self.timebase
             .convert_timestamp(self.ts, timebase)
-            .map(|ts| Self {
-                ts,
-                timebase: Rate::new(timebase),
-                framerate: self.framerate,
-            })
+            .map(|ts| self.with_timebase(ts, timebase))
     }
 }
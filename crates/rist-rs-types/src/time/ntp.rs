@@ -1,4 +1,17 @@
-#[derive(Debug, Clone, Copy)]
+use core::time::Duration;
+
+use rist_rs_macros::cfg_std;
+
+/// Seconds between the NTP epoch (1900-01-01) and the UNIX epoch (1970-01-01)
+pub const UNIX_EPOCH_OFFSET: u64 = 2_208_988_800;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// A 64-bit NTP timestamp (RFC5905) with 32 bits of seconds and 32 bits of fraction.
+///
+/// Ordering compares the raw values and does not take era wrap-around into account, use
+/// [Timestamp::wrapping_sub] to measure the time between two timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Timestamp {
     sec: u32,
     frac: u32,
@@ -26,4 +39,192 @@ impl Timestamp {
     pub fn frac_ms(&self) -> f64 {
         (self.frac as f64) * 1.0e3 / Self::FRAC
     }
+
+    /// Make a timestamp from a duration since the NTP epoch. Seconds wrap around at the end of
+    /// an NTP era
+    pub fn from_duration(d: Duration) -> Self {
+        // round the fraction up so that converting back yields the same number of nanoseconds
+        let frac = ((d.subsec_nanos() as u64) << 32).div_ceil(NANOS_PER_SEC);
+        Self::new(d.as_secs() as u32, frac as u32)
+    }
+
+    /// Get the duration since the start of the NTP era of the timestamp
+    pub fn to_duration(&self) -> Duration {
+        let nanos = (self.frac as u64 * NANOS_PER_SEC) >> 32;
+        Duration::new(self.sec as u64, nanos as u32)
+    }
+
+    /// Make a timestamp from a duration since the UNIX epoch
+    pub fn from_unix_duration(d: Duration) -> Self {
+        Self::from_duration(d + Duration::from_secs(UNIX_EPOCH_OFFSET))
+    }
+
+    /// Get the duration since the UNIX epoch. Timestamps with the most significant bit of the
+    /// seconds cleared are considered to be in NTP era 1 (2036 - 2104, RFC4330 3). Returns
+    /// `None` for timestamps before the UNIX epoch
+    pub fn to_unix_duration(&self) -> Option<Duration> {
+        let era = if self.sec & 0x8000_0000 == 0 {
+            Duration::from_secs(1 << 32)
+        } else {
+            Duration::ZERO
+        };
+        (self.to_duration() + era).checked_sub(Duration::from_secs(UNIX_EPOCH_OFFSET))
+    }
+
+    /// Get the middle 32 bits of the timestamp, 16 bits of seconds and 16 bits of fraction,
+    /// as used for the LSR field of RTCP reception reports (RFC3550 6.4.1)
+    pub fn to_compact(&self) -> u32 {
+        (self.sec << 16) | (self.frac >> 16)
+    }
+
+    /// Make a timestamp from the middle 32 bits. The upper 16 bits of the seconds and the
+    /// lower 16 bits of the fraction are zero
+    pub fn from_compact(compact: u32) -> Self {
+        Self::new(compact >> 16, compact << 16)
+    }
+
+    /// Get the time elapsed from `earlier` to this timestamp, assuming that `earlier` is at most
+    /// one NTP era before this timestamp
+    pub fn wrapping_sub(&self, earlier: Timestamp) -> Duration {
+        let diff = u64::from(*self).wrapping_sub(u64::from(earlier));
+        Self::from(diff).to_duration()
+    }
+}
+
+/// Convert a duration to the compact 16.16 fixed point format, as used for the DLSR field of
+/// RTCP reception reports. Durations exceeding the format saturate
+pub fn compact_from_duration(d: Duration) -> u32 {
+    let compact = (d.as_nanos() << 16) / NANOS_PER_SEC as u128;
+    compact.min(u32::MAX as u128) as u32
+}
+
+/// Convert a value in the compact 16.16 fixed point format to a duration
+pub fn compact_to_duration(compact: u32) -> Duration {
+    Duration::from_nanos((compact as u64 * NANOS_PER_SEC) >> 16)
+}
+
+impl From<u64> for Timestamp {
+    fn from(v: u64) -> Self {
+        Self::new((v >> 32) as u32, v as u32)
+    }
+}
+
+impl From<Timestamp> for u64 {
+    fn from(ts: Timestamp) -> Self {
+        ((ts.sec as u64) << 32) | ts.frac as u64
+    }
+}
+
+cfg_std! {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    impl From<SystemTime> for Timestamp {
+        fn from(t: SystemTime) -> Self {
+            match t.duration_since(UNIX_EPOCH) {
+                Ok(d) => Self::from_unix_duration(d),
+                Err(e) => Self::from_duration(
+                    Duration::from_secs(UNIX_EPOCH_OFFSET).saturating_sub(e.duration()),
+                ),
+            }
+        }
+    }
+
+    impl From<Timestamp> for SystemTime {
+        fn from(ts: Timestamp) -> Self {
+            match ts.to_unix_duration() {
+                Some(d) => UNIX_EPOCH + d,
+                None => {
+                    UNIX_EPOCH - (Duration::from_secs(UNIX_EPOCH_OFFSET) - ts.to_duration())
+                }
+            }
+        }
+    }
+}
+
+#[allow(unused)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn duration() {
+        let d = Duration::new(3_913_056_000, 123_456_789);
+        let ts = Timestamp::from_duration(d);
+        assert_eq!(ts.seconds(), 3_913_056_000);
+        assert_eq!(ts.to_duration(), d);
+        assert_eq!(
+            Timestamp::from_duration(Duration::new(1, 500_000_000)).frac(),
+            1 << 31
+        );
+        assert_eq!(
+            Timestamp::new(0, u32::MAX).to_duration(),
+            Duration::new(0, 999_999_999)
+        );
+    }
+
+    #[test]
+    fn unix() {
+        let ts = Timestamp::from_unix_duration(Duration::ZERO);
+        assert_eq!(ts, Timestamp::new(UNIX_EPOCH_OFFSET as u32, 0));
+        assert_eq!(ts.to_unix_duration(), Some(Duration::ZERO));
+        assert_eq!(
+            Timestamp::new(0, 0).to_unix_duration(),
+            Some(Duration::from_secs((1 << 32) - UNIX_EPOCH_OFFSET))
+        );
+        assert_eq!(Timestamp::new(0x8000_0000, 0).to_unix_duration(), None);
+        // 2036-02-07 06:28:16 UTC, the first second of era 1
+        let d = Duration::from_secs((1 << 32) - UNIX_EPOCH_OFFSET + 1);
+        let ts = Timestamp::from_unix_duration(d);
+        assert_eq!(ts.seconds(), 1);
+        assert_eq!(ts.to_unix_duration(), Some(d));
+    }
+
+    #[test]
+    fn compact() {
+        let ts = Timestamp::new(0x1234_5678, 0x9abc_def0);
+        assert_eq!(ts.to_compact(), 0x5678_9abc);
+        assert_eq!(
+            Timestamp::from_compact(0x5678_9abc),
+            Timestamp::new(0x5678, 0x9abc_0000)
+        );
+        assert_eq!(
+            compact_from_duration(Duration::from_millis(1500)),
+            0x0001_8000
+        );
+        assert_eq!(
+            compact_to_duration(0x0001_8000),
+            Duration::from_millis(1500)
+        );
+        assert_eq!(
+            compact_from_duration(Duration::from_secs(1 << 16)),
+            u32::MAX
+        );
+    }
+
+    #[test]
+    fn wrapping_sub_and_ord() {
+        let a = Timestamp::new(10, 1 << 31);
+        let b = Timestamp::new(12, 0);
+        assert!(a < b);
+        assert_eq!(b.wrapping_sub(a), Duration::from_millis(1500));
+        // across the end of the era
+        let a = Timestamp::new(u32::MAX, 0);
+        let b = Timestamp::new(1, 0);
+        assert!(a > b);
+        assert_eq!(b.wrapping_sub(a), Duration::from_secs(2));
+        assert_eq!(u64::from(a), 0xffff_ffff_0000_0000);
+        assert_eq!(Timestamp::from(u64::from(a)), a);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn system_time() {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        let t = UNIX_EPOCH + Duration::new(1_700_000_000, 250_000_000);
+        let ts = Timestamp::from(t);
+        assert_eq!(ts.seconds() as u64, 1_700_000_000 + UNIX_EPOCH_OFFSET);
+        assert_eq!(ts.frac(), 1 << 30);
+        assert_eq!(SystemTime::from(ts), t);
+        let t = UNIX_EPOCH - Duration::from_secs(10);
+        assert_eq!(SystemTime::from(Timestamp::from(t)), t);
+    }
 }
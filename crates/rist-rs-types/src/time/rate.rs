@@ -1,6 +1,8 @@
-use crate::traits::math::numbers::{Rational, RationalPrimitive};
+use crate::traits::math::numbers::{IntegerRationalPrimitive, Rational, RationalPrimitive};
 use core::{cmp::Ordering, fmt::Display};
 
+/// A rational number, such as a framerate or a timebase. Constructors reduce the fraction to
+/// lowest terms. Rates of integers are compared exactly, rates of floats through their quotient
 #[derive(Clone, Copy, Debug)]
 pub struct Rate<T>
 where
@@ -15,10 +17,7 @@ where
     T: RationalPrimitive,
 {
     fn from(rat: (T, T)) -> Self {
-        Self {
-            num: rat.0,
-            den: rat.1,
-        }
+        Self::rational(rat.0, rat.1)
     }
 }
 
@@ -40,13 +39,17 @@ where
     where
         K: Rational<T>,
     {
-        Self {
-            num: rational.numerator(),
-            den: rational.denominator(),
-        }
+        Self::rational(rational.numerator(), rational.denominator())
     }
 
     pub fn rational(num: T, den: T) -> Self {
+        Self { num, den }.reduced()
+    }
+
+    /// Reduce the fraction to lowest terms, for rates built from the fields directly. Rates
+    /// made by the constructors are already reduced
+    pub fn reduced(self) -> Self {
+        let (num, den) = T::reduce(self.num, self.den);
         Self { num, den }
     }
 }
@@ -65,39 +68,30 @@ where
 impl<T> PartialEq for Rate<T>
 where
     T: RationalPrimitive,
-    T: Into<f64>,
 {
     fn eq(&self, other: &Self) -> bool {
-        if self.denominator() == T::zero() || other.denominator() == T::zero() {
-            false
-        } else {
-            self.to_f64() == other.to_f64()
-        }
+        self.partial_cmp(other) == Some(Ordering::Equal)
     }
 }
 
-impl<T> Eq for Rate<T>
+impl<T> Eq for Rate<T> where T: RationalPrimitive {}
+
+impl<T> PartialOrd for Rate<T>
 where
     T: RationalPrimitive,
-    T: PartialOrd,
-    T: Into<f64>,
 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        T::cmp_fractions((self.num, self.den), (other.num, other.den))
+    }
 }
 
-impl<T> PartialOrd for Rate<T>
+impl<T> Ord for Rate<T>
 where
-    T: RationalPrimitive,
-    T: PartialOrd,
-    f64: From<T>,
+    T: IntegerRationalPrimitive,
 {
-    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
-        if self.num == other.num && self.den == other.den {
-            Some(Ordering::Equal)
-        } else if self.den == other.den {
-            self.num.partial_cmp(&other.num)
-        } else {
-            self.to_f64().partial_cmp(&other.to_f64())
-        }
+    fn cmp(&self, other: &Self) -> Ordering {
+        // integer fractions are totally ordered, see RationalPrimitive::cmp_fractions
+        self.partial_cmp(other).unwrap_or(Ordering::Equal)
     }
 }
 
@@ -108,7 +102,7 @@ mod test {
     #[test]
     fn zero_rate() {
         let rate = Rate::from((1234, 0));
-        assert!(matches!(rate.to_f64_checked(), None));
+        assert!(rate.to_f64_checked().is_none());
     }
 
     #[test]
@@ -125,6 +119,34 @@ mod test {
     fn cmp_float() {
         assert!(Rate::from((60000, 1001)) < Rate::new(60));
     }
+
+    #[test]
+    fn reduced() {
+        let rate = Rate::rational(60000u32, 2002);
+        assert_eq!((rate.num, rate.den), (30000, 1001));
+        assert_eq!((rate.numerator(), rate.denominator()), (30000, 1001));
+        let rate = rate.reduced();
+        assert_eq!((rate.num, rate.den), (30000, 1001));
+        let rate = Rate::from((60000u32, 2002));
+        assert_eq!((rate.num, rate.den), (30000, 1001));
+        let rate = Rate { num: 50, den: 2 };
+        assert_eq!((rate.num, rate.den), (50, 2));
+        assert_eq!(rate.reduced().num, 25);
+    }
+
+    #[test]
+    fn cmp_exact() {
+        // equal as f64, but not as rationals
+        let a = Rate::rational(u32::MAX, u32::MAX - 1);
+        let b = Rate::rational(u32::MAX - 1, u32::MAX - 2);
+        assert_eq!(a.to_f64(), b.to_f64());
+        assert_ne!(a, b);
+        assert!(a < b);
+        assert_eq!(a.cmp(&b), Ordering::Less);
+        assert_eq!(Rate { num: 2, den: 4 }, Rate::rational(1, 2));
+        assert_eq!(Rate::rational(1, -2), Rate::rational(-1, 2));
+        assert!(Rate::rational(1, 0) > Rate::rational(i32::MAX, 1));
+    }
 }
 
 impl<T> Rational<T> for Rate<T>
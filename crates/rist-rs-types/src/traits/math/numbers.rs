@@ -1,14 +1,137 @@
-use num_traits::Num;
+use core::cmp::Ordering;
 
-pub trait RationalPrimitive: Num + Copy + PartialOrd + Into<f64> {}
+use num_traits::{Num, PrimInt};
 
-impl<T> RationalPrimitive for T
-where
-    T: Num + Copy + PartialOrd,
-    f64: From<T>,
-{
+pub trait RationalPrimitive: Num + Copy + PartialOrd + Into<f64> {
+    /// Compare the fractions `a.0 / a.1` and `b.0 / b.1`. Returns `None` if the fractions are
+    /// not comparable
+    fn cmp_fractions(a: (Self, Self), b: (Self, Self)) -> Option<Ordering>;
+
+    /// Reduce the fraction `num / den` to lowest terms
+    fn reduce(num: Self, den: Self) -> (Self, Self);
+}
+
+/// Integer primitives, rationals of these are compared and converted exactly
+pub trait IntegerRationalPrimitive: RationalPrimitive + PrimInt {
+    /// Widen the value for overflow free intermediate results
+    fn widen(self) -> i128;
+}
+
+/// Rounding mode of exact divisions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Round towards negative infinity
+    Floor,
+    /// Round towards positive infinity
+    Ceil,
+    /// Round towards zero
+    Truncate,
+    /// Round to the nearest integer, ties away from zero
+    Nearest,
+}
+
+/// Divide `num` by `den` with the given rounding. Returns `None` if `den` is zero
+pub fn div_round(num: i128, den: i128, rounding: Rounding) -> Option<i128> {
+    if den == 0 {
+        return None;
+    }
+    let (num, den) = if den < 0 { (-num, -den) } else { (num, den) };
+    let floor = num.div_euclid(den);
+    let rem = num.rem_euclid(den);
+    Some(match rounding {
+        Rounding::Floor => floor,
+        Rounding::Ceil if rem != 0 => floor + 1,
+        Rounding::Ceil => floor,
+        Rounding::Truncate if rem != 0 && num < 0 => floor + 1,
+        Rounding::Truncate => floor,
+        Rounding::Nearest if rem * 2 > den || (rem * 2 == den && num > 0) => floor + 1,
+        Rounding::Nearest => floor,
+    })
+}
+
+/// Compare two fractions of widened integers. Fractions with a zero denominator are ordered
+/// like the extended reals, with `-x/0` before and `x/0` after all finite fractions, and `0/0`
+/// after everything else
+fn cmp_wide(a: (i128, i128), b: (i128, i128)) -> Ordering {
+    fn normalize((num, den): (i128, i128)) -> (u8, i128, i128) {
+        let (num, den) = if den < 0 { (-num, -den) } else { (num, den) };
+        let class = match (den, num.signum()) {
+            (0, -1) => 0,
+            (0, 1) => 2,
+            (0, _) => 3,
+            _ => 1,
+        };
+        (class, num, den)
+    }
+    let (a_class, a_num, a_den) = normalize(a);
+    let (b_class, b_num, b_den) = normalize(b);
+    match a_class.cmp(&b_class) {
+        Ordering::Equal if a_class == 1 => (a_num * b_den).cmp(&(b_num * a_den)),
+        ord => ord,
+    }
+}
+
+fn gcd(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.abs()
 }
 
+macro_rules! impl_integer_rational_primitive {
+    ($($t:ty),*) => {$(
+        impl RationalPrimitive for $t {
+            fn cmp_fractions(a: (Self, Self), b: (Self, Self)) -> Option<Ordering> {
+                Some(cmp_wide(
+                    (a.0.widen(), a.1.widen()),
+                    (b.0.widen(), b.1.widen()),
+                ))
+            }
+
+            fn reduce(num: Self, den: Self) -> (Self, Self) {
+                let (n, d) = (num.widen(), den.widen());
+                let g = match gcd(n, d) {
+                    0 => return (num, den),
+                    g if d < 0 => -g,
+                    g => g,
+                };
+                match (Self::try_from(n / g), Self::try_from(d / g)) {
+                    (Ok(num), Ok(den)) => (num, den),
+                    // only a gcd of one moving the sign of MIN can overflow
+                    _ => (num, den),
+                }
+            }
+        }
+
+        impl IntegerRationalPrimitive for $t {
+            fn widen(self) -> i128 {
+                self as i128
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_float_rational_primitive {
+    ($($t:ty),*) => {$(
+        impl RationalPrimitive for $t {
+            fn cmp_fractions(a: (Self, Self), b: (Self, Self)) -> Option<Ordering> {
+                if a.1 == 0. || b.1 == 0. {
+                    None
+                } else {
+                    (a.0 / a.1).partial_cmp(&(b.0 / b.1))
+                }
+            }
+
+            fn reduce(num: Self, den: Self) -> (Self, Self) {
+                (num, den)
+            }
+        }
+    )*};
+}
+
+impl_integer_rational_primitive!(u8, u16, u32, i8, i16, i32);
+impl_float_rational_primitive!(f32, f64);
+
 pub trait Rational<T>
 where
     T: RationalPrimitive,
@@ -67,3 +190,51 @@ where
     T: RationalPrimitive,
 {
 }
+
+#[allow(unused)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rounding() {
+        assert_eq!(div_round(7, 2, Rounding::Floor), Some(3));
+        assert_eq!(div_round(-7, 2, Rounding::Floor), Some(-4));
+        assert_eq!(div_round(7, 2, Rounding::Ceil), Some(4));
+        assert_eq!(div_round(-7, 2, Rounding::Ceil), Some(-3));
+        assert_eq!(div_round(-7, 2, Rounding::Truncate), Some(-3));
+        assert_eq!(div_round(7, -2, Rounding::Truncate), Some(-3));
+        assert_eq!(div_round(7, 2, Rounding::Nearest), Some(4));
+        assert_eq!(div_round(-7, 2, Rounding::Nearest), Some(-4));
+        assert_eq!(div_round(-8, 3, Rounding::Nearest), Some(-3));
+        assert_eq!(div_round(6, 2, Rounding::Ceil), Some(3));
+        assert_eq!(div_round(1, 0, Rounding::Floor), None);
+    }
+
+    #[test]
+    fn reduce() {
+        assert_eq!(u32::reduce(60000, 2002), (30000, 1001));
+        assert_eq!(i32::reduce(4, -6), (-2, 3));
+        assert_eq!(i32::reduce(1, i32::MIN), (1, i32::MIN));
+        assert_eq!(i32::reduce(6, 0), (1, 0));
+        assert_eq!(u8::reduce(0, 0), (0, 0));
+        assert_eq!(f64::reduce(2., 4.), (2., 4.));
+    }
+
+    #[test]
+    fn cmp_fractions() {
+        assert_eq!(
+            u32::cmp_fractions((u32::MAX, u32::MAX - 1), (u32::MAX - 1, u32::MAX - 2)),
+            Some(Ordering::Less)
+        );
+        assert_eq!(i32::cmp_fractions((1, -2), (-2, 4)), Some(Ordering::Equal));
+        assert_eq!(
+            i32::cmp_fractions((-1, 0), (i32::MIN, 1)),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            i32::cmp_fractions((1, 0), (i32::MAX, 1)),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(f64::cmp_fractions((1., 0.), (1., 0.)), None);
+    }
+}
@@ -8,8 +8,9 @@ use rist_rs_macros::cfg_std;
 use std::{fmt::Display, time::UNIX_EPOCH};
 
 pub mod derived_clock;
+pub mod ntp;
 
-///
+/// A point in time as returned by a [Clock]
 pub trait TimePoint:
     Sized
     + Clone
@@ -142,7 +143,7 @@ cfg_std! {
         }
 
         fn saturating_duration_since(&self, earlier: Self) -> Duration {
-            self.duration_since(earlier).unwrap_or_else(|_| (Duration::from_secs(0)))
+            self.duration_since(earlier).unwrap_or_else(|_| Duration::from_secs(0))
         }
 
         fn checked_add(&self, duration: Duration) -> Option<Self> {
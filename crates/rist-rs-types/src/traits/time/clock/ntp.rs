@@ -0,0 +1,35 @@
+use super::Clock;
+use crate::time::ntp::Timestamp;
+
+/// Extension for clocks whose time points can be expressed as NTP timestamps, like the
+/// system clock
+pub trait NtpClock: Clock {
+    /// Returns the current time as NTP timestamp
+    fn now_ntp(&self) -> Timestamp;
+}
+
+impl<C> NtpClock for C
+where
+    C: Clock,
+    C::TimePoint: Into<Timestamp>,
+{
+    fn now_ntp(&self) -> Timestamp {
+        self.now().into()
+    }
+}
+
+#[allow(unused)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn system_clock() {
+        use std::time::SystemTime;
+        let clock = super::super::StdSystemClock;
+        let before = Timestamp::from(SystemTime::now());
+        let now = clock.now_ntp();
+        assert!(now >= before);
+        assert!(now.wrapping_sub(before) < core::time::Duration::from_secs(1));
+    }
+}
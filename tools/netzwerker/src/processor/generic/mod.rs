@@ -54,12 +54,12 @@ pub struct GenericProcessor {
 }
 
 impl GenericProcessor {
-    pub async fn try_new<E: 'static, I: ProcessorImplementation<E> + 'static>(
+    pub async fn try_new<E, I: ProcessorImplementation<E> + 'static>(
         ctl: Controller,
         implementation: I,
     ) -> Result<Self>
     where
-        E: Send + Sync,
+        E: Send + Sync + 'static,
     {
         let state = GenericProcessorState::<E, I>::try_new(ctl, implementation)?;
         let (tx, rx) = mpsc::unbounded_channel();
@@ -20,7 +20,7 @@ macro_rules! processor_tracing_scope {
 
 pub(crate) use processor_tracing_scope;
 
-pub fn send_packet_to(seq: &mut Vec<Connector>, packet: Packet) {
+pub fn send_packet_to(seq: &mut [Connector], packet: Packet) {
     if !seq.is_empty() {
         if seq.len() == 1 {
             seq[0].send_packet(packet);
@@ -30,7 +30,7 @@ pub fn send_packet_to(seq: &mut Vec<Connector>, packet: Packet) {
             seq[1].send_packet(p2);
         } else {
             seq.iter_mut()
-                .zip(packet.into_iter())
+                .zip(packet)
                 .for_each(|(input, packet)| input.send_packet(packet));
         }
     } else {